@@ -0,0 +1,2 @@
+DROP INDEX IDX_W_SCHEDULED_CALLS_STATUS;
+DROP TABLE W_SCHEDULED_CALLS;
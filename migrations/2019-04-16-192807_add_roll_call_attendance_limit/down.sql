@@ -0,0 +1 @@
+ALTER TABLE W_ROLL_CALLS DROP COLUMN ATTENDANCE_LIMIT;
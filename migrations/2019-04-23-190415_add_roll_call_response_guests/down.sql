@@ -0,0 +1 @@
+ALTER TABLE W_ROLL_CALL_RESPONSES DROP COLUMN GUESTS;
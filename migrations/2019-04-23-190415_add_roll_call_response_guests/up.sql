@@ -0,0 +1 @@
+ALTER TABLE W_ROLL_CALL_RESPONSES ADD COLUMN GUESTS INTEGER NOT NULL DEFAULT 0;
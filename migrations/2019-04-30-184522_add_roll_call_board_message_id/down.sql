@@ -0,0 +1 @@
+ALTER TABLE W_ROLL_CALLS DROP COLUMN BOARD_MESSAGE_ID;
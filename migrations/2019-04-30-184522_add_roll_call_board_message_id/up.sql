@@ -0,0 +1 @@
+ALTER TABLE W_ROLL_CALLS ADD COLUMN BOARD_MESSAGE_ID BIGINT;
@@ -0,0 +1,8 @@
+CREATE TABLE W_CHAT_SETTINGS
+(
+  CHAT_ID    BIGINT PRIMARY KEY,
+  PIN_CALLS  BOOLEAN   NOT NULL DEFAULT FALSE,
+
+  CREATED_AT TIMESTAMP NOT NULL,
+  UPDATED_AT TIMESTAMP NOT NULL
+);
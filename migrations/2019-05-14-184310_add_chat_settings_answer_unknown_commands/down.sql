@@ -0,0 +1 @@
+ALTER TABLE W_CHAT_SETTINGS DROP COLUMN ANSWER_UNKNOWN_COMMANDS;
@@ -0,0 +1,4 @@
+ALTER TABLE W_CHAT_SETTINGS DROP COLUMN QUIET;
+ALTER TABLE W_CHAT_SETTINGS DROP COLUMN LANGUAGE;
+ALTER TABLE W_CHAT_SETTINGS DROP COLUMN TIMEZONE;
+ALTER TABLE W_CHAT_SETTINGS DROP COLUMN REPLY_STYLE;
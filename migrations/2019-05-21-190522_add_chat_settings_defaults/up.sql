@@ -0,0 +1,4 @@
+ALTER TABLE W_CHAT_SETTINGS ADD COLUMN QUIET BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE W_CHAT_SETTINGS ADD COLUMN LANGUAGE VARCHAR(10) NOT NULL DEFAULT 'en';
+ALTER TABLE W_CHAT_SETTINGS ADD COLUMN TIMEZONE VARCHAR(50) NOT NULL DEFAULT 'UTC';
+ALTER TABLE W_CHAT_SETTINGS ADD COLUMN REPLY_STYLE VARCHAR(100) NOT NULL DEFAULT 'LIST';
@@ -0,0 +1 @@
+DROP TABLE W_CHAT_ALIASES;
@@ -0,0 +1 @@
+ALTER TABLE W_RECURRING_CALLS DROP COLUMN TIMEZONE;
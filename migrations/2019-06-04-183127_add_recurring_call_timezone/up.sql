@@ -0,0 +1 @@
+ALTER TABLE W_RECURRING_CALLS ADD COLUMN TIMEZONE VARCHAR(50) NOT NULL DEFAULT 'UTC';
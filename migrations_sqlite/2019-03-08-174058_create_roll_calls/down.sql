@@ -0,0 +1,2 @@
+DROP INDEX IDX_W_ROLL_CALLS_CHAT_ID;
+DROP TABLE W_ROLL_CALLS;
@@ -0,0 +1,2 @@
+DROP INDEX IDX_W_ROLL_CALL_RESPONSES_ROLL_CALL_ID;
+DROP TABLE W_ROLL_CALL_RESPONSES;
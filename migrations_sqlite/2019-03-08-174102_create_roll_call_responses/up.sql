@@ -0,0 +1,24 @@
+CREATE TABLE W_ROLL_CALL_RESPONSES
+(
+  ID           INTEGER PRIMARY KEY AUTOINCREMENT,
+
+  ROLL_CALL_ID BIGINT       NOT NULL
+    REFERENCES W_ROLL_CALLS (ID)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+
+  UNIQUE_TOKEN VARCHAR(512) NOT NULL,
+
+  USER_ID      BIGINT,
+  USER_NAME    TEXT,
+
+  STATUS       VARCHAR(100) NOT NULL,
+  REASON       TEXT,
+
+  CREATED_AT   TIMESTAMP    NOT NULL,
+  UPDATED_AT   TIMESTAMP    NOT NULL,
+
+  UNIQUE (ROLL_CALL_ID, UNIQUE_TOKEN)
+);
+
+CREATE INDEX IDX_W_ROLL_CALL_RESPONSES_ROLL_CALL_ID ON W_ROLL_CALL_RESPONSES (ROLL_CALL_ID);
@@ -0,0 +1,20 @@
+CREATE TABLE W_SCHEDULED_CALLS
+(
+  ID           INTEGER PRIMARY KEY AUTOINCREMENT,
+  CHAT_ID      BIGINT       NOT NULL,
+  STATUS       VARCHAR(100) NOT NULL,
+  TITLE        TEXT         NOT NULL,
+
+  START_AT     TIMESTAMP    NOT NULL,
+  END_AT       TIMESTAMP,
+
+  ROLL_CALL_ID BIGINT
+    REFERENCES W_ROLL_CALLS (ID)
+      ON DELETE SET NULL
+      ON UPDATE CASCADE,
+
+  CREATED_AT   TIMESTAMP    NOT NULL,
+  UPDATED_AT   TIMESTAMP    NOT NULL
+);
+
+CREATE INDEX IDX_W_SCHEDULED_CALLS_STATUS ON W_SCHEDULED_CALLS (STATUS);
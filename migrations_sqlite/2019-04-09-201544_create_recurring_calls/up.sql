@@ -0,0 +1,15 @@
+CREATE TABLE W_RECURRING_CALLS
+(
+  ID         INTEGER PRIMARY KEY AUTOINCREMENT,
+  CHAT_ID    BIGINT    NOT NULL,
+  TITLE      TEXT      NOT NULL,
+
+  WEEKDAY    INTEGER   NOT NULL,
+  START_TIME TIME      NOT NULL,
+  NEXT_AT    TIMESTAMP NOT NULL,
+
+  CREATED_AT TIMESTAMP NOT NULL,
+  UPDATED_AT TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IDX_W_RECURRING_CALLS_CHAT_ID ON W_RECURRING_CALLS (CHAT_ID);
@@ -0,0 +1 @@
+ALTER TABLE W_ROLL_CALLS ADD COLUMN ATTENDANCE_LIMIT INTEGER;
@@ -0,0 +1,33 @@
+-- SQLite cannot drop columns, so the table is rebuilt without it
+CREATE TABLE W_ROLL_CALL_RESPONSES_OLD
+(
+  ID           INTEGER PRIMARY KEY AUTOINCREMENT,
+
+  ROLL_CALL_ID BIGINT       NOT NULL
+    REFERENCES W_ROLL_CALLS (ID)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+
+  UNIQUE_TOKEN VARCHAR(512) NOT NULL,
+
+  USER_ID      BIGINT,
+  USER_NAME    TEXT,
+
+  STATUS       VARCHAR(100) NOT NULL,
+  REASON       TEXT,
+
+  CREATED_AT   TIMESTAMP    NOT NULL,
+  UPDATED_AT   TIMESTAMP    NOT NULL,
+
+  UNIQUE (ROLL_CALL_ID, UNIQUE_TOKEN)
+);
+
+INSERT INTO W_ROLL_CALL_RESPONSES_OLD (ID, ROLL_CALL_ID, UNIQUE_TOKEN, USER_ID, USER_NAME, STATUS, REASON,
+                                       CREATED_AT, UPDATED_AT)
+SELECT ID, ROLL_CALL_ID, UNIQUE_TOKEN, USER_ID, USER_NAME, STATUS, REASON, CREATED_AT, UPDATED_AT
+FROM W_ROLL_CALL_RESPONSES;
+
+DROP INDEX IDX_W_ROLL_CALL_RESPONSES_ROLL_CALL_ID;
+DROP TABLE W_ROLL_CALL_RESPONSES;
+ALTER TABLE W_ROLL_CALL_RESPONSES_OLD RENAME TO W_ROLL_CALL_RESPONSES;
+CREATE INDEX IDX_W_ROLL_CALL_RESPONSES_ROLL_CALL_ID ON W_ROLL_CALL_RESPONSES (ROLL_CALL_ID);
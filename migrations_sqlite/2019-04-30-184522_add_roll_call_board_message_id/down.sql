@@ -0,0 +1,21 @@
+-- SQLite cannot drop columns, so the table is rebuilt without it
+CREATE TABLE W_ROLL_CALLS_OLD
+(
+  ID               INTEGER PRIMARY KEY AUTOINCREMENT,
+  CHAT_ID          BIGINT       NOT NULL,
+  STATUS           VARCHAR(100) NOT NULL,
+  TITLE            TEXT         NOT NULL,
+  QUIET            BOOLEAN      NOT NULL DEFAULT FALSE,
+
+  CREATED_AT       TIMESTAMP    NOT NULL,
+  UPDATED_AT       TIMESTAMP    NOT NULL,
+  ATTENDANCE_LIMIT INTEGER
+);
+
+INSERT INTO W_ROLL_CALLS_OLD (ID, CHAT_ID, STATUS, TITLE, QUIET, CREATED_AT, UPDATED_AT, ATTENDANCE_LIMIT)
+SELECT ID, CHAT_ID, STATUS, TITLE, QUIET, CREATED_AT, UPDATED_AT, ATTENDANCE_LIMIT FROM W_ROLL_CALLS;
+
+DROP INDEX IDX_W_ROLL_CALLS_CHAT_ID;
+DROP TABLE W_ROLL_CALLS;
+ALTER TABLE W_ROLL_CALLS_OLD RENAME TO W_ROLL_CALLS;
+CREATE INDEX IDX_W_ROLL_CALLS_CHAT_ID ON W_ROLL_CALLS (CHAT_ID);
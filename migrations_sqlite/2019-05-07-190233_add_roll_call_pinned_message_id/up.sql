@@ -0,0 +1 @@
+ALTER TABLE W_ROLL_CALLS ADD COLUMN PINNED_MESSAGE_ID BIGINT;
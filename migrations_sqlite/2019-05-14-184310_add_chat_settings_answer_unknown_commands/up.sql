@@ -0,0 +1 @@
+ALTER TABLE W_CHAT_SETTINGS ADD COLUMN ANSWER_UNKNOWN_COMMANDS BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,17 @@
+-- SQLite cannot drop columns, so the table is rebuilt without them
+CREATE TABLE W_CHAT_SETTINGS_OLD
+(
+  CHAT_ID                 BIGINT PRIMARY KEY,
+  PIN_CALLS               BOOLEAN   NOT NULL DEFAULT FALSE,
+
+  CREATED_AT              TIMESTAMP NOT NULL,
+  UPDATED_AT              TIMESTAMP NOT NULL,
+  ANSWER_UNKNOWN_COMMANDS BOOLEAN   NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO W_CHAT_SETTINGS_OLD (CHAT_ID, PIN_CALLS, CREATED_AT, UPDATED_AT, ANSWER_UNKNOWN_COMMANDS)
+SELECT CHAT_ID, PIN_CALLS, CREATED_AT, UPDATED_AT, ANSWER_UNKNOWN_COMMANDS
+FROM W_CHAT_SETTINGS;
+
+DROP TABLE W_CHAT_SETTINGS;
+ALTER TABLE W_CHAT_SETTINGS_OLD RENAME TO W_CHAT_SETTINGS;
@@ -0,0 +1,9 @@
+CREATE TABLE W_CHAT_ALIASES
+(
+  CHAT_ID    BIGINT    NOT NULL,
+  ALIAS      VARCHAR   NOT NULL,
+  COMMAND    VARCHAR   NOT NULL,
+
+  CREATED_AT TIMESTAMP NOT NULL,
+  PRIMARY KEY (CHAT_ID, ALIAS)
+);
@@ -0,0 +1,23 @@
+-- SQLite cannot drop columns, so the table is rebuilt without it
+CREATE TABLE W_RECURRING_CALLS_OLD
+(
+  ID         INTEGER PRIMARY KEY AUTOINCREMENT,
+  CHAT_ID    BIGINT    NOT NULL,
+  TITLE      TEXT      NOT NULL,
+
+  WEEKDAY    INTEGER   NOT NULL,
+  START_TIME TIME      NOT NULL,
+  NEXT_AT    TIMESTAMP NOT NULL,
+
+  CREATED_AT TIMESTAMP NOT NULL,
+  UPDATED_AT TIMESTAMP NOT NULL
+);
+
+INSERT INTO W_RECURRING_CALLS_OLD (ID, CHAT_ID, TITLE, WEEKDAY, START_TIME, NEXT_AT, CREATED_AT, UPDATED_AT)
+SELECT ID, CHAT_ID, TITLE, WEEKDAY, START_TIME, NEXT_AT, CREATED_AT, UPDATED_AT
+FROM W_RECURRING_CALLS;
+
+DROP TABLE W_RECURRING_CALLS;
+ALTER TABLE W_RECURRING_CALLS_OLD RENAME TO W_RECURRING_CALLS;
+
+CREATE INDEX IDX_W_RECURRING_CALLS_CHAT_ID ON W_RECURRING_CALLS (CHAT_ID);
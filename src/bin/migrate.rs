@@ -31,7 +31,7 @@ fn main() -> Result<(), failure::Error> {
     let _sentry_guard = sentry::init(settings.sentry.dsn.clone());
     sentry::integrations::panic::register_panic_handler();
 
-    info!("Applying database migrations...");
+    info!("Applying database migrations..."; "backend" => format!("{:?}", settings.database.backend));
     whosinbot::migrate_db(&settings)
         .on_ok(|_| {
             info!("Database was migrated successfully.");
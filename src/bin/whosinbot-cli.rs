@@ -0,0 +1,46 @@
+extern crate failure;
+#[macro_use]
+extern crate slog;
+extern crate slog_async;
+#[macro_use]
+extern crate slog_scope;
+extern crate slog_term;
+extern crate whosinbot;
+
+use whosinbot::bot::WhosInBot;
+use whosinbot::db::InMemoryRepository;
+use whosinbot::repl::ReplTransport;
+use whosinbot::util::result::ResultExt;
+
+fn create_logger() -> slog::Logger {
+    use slog::Drain;
+    use std::sync::Arc;
+
+    let decorator = slog_term::TermDecorator::new().stderr().build();
+    let drain = slog_term::CompactFormat::new(decorator).build().fuse();
+    let drain = slog_async::Async::new(drain).build().fuse();
+    slog::Logger::root(Arc::new(drain), o!())
+}
+
+fn main() -> Result<(), failure::Error> {
+    let _slog_guard = slog_scope::set_global_logger(create_logger());
+
+    let stdin = std::io::stdin();
+    let transport = ReplTransport::new(stdin.lock(), std::io::stdout());
+
+    let bot = if std::env::args().any(|arg| arg == "--memory") {
+        WhosInBot::new(transport, Box::new(InMemoryRepository::new()))
+    } else {
+        let settings = whosinbot::settings::Settings::main().on_err(|error| {
+            error!("Error loading configuration: {}", error; "details" => format!("{:?}", error));
+        })?;
+        let repository = whosinbot::create_repository(&settings.database)?;
+        // the database is shared with the bot, so its calls are kept the same way
+        WhosInBot::new(transport, repository)
+            .with_retention(settings.retention.policies(), settings.retention.interval())
+    };
+
+    bot.run().on_err(|error| {
+        error!("An error has occurred: {}", error; "details" => format!("{:?}", error));
+    })
+}
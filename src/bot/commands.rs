@@ -0,0 +1,2391 @@
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use regex::{Captures, Regex};
+
+use crate::models::{
+    parse_timezone, AttendanceStatus, CallId, CallSelector, ChatAlias, ChatId, RecurrenceId,
+    ReplyStyle, RollCall, UpdateChatSettings, UserId, LANGUAGES,
+};
+
+use super::transport::ChatCommand;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Starts a new call, closing the open calls of the chat unless it runs `alongside` them.
+    StartRollCall {
+        chat_id: ChatId,
+        title: String,
+        alongside: bool,
+    },
+
+    EndRollCall {
+        chat_id: ChatId,
+        selector: CallSelector,
+    },
+
+    ReopenRollCall {
+        chat_id: ChatId,
+    },
+
+    RestartRollCall {
+        chat_id: ChatId,
+        title: String,
+        keep: bool,
+    },
+
+    ScheduleRollCall {
+        chat_id: ChatId,
+        title: String,
+        start_at: NaiveDateTime,
+        end_at: Option<NaiveDateTime>,
+    },
+
+    AddRecurringCall {
+        chat_id: ChatId,
+        title: String,
+        weekday: Weekday,
+        start_time: NaiveTime,
+    },
+
+    ListRecurringCalls {
+        chat_id: ChatId,
+    },
+
+    CancelRecurringCall {
+        chat_id: ChatId,
+        id: RecurrenceId,
+    },
+
+    UpdateTitle {
+        chat_id: ChatId,
+        selector: CallSelector,
+        title: String,
+    },
+
+    UpdateQuiet {
+        chat_id: ChatId,
+        quiet: bool,
+    },
+
+    UpdateLimit {
+        chat_id: ChatId,
+        limit: Option<i32>,
+    },
+
+    UpdateAttendanceSelf {
+        chat_id: ChatId,
+        selector: CallSelector,
+        user_id: UserId,
+        username: String,
+        status: AttendanceStatus,
+        reason: String,
+        guests: i32,
+    },
+
+    UpdateAttendanceFor {
+        chat_id: ChatId,
+        selector: CallSelector,
+        username: String,
+        status: AttendanceStatus,
+        reason: String,
+        guests: i32,
+    },
+
+    UpdateAttendanceForAll {
+        chat_id: ChatId,
+        selector: CallSelector,
+        status: AttendanceStatus,
+        attendees: Vec<Attendee>,
+    },
+
+    RemoveResponseSelf {
+        chat_id: ChatId,
+        selector: CallSelector,
+        user_id: UserId,
+        username: String,
+    },
+
+    RemoveResponseFor {
+        chat_id: ChatId,
+        selector: CallSelector,
+        username: String,
+    },
+
+    GetAllAttendances {
+        chat_id: ChatId,
+        selector: CallSelector,
+    },
+
+    UpdateBoard {
+        chat_id: ChatId,
+        selector: CallSelector,
+        enabled: bool,
+    },
+
+    UpdatePinCalls {
+        chat_id: ChatId,
+        pin_calls: bool,
+    },
+
+    UpdateAnswerUnknown {
+        chat_id: ChatId,
+        answer: bool,
+    },
+
+    ShowSettings {
+        chat_id: ChatId,
+    },
+
+    UpdateSetting {
+        chat_id: ChatId,
+        setting: Setting,
+    },
+
+    ShowHistory {
+        chat_id: ChatId,
+    },
+
+    ShowCall {
+        chat_id: ChatId,
+        call_id: CallId,
+    },
+
+    ListAliases {
+        chat_id: ChatId,
+    },
+
+    AddAlias {
+        chat_id: ChatId,
+        alias: String,
+        command: String,
+    },
+
+    RemoveAlias {
+        chat_id: ChatId,
+        alias: String,
+    },
+
+    ListAvailableCommands {
+        chat_id: ChatId,
+    },
+
+    ShowHelp {
+        chat_id: ChatId,
+        command: String,
+    },
+}
+
+/// Someone whose attendance is set by someone else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attendee {
+    pub username: String,
+    pub reason: String,
+    pub guests: i32,
+}
+
+/// A new value for one of the settings of a chat, as in `/settings quiet on`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Setting {
+    Quiet(bool),
+    Language(String),
+    Timezone(Tz),
+    ReplyStyle(ReplyStyle),
+    PinCalls(bool),
+    AnswerUnknown(bool),
+}
+
+impl Setting {
+    /// The names of the settings, in the order `/settings` shows them.
+    pub const NAMES: [&'static str; 6] = [
+        "quiet",
+        "language",
+        "timezone",
+        "reply_style",
+        "pin",
+        "answer_unknown",
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Quiet(_) => "quiet",
+            Setting::Language(_) => "language",
+            Setting::Timezone(_) => "timezone",
+            Setting::ReplyStyle(_) => "reply_style",
+            Setting::PinCalls(_) => "pin",
+            Setting::AnswerUnknown(_) => "answer_unknown",
+        }
+    }
+
+    pub fn to_update(&self) -> UpdateChatSettings {
+        let update = UpdateChatSettings::new();
+        match *self {
+            Setting::Quiet(quiet) => update.with_quiet(quiet),
+            Setting::Language(ref language) => update.with_language(language),
+            Setting::Timezone(timezone) => update.with_timezone(timezone),
+            Setting::ReplyStyle(reply_style) => update.with_reply_style(reply_style),
+            Setting::PinCalls(pin_calls) => update.with_pin_calls(pin_calls),
+            Setting::AnswerUnknown(answer) => update.with_answer_unknown_commands(answer),
+        }
+    }
+}
+
+impl FromStr for Setting {
+    type Err = CommandParseError;
+
+    fn from_str(text: &str) -> Result<Setting, CommandParseError> {
+        let mut words = text.split_whitespace();
+        let name = words.next().unwrap_or("").to_lowercase();
+        // only the names of timezones are not lowercase, e.g. Europe/Berlin
+        let raw_value = words.collect::<Vec<_>>().join(" ");
+        let value = raw_value.to_lowercase();
+
+        let on_off = |value: &str| match value {
+            "on" => Some(true),
+            "off" => Some(false),
+            _ => None,
+        };
+        let setting = match name.as_str() {
+            "quiet" => on_off(&value).map(Setting::Quiet),
+            "language" => LANGUAGES
+                .iter()
+                .find(|&&language| language == value)
+                .map(|&language| Setting::Language(language.to_owned())),
+            "timezone" => parse_timezone(&raw_value).map(Setting::Timezone),
+            "reply_style" => match value.as_str() {
+                "list" => Some(Setting::ReplyStyle(ReplyStyle::List)),
+                "board" => Some(Setting::ReplyStyle(ReplyStyle::Board)),
+                _ => None,
+            },
+            "pin" => on_off(&value).map(Setting::PinCalls),
+            "answer_unknown" => on_off(&value).map(Setting::AnswerUnknown),
+            _ => return Err(CommandParseError::InvalidSetting(name)),
+        };
+
+        setting.ok_or(CommandParseError::InvalidSettingValue(name))
+    }
+}
+
+#[derive(Debug, Fail, PartialEq, Eq)]
+pub enum CommandParseError {
+    #[fail(display = "missing title")]
+    MissingTitle,
+
+    #[fail(display = "missing username")]
+    MissingUsername,
+
+    #[fail(display = "invalid schedule")]
+    InvalidSchedule,
+
+    #[fail(display = "invalid recurrence")]
+    InvalidRecurrence,
+
+    #[fail(display = "invalid limit")]
+    InvalidLimit,
+
+    #[fail(display = "invalid guests")]
+    InvalidGuests,
+
+    #[fail(display = "invalid call number")]
+    InvalidCallNumber,
+
+    #[fail(display = "unterminated quote")]
+    UnterminatedQuote,
+
+    #[fail(display = "invalid mention ({})", _0)]
+    InvalidMention(String),
+
+    #[fail(display = "Invalid command ({})", _0)]
+    InvalidCommand(String),
+
+    #[fail(display = "Unknown command ({})", _0)]
+    UnknownCommand(String),
+
+    #[fail(display = "invalid setting ({})", _0)]
+    InvalidSetting(String),
+
+    #[fail(display = "invalid value for setting {}", _0)]
+    InvalidSettingValue(String),
+
+    #[fail(display = "invalid alias")]
+    InvalidAlias,
+
+    #[fail(display = "alias taken ({})", _0)]
+    AliasTaken(String),
+
+    #[fail(display = "invalid command for alias ({})", _0)]
+    InvalidAliasCommand(String),
+}
+
+impl Command {
+    /// Parses a command going by any of its names, including the aliases the chat gave it. Only a
+    /// `#<number>` in front of the parameters selects a call, see `from_chat_with_open_calls`.
+    pub fn from_chat(
+        chat_command: ChatCommand,
+        aliases: &[ChatAlias],
+    ) -> Result<Self, CommandParseError> {
+        Self::from_chat_with_open_calls(chat_command, aliases, &[])
+    }
+
+    /// Parses a command like `from_chat`, where a `#<name>` in front of the parameters also
+    /// selects an open call whose title starts with the name. Otherwise it is left in the
+    /// parameters, as in `/in #sometag`.
+    pub fn from_chat_with_open_calls(
+        chat_command: ChatCommand,
+        aliases: &[ChatAlias],
+        open_calls: &[RollCall],
+    ) -> Result<Self, CommandParseError> {
+        let ChatCommand {
+            chat_id,
+            user_id,
+            username,
+            command,
+            command_params,
+            ..
+        } = chat_command;
+
+        let spec = match find_command(&command, aliases) {
+            Some(spec) => spec,
+            None => return Err(CommandParseError::UnknownCommand(command)),
+        };
+
+        let (selector, params) = if spec.selectable {
+            parse_selector(&command_params, open_calls)
+        } else {
+            (CallSelector::Latest, command_params)
+        };
+
+        (spec.parse)(Args {
+            chat_id,
+            user_id,
+            username,
+            command,
+            selector,
+            params,
+        })
+    }
+}
+
+/// The groups commands are listed in by /help, as in the README.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandGroup {
+    Basic,
+    Attendance,
+    Other,
+}
+
+impl CommandGroup {
+    pub const ALL: [CommandGroup; 3] = [
+        CommandGroup::Basic,
+        CommandGroup::Attendance,
+        CommandGroup::Other,
+    ];
+}
+
+/// A command the bot knows, with the other names it goes by and how to use it.
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// The names of the command in the other languages the bot speaks, and the like.
+    pub aliases: &'static [&'static str],
+    pub group: CommandGroup,
+    /// The parameters of the command, as in `/set_in_for <name> [+guests] [reason]`.
+    pub usage: &'static str,
+    /// The id of the description of the command in the message catalogue.
+    pub help: &'static str,
+    pub examples: &'static [&'static str],
+    /// Whether the command acts on the call picked by an optional selector in front of its
+    /// parameters.
+    pub selectable: bool,
+    parse: fn(Args) -> Result<Command, CommandParseError>,
+}
+
+impl CommandSpec {
+    /// Whether the parameters start with a `#<name>` that selects a call if an open call goes by
+    /// that name, which takes the open calls of the chat to parse.
+    pub fn may_select_by_name(&self, params: &str) -> bool {
+        match SELECTOR_REGEX.captures(params) {
+            Some(ref captures) if self.selectable => captures[1].parse::<CallId>().is_err(),
+            _ => false,
+        }
+    }
+}
+
+/// What a command is parsed from, with the selector already split off its parameters.
+struct Args {
+    chat_id: ChatId,
+    user_id: UserId,
+    username: String,
+    /// The name the command was given, which may be an alias.
+    command: String,
+    selector: CallSelector,
+    params: String,
+}
+
+/// Every command the bot knows, in the order they are listed by /help.
+pub const COMMANDS: [CommandSpec; 28] = [
+    CommandSpec {
+        name: "/start_roll_call",
+        aliases: &[],
+        group: CommandGroup::Basic,
+        usage: "/start_roll_call [title] [--alongside]",
+        help: "help.start_roll_call",
+        examples: &[
+            "/start_roll_call Football on Tuesday",
+            "/start_roll_call Dinner on Sunday --alongside",
+        ],
+        selectable: false,
+        parse: parse_start_roll_call,
+    },
+    CommandSpec {
+        name: "/end_roll_call",
+        aliases: &[],
+        group: CommandGroup::Basic,
+        usage: "/end_roll_call",
+        help: "help.end_roll_call",
+        examples: &[],
+        selectable: true,
+        parse: parse_end_roll_call,
+    },
+    CommandSpec {
+        name: "/reopen",
+        aliases: &[],
+        group: CommandGroup::Basic,
+        usage: "/reopen",
+        help: "help.reopen",
+        examples: &[],
+        selectable: false,
+        parse: parse_reopen,
+    },
+    CommandSpec {
+        name: "/restart_roll_call",
+        aliases: &[],
+        group: CommandGroup::Basic,
+        usage: "/restart_roll_call [title] [--keep]",
+        help: "help.restart_roll_call",
+        examples: &["/restart_roll_call", "/restart_roll_call Football --keep"],
+        selectable: false,
+        parse: parse_restart_roll_call,
+    },
+    CommandSpec {
+        name: "/schedule_roll_call",
+        aliases: &[],
+        group: CommandGroup::Basic,
+        usage: "/schedule_roll_call <date> <HH:MM>[-HH:MM] <title>",
+        help: "help.schedule_roll_call",
+        examples: &[
+            "/schedule_roll_call 2019-03-20 18:00 Football",
+            "/schedule_roll_call 2019-03-20 18:00-20:00 Football",
+        ],
+        selectable: false,
+        parse: parse_schedule_roll_call,
+    },
+    CommandSpec {
+        name: "/recurring",
+        aliases: &[],
+        group: CommandGroup::Basic,
+        usage: "/recurring weekly <day> <HH:MM> <title> | list | cancel <number>",
+        help: "help.recurring",
+        examples: &[
+            "/recurring weekly tue 18:00 Football",
+            "/recurring list",
+            "/recurring cancel 1",
+        ],
+        selectable: false,
+        parse: parse_recurring,
+    },
+    CommandSpec {
+        name: "/set_title",
+        aliases: &[],
+        group: CommandGroup::Basic,
+        usage: "/set_title <title>",
+        help: "help.set_title",
+        examples: &["/set_title Football on Tuesday"],
+        selectable: true,
+        parse: parse_set_title,
+    },
+    CommandSpec {
+        name: "/shh",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/shh",
+        help: "help.shh",
+        examples: &[],
+        selectable: false,
+        parse: parse_shh,
+    },
+    CommandSpec {
+        name: "/louder",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/louder",
+        help: "help.louder",
+        examples: &[],
+        selectable: false,
+        parse: parse_louder,
+    },
+    CommandSpec {
+        name: "/set_limit",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/set_limit <number|none>",
+        help: "help.set_limit",
+        examples: &["/set_limit 10", "/set_limit none"],
+        selectable: false,
+        parse: parse_set_limit,
+    },
+    CommandSpec {
+        name: "/in",
+        aliases: &["/dabei", "/co"],
+        group: CommandGroup::Attendance,
+        usage: "/in [+guests] [reason]",
+        help: "help.in",
+        examples: &["/in", "/in +2 bringing the kids"],
+        selectable: true,
+        parse: parse_in,
+    },
+    CommandSpec {
+        name: "/out",
+        aliases: &["/nein", "/khong"],
+        group: CommandGroup::Attendance,
+        usage: "/out [reason]",
+        help: "help.out",
+        examples: &["/out", "/out sick"],
+        selectable: true,
+        parse: parse_out,
+    },
+    CommandSpec {
+        name: "/maybe",
+        aliases: &["/vielleicht", "/cothe"],
+        group: CommandGroup::Attendance,
+        usage: "/maybe [+guests] [reason]",
+        help: "help.maybe",
+        examples: &["/maybe", "/maybe running late"],
+        selectable: true,
+        parse: parse_maybe,
+    },
+    CommandSpec {
+        name: "/set_in_for",
+        aliases: &[],
+        group: CommandGroup::Attendance,
+        usage: "/set_in_for <name> [+guests] [reason]",
+        help: "help.set_in_for",
+        examples: &[
+            "/set_in_for Dave",
+            "/set_in_for \"Mary Jane\" +1 running late",
+            "/set_in_for Dave, Sarah, Tom",
+        ],
+        selectable: true,
+        parse: parse_set_in_for,
+    },
+    CommandSpec {
+        name: "/set_out_for",
+        aliases: &[],
+        group: CommandGroup::Attendance,
+        usage: "/set_out_for <name> [reason]",
+        help: "help.set_out_for",
+        examples: &["/set_out_for Dave sick"],
+        selectable: true,
+        parse: parse_set_out_for,
+    },
+    CommandSpec {
+        name: "/set_maybe_for",
+        aliases: &[],
+        group: CommandGroup::Attendance,
+        usage: "/set_maybe_for <name> [+guests] [reason]",
+        help: "help.set_maybe_for",
+        examples: &["/set_maybe_for Dave +1"],
+        selectable: true,
+        parse: parse_set_maybe_for,
+    },
+    CommandSpec {
+        name: "/remove_me",
+        aliases: &[],
+        group: CommandGroup::Attendance,
+        usage: "/remove_me",
+        help: "help.remove_me",
+        examples: &[],
+        selectable: true,
+        parse: parse_remove_me,
+    },
+    CommandSpec {
+        name: "/remove_for",
+        aliases: &[],
+        group: CommandGroup::Attendance,
+        usage: "/remove_for <name>",
+        help: "help.remove_for",
+        examples: &["/remove_for Dave"],
+        selectable: true,
+        parse: parse_remove_for,
+    },
+    CommandSpec {
+        name: "/whos_in",
+        aliases: &[],
+        group: CommandGroup::Attendance,
+        usage: "/whos_in",
+        help: "help.whos_in",
+        examples: &[],
+        selectable: true,
+        parse: parse_whos_in,
+    },
+    CommandSpec {
+        name: "/board",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/board [on|off]",
+        help: "help.board",
+        examples: &["/board", "/board off"],
+        selectable: true,
+        parse: parse_board,
+    },
+    CommandSpec {
+        name: "/pin",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/pin [on|off]",
+        help: "help.pin",
+        examples: &["/pin on"],
+        selectable: false,
+        parse: parse_pin,
+    },
+    CommandSpec {
+        name: "/answer_unknown",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/answer_unknown [on|off]",
+        help: "help.answer_unknown",
+        examples: &["/answer_unknown off"],
+        selectable: false,
+        parse: parse_answer_unknown,
+    },
+    CommandSpec {
+        name: "/settings",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/settings [<name> <value>]",
+        help: "help.settings",
+        examples: &[
+            "/settings",
+            "/settings language de",
+            "/settings timezone Europe/Berlin",
+        ],
+        selectable: false,
+        parse: parse_settings,
+    },
+    CommandSpec {
+        name: "/alias",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/alias [list | add <alias> <command> | remove <alias>]",
+        help: "help.alias",
+        examples: &["/alias add /yes /in", "/alias remove /yes"],
+        selectable: false,
+        parse: parse_alias,
+    },
+    CommandSpec {
+        name: "/history",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/history",
+        help: "help.history",
+        examples: &[],
+        selectable: false,
+        parse: parse_history,
+    },
+    CommandSpec {
+        name: "/show_call",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/show_call <number>",
+        help: "help.show_call",
+        examples: &["/show_call 12"],
+        selectable: false,
+        parse: parse_show_call,
+    },
+    CommandSpec {
+        name: "/help",
+        aliases: &[],
+        group: CommandGroup::Other,
+        usage: "/help [command]",
+        help: "help.help",
+        examples: &["/help", "/help set_in_for"],
+        selectable: false,
+        parse: parse_help,
+    },
+    CommandSpec {
+        name: "/available_commands",
+        aliases: &["/start"],
+        group: CommandGroup::Other,
+        usage: "/available_commands",
+        help: "help.available_commands",
+        examples: &[],
+        selectable: false,
+        parse: parse_available_commands,
+    },
+];
+
+/// Finds the command going by a name. The built-in names come first, so that the aliases of a
+/// chat cannot change what they do.
+pub fn find_command(name: &str, aliases: &[ChatAlias]) -> Option<&'static CommandSpec> {
+    let find = |name: &str| {
+        COMMANDS
+            .iter()
+            .find(|spec| spec.name == name || spec.aliases.contains(&name))
+    };
+
+    find(name).or_else(|| {
+        let alias = aliases.iter().find(|alias| alias.alias == name)?;
+        find(&alias.command)
+    })
+}
+
+fn parse_start_roll_call(args: Args) -> Result<Command, CommandParseError> {
+    let words: Vec<&str> = args.params.split_whitespace().collect();
+    let alongside = words.contains(&"--alongside");
+    let title = if alongside {
+        words
+            .into_iter()
+            .filter(|&word| word != "--alongside")
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        args.params
+    };
+
+    Ok(Command::StartRollCall {
+        chat_id: args.chat_id,
+        title,
+        alongside,
+    })
+}
+
+fn parse_end_roll_call(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::EndRollCall {
+        chat_id: args.chat_id,
+        selector: args.selector,
+    })
+}
+
+fn parse_reopen(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::ReopenRollCall {
+        chat_id: args.chat_id,
+    })
+}
+
+fn parse_restart_roll_call(args: Args) -> Result<Command, CommandParseError> {
+    let words: Vec<&str> = args.params.split_whitespace().collect();
+    let keep = words.contains(&"--keep");
+    let title = words
+        .into_iter()
+        .filter(|&word| word != "--keep")
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok(Command::RestartRollCall {
+        chat_id: args.chat_id,
+        title,
+        keep,
+    })
+}
+
+fn parse_schedule_roll_call(args: Args) -> Result<Command, CommandParseError> {
+    let chat_id = args.chat_id;
+    args.params.parse().map(
+        |Schedule(start_at, end_at, title)| Command::ScheduleRollCall {
+            chat_id,
+            title,
+            start_at,
+            end_at,
+        },
+    )
+}
+
+fn parse_recurring(args: Args) -> Result<Command, CommandParseError> {
+    let chat_id = args.chat_id;
+    args.params.parse().map(|recurrence| match recurrence {
+        Recurrence::Weekly(weekday, start_time, title) => Command::AddRecurringCall {
+            chat_id,
+            title,
+            weekday,
+            start_time,
+        },
+        Recurrence::List => Command::ListRecurringCalls { chat_id },
+        Recurrence::Cancel(id) => Command::CancelRecurringCall { chat_id, id },
+    })
+}
+
+fn parse_set_title(args: Args) -> Result<Command, CommandParseError> {
+    match args.params {
+        ref title if title.is_empty() => Err(CommandParseError::MissingTitle),
+        title => Ok(Command::UpdateTitle {
+            chat_id: args.chat_id,
+            selector: args.selector,
+            title,
+        }),
+    }
+}
+
+fn parse_shh(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::UpdateQuiet {
+        chat_id: args.chat_id,
+        quiet: true,
+    })
+}
+
+fn parse_louder(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::UpdateQuiet {
+        chat_id: args.chat_id,
+        quiet: false,
+    })
+}
+
+fn parse_set_limit(args: Args) -> Result<Command, CommandParseError> {
+    let chat_id = args.chat_id;
+    match args.params.to_lowercase().as_ref() {
+        "none" | "off" | "0" => Ok(Command::UpdateLimit {
+            chat_id,
+            limit: None,
+        }),
+        limit => match limit.parse::<i32>() {
+            Ok(limit) if limit > 0 => Ok(Command::UpdateLimit {
+                chat_id,
+                limit: Some(limit),
+            }),
+            _ => Err(CommandParseError::InvalidLimit),
+        },
+    }
+}
+
+fn attendance_self(args: Args, status: AttendanceStatus) -> Result<Command, CommandParseError> {
+    let Args {
+        chat_id,
+        user_id,
+        username,
+        selector,
+        params,
+        ..
+    } = args;
+    parse_guests_and_reason(&params, status).map(|GuestsAndReason(guests, reason)| {
+        Command::UpdateAttendanceSelf {
+            chat_id,
+            selector,
+            user_id,
+            username,
+            status,
+            reason,
+            guests,
+        }
+    })
+}
+
+fn parse_in(args: Args) -> Result<Command, CommandParseError> {
+    attendance_self(args, AttendanceStatus::In)
+}
+
+fn parse_out(args: Args) -> Result<Command, CommandParseError> {
+    attendance_self(args, AttendanceStatus::Out)
+}
+
+fn parse_maybe(args: Args) -> Result<Command, CommandParseError> {
+    attendance_self(args, AttendanceStatus::Maybe)
+}
+
+fn parse_set_in_for(args: Args) -> Result<Command, CommandParseError> {
+    let (chat_id, selector) = (args.chat_id, args.selector);
+    Attendees::parse(&args.params, AttendanceStatus::In)
+        .map(|attendees| attendance_for(chat_id, selector, AttendanceStatus::In, attendees))
+}
+
+fn parse_set_out_for(args: Args) -> Result<Command, CommandParseError> {
+    let (chat_id, selector) = (args.chat_id, args.selector);
+    Attendees::parse(&args.params, AttendanceStatus::Out)
+        .map(|attendees| attendance_for(chat_id, selector, AttendanceStatus::Out, attendees))
+}
+
+fn parse_set_maybe_for(args: Args) -> Result<Command, CommandParseError> {
+    let (chat_id, selector) = (args.chat_id, args.selector);
+    Attendees::parse(&args.params, AttendanceStatus::Maybe)
+        .map(|attendees| attendance_for(chat_id, selector, AttendanceStatus::Maybe, attendees))
+}
+
+fn parse_remove_me(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::RemoveResponseSelf {
+        chat_id: args.chat_id,
+        selector: args.selector,
+        user_id: args.user_id,
+        username: args.username,
+    })
+}
+
+fn parse_remove_for(args: Args) -> Result<Command, CommandParseError> {
+    let (chat_id, selector) = (args.chat_id, args.selector);
+    args.params
+        .parse()
+        .map(|NameAndReason(username, _)| Command::RemoveResponseFor {
+            chat_id,
+            selector,
+            username,
+        })
+}
+
+fn parse_whos_in(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::GetAllAttendances {
+        chat_id: args.chat_id,
+        selector: args.selector,
+    })
+}
+
+/// Parses the `on` or `off` that switches something, which is on if nothing is given.
+fn parse_on_off(args: &Args) -> Result<bool, CommandParseError> {
+    match args.params.to_lowercase().as_ref() {
+        "" | "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(CommandParseError::InvalidCommand(args.command.clone())),
+    }
+}
+
+fn parse_board(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::UpdateBoard {
+        enabled: parse_on_off(&args)?,
+        chat_id: args.chat_id,
+        selector: args.selector,
+    })
+}
+
+fn parse_pin(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::UpdatePinCalls {
+        chat_id: args.chat_id,
+        pin_calls: parse_on_off(&args)?,
+    })
+}
+
+fn parse_answer_unknown(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::UpdateAnswerUnknown {
+        chat_id: args.chat_id,
+        answer: parse_on_off(&args)?,
+    })
+}
+
+fn parse_settings(args: Args) -> Result<Command, CommandParseError> {
+    let chat_id = args.chat_id;
+    if args.params.is_empty() {
+        return Ok(Command::ShowSettings { chat_id });
+    }
+
+    args.params
+        .parse()
+        .map(|setting| Command::UpdateSetting { chat_id, setting })
+}
+
+lazy_static! {
+    static ref ALIAS_REGEX: Regex =
+        Regex::new(r"^/[a-z0-9_]{1,32}$").expect("Failed to create Regex");
+}
+
+/// Parses a name given to `/alias`, which may leave out the slash.
+fn parse_alias_name(name: &str) -> Result<String, CommandParseError> {
+    let name = format!("/{}", name.trim_start_matches('/').to_lowercase());
+    if ALIAS_REGEX.is_match(&name) {
+        Ok(name)
+    } else {
+        Err(CommandParseError::InvalidAlias)
+    }
+}
+
+fn parse_alias(args: Args) -> Result<Command, CommandParseError> {
+    let chat_id = args.chat_id;
+    let words: Vec<&str> = args.params.split_whitespace().collect();
+    match words.as_slice() {
+        [] | ["list"] => Ok(Command::ListAliases { chat_id }),
+        ["add", alias, command] => {
+            let alias = parse_alias_name(alias)?;
+            if find_command(&alias, &[]).is_some() {
+                return Err(CommandParseError::AliasTaken(alias));
+            }
+            let command = parse_alias_name(command)?;
+            match find_command(&command, &[]) {
+                Some(spec) => Ok(Command::AddAlias {
+                    chat_id,
+                    alias,
+                    command: spec.name.to_owned(),
+                }),
+                None => Err(CommandParseError::InvalidAliasCommand(command)),
+            }
+        }
+        ["remove", alias] => Ok(Command::RemoveAlias {
+            chat_id,
+            alias: parse_alias_name(alias)?,
+        }),
+        _ => Err(CommandParseError::InvalidAlias),
+    }
+}
+
+fn parse_history(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::ShowHistory {
+        chat_id: args.chat_id,
+    })
+}
+
+fn parse_show_call(args: Args) -> Result<Command, CommandParseError> {
+    let number = args.params.trim_start_matches('#');
+    match number.parse::<CallId>() {
+        Ok(call_id) => Ok(Command::ShowCall {
+            chat_id: args.chat_id,
+            call_id,
+        }),
+        Err(_) => Err(CommandParseError::InvalidCallNumber),
+    }
+}
+
+/// Parses `/help` on its own as /available_commands, and `/help set_in_for` as the help of a
+/// command, with or without its slash.
+fn parse_help(args: Args) -> Result<Command, CommandParseError> {
+    let chat_id = args.chat_id;
+    match args.params.split_whitespace().next() {
+        None => Ok(Command::ListAvailableCommands { chat_id }),
+        Some(command) => Ok(Command::ShowHelp {
+            chat_id,
+            command: format!("/{}", command.trim_start_matches('/').to_lowercase()),
+        }),
+    }
+}
+
+fn parse_available_commands(args: Args) -> Result<Command, CommandParseError> {
+    Ok(Command::ListAvailableCommands {
+        chat_id: args.chat_id,
+    })
+}
+
+lazy_static! {
+    static ref SELECTOR_REGEX: Regex =
+        Regex::new(r"^#(\S+)\s*(.*)$").expect("Failed to create Regex");
+}
+
+/// Splits a `#<number>` selector off the parameters, or a `#<name>` one if one of the open calls
+/// goes by that name, selecting the latest call if there is none.
+fn parse_selector(text: &str, open_calls: &[RollCall]) -> (CallSelector, String) {
+    let captures = match SELECTOR_REGEX.captures(text) {
+        Some(captures) => captures,
+        None => return (CallSelector::Latest, text.to_owned()),
+    };
+
+    let selector = match captures[1].parse::<CallId>() {
+        Ok(id) => CallSelector::Id(id),
+        Err(_) => CallSelector::Name(captures[1].to_owned()),
+    };
+
+    match selector {
+        CallSelector::Name(_) if !open_calls.iter().any(|call| selector.matches(call)) => {
+            (CallSelector::Latest, text.to_owned())
+        }
+        selector => (selector, captures[2].to_owned()),
+    }
+}
+
+/// A name followed by an optional reason, as in `/set_in_for Dave running late`. A name of several
+/// words is either quoted, as in `"Mary Jane" running late`, or ends at the first colon followed by
+/// a space, as in `Mary Jane: back at 10:30`, where the rest is the reason as it is. Without such a
+/// colon, the name is the first word. A mention like `@maryjane` is kept as it is.
+#[derive(Debug, PartialEq, Eq)]
+struct NameAndReason(String, String);
+
+lazy_static! {
+    static ref QUOTED_NAME_REGEX: Regex =
+        Regex::new(r#"(?s)^["“]([^"”]*)["”]\s*:?\s*(.*)$"#).expect("Failed to create Regex");
+    static ref MENTION_REGEX: Regex =
+        Regex::new(r"(?s)^(@\S*?):?(?:\s+(.*))?$").expect("Failed to create Regex");
+    static ref VALID_MENTION_REGEX: Regex = Regex::new(r"^@\w+$").expect("Failed to create Regex");
+    static ref SEPARATED_NAME_REGEX: Regex =
+        Regex::new(r"(?s)^([^:]+?)(?:\s+(\+\d+))?:(?:\s+(.*))?$").expect("Failed to create Regex");
+    static ref NAME_REASON_REGEX: Regex =
+        Regex::new(r"(?s)^(\S+)\s*(.*)$").expect("Failed to create Regex");
+}
+
+impl FromStr for NameAndReason {
+    type Err = CommandParseError;
+
+    fn from_str(text: &str) -> Result<NameAndReason, CommandParseError> {
+        let text = text.trim();
+        let (username, reason) = if text.starts_with(&['"', '“'][..]) {
+            let captures = QUOTED_NAME_REGEX
+                .captures(text)
+                .ok_or(CommandParseError::UnterminatedQuote)?;
+            (capture(&captures, 1), capture(&captures, 2))
+        } else if let Some(captures) = MENTION_REGEX.captures(text) {
+            let mention = capture(&captures, 1);
+            if !VALID_MENTION_REGEX.is_match(&mention) {
+                return Err(CommandParseError::InvalidMention(mention));
+            }
+            (mention, capture(&captures, 2))
+        } else if let Some(captures) = SEPARATED_NAME_REGEX.captures(text) {
+            // guests go before the colon, as in `Mary Jane +1: running late`
+            let reason = format!("{} {}", capture(&captures, 2), capture(&captures, 3));
+            (capture(&captures, 1), reason.trim().to_owned())
+        } else if let Some(captures) = NAME_REASON_REGEX.captures(text) {
+            (capture(&captures, 1), capture(&captures, 2))
+        } else {
+            return Err(CommandParseError::MissingUsername);
+        };
+
+        if username.is_empty() {
+            return Err(CommandParseError::MissingUsername);
+        }
+        Ok(NameAndReason(username, reason))
+    }
+}
+
+fn capture(captures: &Captures, index: usize) -> String {
+    captures
+        .get(index)
+        .map_or("", |m| m.as_str())
+        .trim()
+        .to_owned()
+}
+
+/// Sets the attendance of a single person the way it always was, or of several at once.
+fn attendance_for(
+    chat_id: ChatId,
+    selector: CallSelector,
+    status: AttendanceStatus,
+    Attendees(mut attendees): Attendees,
+) -> Command {
+    if attendees.len() > 1 {
+        return Command::UpdateAttendanceForAll {
+            chat_id,
+            selector,
+            status,
+            attendees,
+        };
+    }
+
+    let Attendee {
+        username,
+        reason,
+        guests,
+    } = attendees.remove(0);
+    Command::UpdateAttendanceFor {
+        chat_id,
+        selector,
+        username,
+        status,
+        reason,
+        guests,
+    }
+}
+
+/// One or more people with an optional number of guests and reason each, as in `/set_in_for Dave
+/// +1 running late` or `/set_in_for Dave, Sarah +1, Tom`. Commas and new lines always separate
+/// people, except within a quoted name or after a colon followed by a space, which starts a reason
+/// that runs to the end of the line, as in `/set_in_for Dave: late, sorry`.
+#[derive(Debug, PartialEq, Eq)]
+struct Attendees(Vec<Attendee>);
+
+impl Attendees {
+    fn parse(text: &str, status: AttendanceStatus) -> Result<Attendees, CommandParseError> {
+        let attendees: Result<Vec<Attendee>, _> = split_attendees(text)
+            .into_iter()
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| parse_attendee(entry, status))
+            .collect();
+
+        match attendees {
+            Ok(ref attendees) if attendees.is_empty() => Err(CommandParseError::MissingUsername),
+            attendees => attendees.map(Attendees),
+        }
+    }
+}
+
+/// Splits the text at commas and new lines, leaving out commas within a quoted name or a reason.
+fn split_attendees(text: &str) -> Vec<&str> {
+    let mut entries = vec![];
+    let mut quoted = false;
+    let mut reason = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' | '“' | '”' if !reason => quoted = !quoted,
+            ':' if !quoted && separates_reason(&text[i + 1..]) => reason = true,
+            ',' if !quoted && !reason => {
+                entries.push(&text[start..i]);
+                start = i + 1;
+            }
+            '\n' => {
+                entries.push(&text[start..i]);
+                start = i + 1;
+                quoted = false;
+                reason = false;
+            }
+            _ => {}
+        }
+    }
+    entries.push(&text[start..]);
+    entries
+}
+
+/// Whether a colon followed by `rest` starts a reason, unlike the one in `10:30`.
+fn separates_reason(rest: &str) -> bool {
+    rest.is_empty() || rest.starts_with(char::is_whitespace)
+}
+
+fn parse_attendee(text: &str, status: AttendanceStatus) -> Result<Attendee, CommandParseError> {
+    let NameAndReason(username, reason) = text.parse()?;
+    let GuestsAndReason(guests, reason) = parse_guests_and_reason(&reason, status)?;
+    Ok(Attendee {
+        username,
+        reason,
+        guests,
+    })
+}
+
+/// An optional `+<guests>` followed by the reason of an attendance command, as in `/in +2 bringing
+/// my brothers`.
+#[derive(Debug, PartialEq, Eq)]
+struct GuestsAndReason(i32, String);
+
+const MAX_GUESTS: i32 = 99;
+
+lazy_static! {
+    static ref GUESTS_REASON_REGEX: Regex =
+        Regex::new(r"^\+(\d+)(?:\s+(.*))?$").expect("Failed to create Regex");
+}
+
+/// Only those who are in or maybe bring guests, so `/out +2` is out with the reason `+2`.
+fn parse_guests_and_reason(
+    text: &str,
+    status: AttendanceStatus,
+) -> Result<GuestsAndReason, CommandParseError> {
+    match status {
+        AttendanceStatus::Out => Ok(GuestsAndReason(0, text.to_owned())),
+        _ => text.parse(),
+    }
+}
+
+impl FromStr for GuestsAndReason {
+    type Err = CommandParseError;
+
+    fn from_str(text: &str) -> Result<GuestsAndReason, CommandParseError> {
+        let captures = match GUESTS_REASON_REGEX.captures(text) {
+            Some(captures) => captures,
+            None => return Ok(GuestsAndReason(0, text.to_owned())),
+        };
+
+        let guests = match captures[1].parse() {
+            Ok(guests) if guests <= MAX_GUESTS => guests,
+            _ => return Err(CommandParseError::InvalidGuests),
+        };
+
+        let reason = captures.get(2).map_or("", |reason| reason.as_str());
+        Ok(GuestsAndReason(guests, reason.to_owned()))
+    }
+}
+
+/// The `<date> <start>[-<end>] <title>` parameters of `/schedule_roll_call`. An end time that is
+/// not after the start time is taken to be on the next day.
+#[derive(Debug, PartialEq, Eq)]
+struct Schedule(NaiveDateTime, Option<NaiveDateTime>, String);
+
+lazy_static! {
+    static ref SCHEDULE_REGEX: Regex = Regex::new(
+        r"^(\d{4}-\d{1,2}-\d{1,2})\s+(\d{1,2}:\d{2})(?:\s*-\s*(\d{1,2}:\d{2}))?(?:\s+(.*))?$"
+    )
+    .expect("Failed to create Regex");
+}
+
+impl FromStr for Schedule {
+    type Err = CommandParseError;
+
+    fn from_str(text: &str) -> Result<Schedule, CommandParseError> {
+        let parse_time = |time: &str| NaiveTime::parse_from_str(time, "%H:%M");
+
+        let captures = SCHEDULE_REGEX
+            .captures(text)
+            .ok_or(CommandParseError::InvalidSchedule)?;
+
+        let date = NaiveDate::parse_from_str(&captures[1], "%Y-%m-%d")
+            .map_err(|_| CommandParseError::InvalidSchedule)?;
+        let start_at = parse_time(&captures[2])
+            .map(|time| date.and_time(time))
+            .map_err(|_| CommandParseError::InvalidSchedule)?;
+
+        let end_at = match captures.get(3) {
+            None => None,
+            Some(end) => {
+                let end_at = parse_time(end.as_str())
+                    .map(|time| date.and_time(time))
+                    .map_err(|_| CommandParseError::InvalidSchedule)?;
+
+                if end_at > start_at {
+                    Some(end_at)
+                } else {
+                    Some(end_at + Duration::days(1))
+                }
+            }
+        };
+
+        let title = captures.get(4).map_or("", |title| title.as_str());
+        Ok(Schedule(start_at, end_at, title.to_owned()))
+    }
+}
+
+/// The parameters of `/recurring`, which adds, lists or cancels the recurring calls of a chat.
+#[derive(Debug, PartialEq, Eq)]
+enum Recurrence {
+    Weekly(Weekday, NaiveTime, String),
+    List,
+    Cancel(RecurrenceId),
+}
+
+lazy_static! {
+    static ref RECURRENCE_REGEX: Regex =
+        Regex::new(r"^(?i:weekly)\s+(\S+)\s+(\d{1,2}:\d{2})(?:\s+(.*))?$")
+            .expect("Failed to create Regex");
+}
+
+impl FromStr for Recurrence {
+    type Err = CommandParseError;
+
+    fn from_str(text: &str) -> Result<Recurrence, CommandParseError> {
+        use self::CommandParseError::InvalidRecurrence;
+
+        let mut words = text.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("list"), None, _) => return Ok(Recurrence::List),
+            (Some("cancel"), Some(id), None) => {
+                let id = id.trim_start_matches('#');
+                return id
+                    .parse()
+                    .map(Recurrence::Cancel)
+                    .map_err(|_| InvalidRecurrence);
+            }
+            _ => {}
+        }
+
+        let captures = RECURRENCE_REGEX.captures(text).ok_or(InvalidRecurrence)?;
+        let weekday = captures[1].parse().map_err(|_| InvalidRecurrence)?;
+        let start_time =
+            NaiveTime::parse_from_str(&captures[2], "%H:%M").map_err(|_| InvalidRecurrence)?;
+        let title = captures.get(3).map_or("", |title| title.as_str());
+
+        Ok(Recurrence::Weekly(weekday, start_time, title.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod command_tests {
+        use crate::models::AttendanceStatus::*;
+        use crate::util::testutil::factories::*;
+
+        use super::super::{Command::*, *};
+
+        #[test]
+        fn test_from_start_roll_calll_command() {
+            let input = create_command("/start_roll_call", "some title");
+
+            let expected = Ok(StartRollCall {
+                chat_id: 2,
+                title: "some title".to_string(),
+                alongside: false,
+            });
+
+            let actual = Command::from_chat(input, &[]);
+            assert_eq!(expected, actual);
+
+            let input = create_command("/start_roll_call", "Dinner on Sunday --alongside");
+
+            let expected = Ok(StartRollCall {
+                chat_id: 2,
+                title: "Dinner on Sunday".to_string(),
+                alongside: true,
+            });
+
+            assert_eq!(expected, Command::from_chat(input, &[]));
+        }
+
+        #[test]
+        fn test_from_end_roll_call_command() {
+            let input = create_command("/end_roll_call", "whatever");
+
+            let expected = Ok(EndRollCall {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+            });
+
+            let actual = Command::from_chat(input, &[]);
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_schedule_roll_call_command() {
+            let input = create_command("/schedule_roll_call", "2019-03-20 18:00-20:00 some title");
+
+            let date = NaiveDate::from_ymd(2019, 3, 20);
+            let expected = Ok(ScheduleRollCall {
+                chat_id: 2,
+                title: "some title".to_string(),
+                start_at: date.and_hms(18, 0, 0),
+                end_at: Some(date.and_hms(20, 0, 0)),
+            });
+
+            let actual = Command::from_chat(input, &[]);
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_recurring_command() {
+            let input = create_command("/recurring", "weekly tue 18:00 some title");
+
+            let expected = Ok(AddRecurringCall {
+                chat_id: 2,
+                title: "some title".to_string(),
+                weekday: Weekday::Tue,
+                start_time: NaiveTime::from_hms(18, 0, 0),
+            });
+
+            let actual = Command::from_chat(input, &[]);
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_set_title_command() {
+            let input = create_command("/set_title", "new title");
+
+            let expected = Ok(UpdateTitle {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                title: "new title".to_string(),
+            });
+
+            let actual = Command::from_chat(input, &[]);
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_shh_command() {
+            let input = create_command("/shh", "whatever");
+
+            let expected = Ok(UpdateQuiet {
+                chat_id: 2,
+                quiet: true,
+            });
+
+            let actual = Command::from_chat(input, &[]);
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_louder_command() {
+            let input = create_command("/louder", "whatever");
+
+            let expected = Ok(UpdateQuiet {
+                chat_id: 2,
+                quiet: false,
+            });
+
+            let actual = Command::from_chat(input, &[]);
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_set_limit_command() {
+            let expected = Ok(UpdateLimit {
+                chat_id: 2,
+                limit: Some(10),
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/set_limit", "10"), &[])
+            );
+
+            let expected = Ok(UpdateLimit {
+                chat_id: 2,
+                limit: None,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/set_limit", "none"), &[])
+            );
+
+            assert_eq!(
+                Err(CommandParseError::InvalidLimit),
+                Command::from_chat(create_command("/set_limit", ""), &[])
+            );
+            assert_eq!(
+                Err(CommandParseError::InvalidLimit),
+                Command::from_chat(create_command("/set_limit", "-1"), &[])
+            );
+            assert_eq!(
+                Err(CommandParseError::InvalidLimit),
+                Command::from_chat(create_command("/set_limit", "ten"), &[])
+            );
+        }
+
+        #[test]
+        fn test_from_self_attendance_command() {
+            let input = vec!["/in", "/out", "/maybe"];
+            let expected_status = vec![In, Out, Maybe];
+
+            for (i, cmd) in input.into_iter().enumerate() {
+                let input = create_command(cmd, "my reason");
+
+                let expected = Ok(UpdateAttendanceSelf {
+                    chat_id: 2,
+                    selector: CallSelector::Latest,
+                    user_id: 1,
+                    username: "User 1".to_string(),
+                    status: expected_status[i],
+                    reason: "my reason".to_string(),
+                    guests: 0,
+                });
+
+                let actual = Command::from_chat(input, &[]);
+                assert_eq!(expected, actual);
+            }
+        }
+
+        #[test]
+        fn test_from_attendance_for_command() {
+            let input = vec!["/set_in_for", "/set_out_for", "/set_maybe_for"];
+            let expected_status = vec![In, Out, Maybe];
+
+            for (i, cmd) in input.into_iter().enumerate() {
+                let input = create_command(cmd, "Peter some reason");
+
+                let expected = Ok(UpdateAttendanceFor {
+                    chat_id: 2,
+                    selector: CallSelector::Latest,
+                    username: "Peter".to_string(),
+                    status: expected_status[i],
+                    reason: "some reason".to_string(),
+                    guests: 0,
+                });
+
+                let actual = Command::from_chat(input, &[]);
+                assert_eq!(expected, actual);
+            }
+        }
+
+        #[test]
+        fn test_from_attendance_command_with_guests() {
+            let input = create_command("/in", "+2 bringing my brothers");
+
+            let expected = Ok(UpdateAttendanceSelf {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                user_id: 1,
+                username: "User 1".to_string(),
+                status: In,
+                reason: "bringing my brothers".to_string(),
+                guests: 2,
+            });
+
+            assert_eq!(expected, Command::from_chat(input, &[]));
+
+            let input = create_command("/set_in_for", "Peter +1");
+
+            let expected = Ok(UpdateAttendanceFor {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                username: "Peter".to_string(),
+                status: In,
+                reason: "".to_string(),
+                guests: 1,
+            });
+
+            assert_eq!(expected, Command::from_chat(input, &[]));
+        }
+
+        #[test]
+        fn test_from_out_command_takes_guests_as_reason() {
+            let expected = Ok(UpdateAttendanceSelf {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                user_id: 1,
+                username: "User 1".to_string(),
+                status: Out,
+                reason: "+2".to_string(),
+                guests: 0,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/out", "+2"), &[])
+            );
+
+            let expected = Ok(UpdateAttendanceFor {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                username: "Peter".to_string(),
+                status: Out,
+                reason: "+1 sick".to_string(),
+                guests: 0,
+            });
+            let input = create_command("/set_out_for", "Peter +1 sick");
+            assert_eq!(expected, Command::from_chat(input, &[]));
+        }
+
+        #[test]
+        fn test_from_commands_with_selector() {
+            let input = create_command("/in", "#3 +1 running late");
+
+            let expected = Ok(UpdateAttendanceSelf {
+                chat_id: 2,
+                selector: CallSelector::Id(3),
+                user_id: 1,
+                username: "User 1".to_string(),
+                status: In,
+                reason: "running late".to_string(),
+                guests: 1,
+            });
+
+            assert_eq!(expected, Command::from_chat(input, &[]));
+
+            let input = create_command("/set_title", "#football Football on Sunday");
+
+            let expected = Ok(UpdateTitle {
+                chat_id: 2,
+                selector: CallSelector::Name("football".to_string()),
+                title: "Football on Sunday".to_string(),
+            });
+
+            let open_calls = vec![RollCall {
+                title: "Football on Saturday".to_string(),
+                ..create_call()
+            }];
+            assert_eq!(
+                expected,
+                Command::from_chat_with_open_calls(input, &[], &open_calls)
+            );
+
+            // titles of new calls may start with a '#'
+            let input = create_command("/start_roll_call", "#1 fans");
+
+            let expected = Ok(StartRollCall {
+                chat_id: 2,
+                title: "#1 fans".to_string(),
+                alongside: false,
+            });
+
+            assert_eq!(expected, Command::from_chat(input, &[]));
+        }
+
+        #[test]
+        fn test_from_whosin_command() {
+            let input = create_command("/whos_in", "whatever");
+
+            let expected = Ok(GetAllAttendances {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+            });
+            let actual = Command::from_chat(input, &[]);
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_board_command() {
+            let expected = Ok(UpdateBoard {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                enabled: true,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/board", ""), &[])
+            );
+
+            let expected = Ok(UpdateBoard {
+                chat_id: 2,
+                selector: CallSelector::Id(3),
+                enabled: false,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/board", "#3 off"), &[])
+            );
+
+            assert_eq!(
+                Err(CommandParseError::InvalidCommand("/board".to_string())),
+                Command::from_chat(create_command("/board", "maybe"), &[])
+            );
+        }
+
+        #[test]
+        fn test_from_set_for_command_with_list() {
+            let command = create_command("/set_out_for", "#3 Dave, Sarah");
+
+            let attendee = |username: &str| Attendee {
+                username: username.to_string(),
+                reason: String::new(),
+                guests: 0,
+            };
+            let expected = Ok(UpdateAttendanceForAll {
+                chat_id: 2,
+                selector: CallSelector::Id(3),
+                status: AttendanceStatus::Out,
+                attendees: vec![attendee("Dave"), attendee("Sarah")],
+            });
+            assert_eq!(expected, Command::from_chat(command, &[]));
+        }
+
+        #[test]
+        fn test_from_remove_commands() {
+            let expected = Ok(RemoveResponseSelf {
+                chat_id: 2,
+                selector: CallSelector::Id(3),
+                user_id: 1,
+                username: "User 1".to_string(),
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/remove_me", "#3"), &[])
+            );
+
+            let expected = Ok(RemoveResponseFor {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                username: "Henry".to_string(),
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/remove_for", "Henry"), &[])
+            );
+
+            assert_eq!(
+                Err(CommandParseError::MissingUsername),
+                Command::from_chat(create_command("/remove_for", ""), &[])
+            );
+        }
+
+        #[test]
+        fn test_from_restart_command() {
+            let expected = Ok(RestartRollCall {
+                chat_id: 2,
+                title: String::new(),
+                keep: false,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/restart_roll_call", ""), &[])
+            );
+
+            let expected = Ok(RestartRollCall {
+                chat_id: 2,
+                title: "Football on Friday".to_string(),
+                keep: true,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(
+                    create_command("/restart_roll_call", "Football on Friday --keep"),
+                    &[]
+                )
+            );
+            assert_eq!(
+                expected,
+                Command::from_chat(
+                    create_command("/restart_roll_call", "--keep Football on Friday"),
+                    &[]
+                )
+            );
+        }
+
+        #[test]
+        fn test_from_show_call_command() {
+            let expected = Ok(ShowCall {
+                chat_id: 2,
+                call_id: 12,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/show_call", "12"), &[])
+            );
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/show_call", "#12"), &[])
+            );
+
+            assert_eq!(
+                Err(CommandParseError::InvalidCallNumber),
+                Command::from_chat(create_command("/show_call", ""), &[])
+            );
+            assert_eq!(
+                Err(CommandParseError::InvalidCallNumber),
+                Command::from_chat(create_command("/show_call", "football"), &[])
+            );
+        }
+
+        #[test]
+        fn test_from_pin_command() {
+            let expected = Ok(UpdatePinCalls {
+                chat_id: 2,
+                pin_calls: true,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/pin", "on"), &[])
+            );
+
+            let expected = Ok(UpdatePinCalls {
+                chat_id: 2,
+                pin_calls: false,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/pin", "OFF"), &[])
+            );
+        }
+
+        #[test]
+        fn test_from_settings_command() {
+            assert_eq!(
+                Ok(ShowSettings { chat_id: 2 }),
+                Command::from_chat(create_command("/settings", ""), &[])
+            );
+
+            let expected = Ok(UpdateSetting {
+                chat_id: 2,
+                setting: Setting::Timezone(Tz::Europe__Berlin),
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/settings", "timezone Europe/Berlin"), &[])
+            );
+
+            let expected = Ok(UpdateSetting {
+                chat_id: 2,
+                setting: Setting::ReplyStyle(ReplyStyle::Board),
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/settings", "Reply_Style BOARD"), &[])
+            );
+
+            let expected = Err(CommandParseError::InvalidSettingValue("quiet".to_string()));
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/settings", "quiet maybe"), &[])
+            );
+
+            let expected = Err(CommandParseError::InvalidSetting("colour".to_string()));
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/settings", "colour blue"), &[])
+            );
+        }
+
+        #[test]
+        fn test_from_unknown_command() {
+            let input = create_command("/weather", "London");
+
+            let expected = Err(CommandParseError::UnknownCommand("/weather".to_string()));
+            assert_eq!(expected, Command::from_chat(input, &[]));
+        }
+
+        #[test]
+        fn test_from_start_command() {
+            let input = create_command("/start", "whatever");
+
+            let expected = Ok(ListAvailableCommands { chat_id: 2 });
+            let actual = Command::from_chat(input, &[]);
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_available_command() {
+            let input = create_command("/available_commands", "whatever");
+
+            let expected = Ok(ListAvailableCommands { chat_id: 2 });
+            let actual = Command::from_chat(input, &[]);
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_from_help_command() {
+            let expected = Ok(ListAvailableCommands { chat_id: 2 });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/help", ""), &[])
+            );
+
+            let expected = Ok(ShowHelp {
+                chat_id: 2,
+                command: "/set_in_for".to_string(),
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/help", "set_in_for"), &[])
+            );
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/help", " /Set_In_For "), &[])
+            );
+        }
+
+        #[test]
+        fn test_commands_have_usage_in_their_group() {
+            for spec in COMMANDS.iter() {
+                assert!(spec.usage.starts_with(spec.name), "{}", spec.name);
+                assert_eq!(format!("help.{}", &spec.name[1..]), spec.help);
+                assert!(CommandGroup::ALL.contains(&spec.group));
+            }
+        }
+    }
+
+    mod alias_tests {
+        use chrono::Utc;
+
+        use super::super::*;
+        use crate::util::testutil::factories::*;
+
+        #[test]
+        fn test_from_built_in_and_chat_aliases() {
+            let expected = Ok(Command::UpdateAttendanceSelf {
+                chat_id: 2,
+                selector: CallSelector::Id(3),
+                user_id: 1,
+                username: "User 1".to_string(),
+                status: AttendanceStatus::In,
+                reason: "gerne".to_string(),
+                guests: 0,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/dabei", "#3 gerne"), &[])
+            );
+
+            let aliases = vec![ChatAlias {
+                chat_id: 2,
+                alias: "/yes".to_string(),
+                command: "/in".to_string(),
+                created_at: Utc::now().naive_local(),
+            }];
+            assert_eq!(
+                expected,
+                Command::from_chat(create_command("/yes", "#3 gerne"), &aliases)
+            );
+            assert_eq!(
+                Err(CommandParseError::UnknownCommand("/yes".to_string())),
+                Command::from_chat(create_command("/yes", ""), &[])
+            );
+        }
+
+        #[test]
+        fn test_from_alias_command() {
+            let from_chat = |params| Command::from_chat(create_command("/alias", params), &[]);
+
+            assert_eq!(Ok(Command::ListAliases { chat_id: 2 }), from_chat(""));
+            assert_eq!(Ok(Command::ListAliases { chat_id: 2 }), from_chat("list"));
+
+            let expected = Ok(Command::AddAlias {
+                chat_id: 2,
+                alias: "/yes".to_string(),
+                command: "/in".to_string(),
+            });
+            assert_eq!(expected, from_chat("add /yes /in"));
+            assert_eq!(expected, from_chat("add Yes dabei"));
+
+            let expected = Ok(Command::RemoveAlias {
+                chat_id: 2,
+                alias: "/yes".to_string(),
+            });
+            assert_eq!(expected, from_chat("remove /yes"));
+
+            let taken = Err(CommandParseError::AliasTaken("/dabei".to_string()));
+            assert_eq!(taken, from_chat("add /dabei /out"));
+            let unknown = Err(CommandParseError::InvalidAliasCommand(
+                "/weather".to_string(),
+            ));
+            assert_eq!(unknown, from_chat("add /yes /weather"));
+            assert_eq!(Err(CommandParseError::InvalidAlias), from_chat("add /yes"));
+            assert_eq!(
+                Err(CommandParseError::InvalidAlias),
+                from_chat("add /jä /in")
+            );
+        }
+
+        #[test]
+        fn test_commands_have_unique_names() {
+            let mut names: Vec<&str> = COMMANDS
+                .iter()
+                .flat_map(|spec| spec.aliases.iter().cloned().chain(Some(spec.name)))
+                .collect();
+            let count = names.len();
+            names.sort();
+            names.dedup();
+
+            assert_eq!(count, names.len());
+        }
+    }
+
+    mod selector_tests {
+        use crate::util::testutil::factories::*;
+
+        use super::super::*;
+
+        #[test]
+        fn test_parse_selector() {
+            let open_calls = vec![RollCall {
+                title: "Dinner on Sunday".to_string(),
+                ..create_call()
+            }];
+
+            let expected = (CallSelector::Id(12), "some reason".to_string());
+            assert_eq!(expected, parse_selector("#12 some reason", &[]));
+
+            let expected = (CallSelector::Name("dinner".to_string()), "".to_string());
+            assert_eq!(expected, parse_selector("#dinner", &open_calls));
+
+            let expected = (CallSelector::Latest, "some #reason".to_string());
+            assert_eq!(expected, parse_selector("some #reason", &open_calls));
+        }
+
+        #[test]
+        fn test_tags_are_not_selectors_without_a_call() {
+            let open_calls = vec![RollCall {
+                title: "Dinner on Sunday".to_string(),
+                ..create_call()
+            }];
+
+            let expected = (CallSelector::Latest, "#sometag".to_string());
+            assert_eq!(expected, parse_selector("#sometag", &open_calls));
+            assert_eq!(expected, parse_selector("#sometag", &[]));
+
+            let input = create_command("/in", "#football late");
+            let spec = find_command("/in", &[]).unwrap();
+            assert!(spec.may_select_by_name(&input.command_params));
+            assert!(!spec.may_select_by_name("#12 late"));
+
+            let expected = Ok(Command::UpdateAttendanceSelf {
+                chat_id: 2,
+                selector: CallSelector::Latest,
+                user_id: 1,
+                username: "User 1".to_string(),
+                status: AttendanceStatus::In,
+                reason: "#football late".to_string(),
+                guests: 0,
+            });
+            assert_eq!(
+                expected,
+                Command::from_chat_with_open_calls(input, &[], &open_calls)
+            );
+        }
+    }
+
+    mod name_and_reason_tests {
+        use super::super::*;
+
+        #[test]
+        fn test_parse_from_valid_string() {
+            let input = "Peter will come";
+            let expected = Ok(NameAndReason("Peter".to_string(), "will come".to_string()));
+            let actual = input.parse::<NameAndReason>();
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_parse_from_valid_string_without_reason() {
+            let input = "Peter  ";
+            let expected = Ok(NameAndReason("Peter".to_owned(), "".to_owned()));
+            let actual = input.parse::<NameAndReason>();
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_parse_from_invalid_string() {
+            let input = "   ";
+            let expected = Err(CommandParseError::MissingUsername);
+            let actual = input.parse::<NameAndReason>();
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_parse_quoted_name() {
+            let expected = Ok(NameAndReason("Mary Jane".to_owned(), "late".to_owned()));
+            assert_eq!(expected, r#""Mary Jane" late"#.parse::<NameAndReason>());
+            assert_eq!(expected, "“Mary Jane”: late".parse::<NameAndReason>());
+
+            let expected = Err(CommandParseError::UnterminatedQuote);
+            assert_eq!(expected, r#""Mary Jane late"#.parse::<NameAndReason>());
+
+            let expected = Err(CommandParseError::MissingUsername);
+            assert_eq!(expected, r#""  " late"#.parse::<NameAndReason>());
+        }
+
+        #[test]
+        fn test_parse_mention() {
+            let expected = Ok(NameAndReason("@mary_jane".to_owned(), "late".to_owned()));
+            assert_eq!(expected, "@mary_jane late".parse::<NameAndReason>());
+            assert_eq!(expected, "@mary_jane: late".parse::<NameAndReason>());
+
+            let expected = Err(CommandParseError::InvalidMention("@".to_owned()));
+            assert_eq!(expected, "@ late".parse::<NameAndReason>());
+        }
+
+        #[test]
+        fn test_parse_name_before_separator() {
+            let expected = Ok(NameAndReason("Mary Jane".to_owned(), "late".to_owned()));
+            assert_eq!(expected, "Mary Jane: late".parse::<NameAndReason>());
+
+            let expected = Ok(NameAndReason("Mary Jane".to_owned(), "+1 late".to_owned()));
+            assert_eq!(expected, "Mary Jane +1: late".parse::<NameAndReason>());
+
+            let expected = Ok(NameAndReason("Tom".to_owned(), "sick".to_owned()));
+            assert_eq!(expected, "Tom: sick".parse::<NameAndReason>());
+
+            let expected = Ok(NameAndReason("mary jane".to_owned(), "late".to_owned()));
+            assert_eq!(expected, "mary jane : late".parse::<NameAndReason>());
+
+            // the name ends at the first colon, the reason is the rest as it is
+            let expected = Ok(NameAndReason(
+                "Mary Jane".to_owned(),
+                "late: 5min".to_owned(),
+            ));
+            assert_eq!(expected, "Mary Jane: late: 5min".parse::<NameAndReason>());
+
+            let expected = Ok(NameAndReason(
+                "Mary Jane".to_owned(),
+                "back at 10:30".to_owned(),
+            ));
+            assert_eq!(
+                expected,
+                "Mary Jane: back at 10:30".parse::<NameAndReason>()
+            );
+
+            let expected = Ok(NameAndReason(
+                "Mary Jane".to_owned(),
+                "back at 10:30".to_owned(),
+            ));
+            assert_eq!(
+                expected,
+                r#""Mary Jane": back at 10:30"#.parse::<NameAndReason>()
+            );
+
+            let expected = Ok(NameAndReason(
+                "Peter".to_owned(),
+                "back at 10:30".to_owned(),
+            ));
+            assert_eq!(expected, "Peter back at 10:30".parse::<NameAndReason>());
+        }
+    }
+
+    mod attendees_tests {
+        use super::super::*;
+        use crate::models::AttendanceStatus::{In, Out};
+
+        fn attendee(username: &str, reason: &str, guests: i32) -> Attendee {
+            Attendee {
+                username: username.to_string(),
+                reason: reason.to_string(),
+                guests,
+            }
+        }
+
+        #[test]
+        fn test_parse_single_attendee() {
+            let expected = Ok(Attendees(vec![attendee("Dave", "late, sorry", 1)]));
+            assert_eq!(expected, Attendees::parse("Dave +1: late, sorry", In));
+
+            let expected = Ok(Attendees(vec![attendee("Dave", "", 0)]));
+            assert_eq!(expected, Attendees::parse("Dave,", In));
+
+            let expected = Ok(Attendees(vec![attendee("Dave", "because reasons", 0)]));
+            assert_eq!(expected, Attendees::parse("Dave: because reasons", In));
+        }
+
+        #[test]
+        fn test_parse_commas_always_separate_people() {
+            let expected = Ok(Attendees(vec![
+                attendee("dave", "", 0),
+                attendee("sarah", "jones", 0),
+            ]));
+            assert_eq!(expected, Attendees::parse("dave, sarah jones", In));
+
+            let expected = Ok(Attendees(vec![
+                attendee("Dave", "", 0),
+                attendee("Tom", "running late", 0),
+            ]));
+            assert_eq!(expected, Attendees::parse("Dave, Tom running late", In));
+
+            let expected = Ok(Attendees(vec![
+                attendee("Dave", "late, sorry", 0),
+                attendee("Tom", "", 0),
+            ]));
+            assert_eq!(expected, Attendees::parse("Dave: late, sorry\nTom", In));
+
+            let expected = Ok(Attendees(vec![
+                attendee("Peter", "back at 10:30", 0),
+                attendee("Tom", "", 0),
+            ]));
+            assert_eq!(expected, Attendees::parse("Peter back at 10:30, Tom", In));
+        }
+
+        #[test]
+        fn test_parse_list_of_attendees() {
+            let expected = Ok(Attendees(vec![
+                attendee("Dave", "", 0),
+                attendee("Sarah", "", 2),
+                attendee("Tom", "running late", 0),
+            ]));
+            assert_eq!(
+                expected,
+                Attendees::parse("Dave, Sarah +2,Tom running late", In)
+            );
+            assert_eq!(
+                expected,
+                Attendees::parse("Dave\nSarah +2\n\nTom running late\n", In)
+            );
+        }
+
+        #[test]
+        fn test_parse_list_with_quoted_names() {
+            let expected = Ok(Attendees(vec![
+                attendee("Smith, John", "", 0),
+                attendee("Mary Jane", "", 1),
+            ]));
+            assert_eq!(
+                expected,
+                Attendees::parse(r#""Smith, John", Mary Jane +1:"#, In)
+            );
+        }
+
+        #[test]
+        fn test_parse_invalid_attendees() {
+            let expected = Err(CommandParseError::MissingUsername);
+            assert_eq!(expected, Attendees::parse(" , ", In));
+
+            let expected = Err(CommandParseError::InvalidGuests);
+            assert_eq!(expected, Attendees::parse("Dave, Sarah +100", In));
+
+            let expected = Ok(Attendees(vec![attendee("Sarah", "+2", 0)]));
+            assert_eq!(expected, Attendees::parse("Sarah +2", Out));
+        }
+    }
+
+    mod guests_and_reason_tests {
+        use super::super::*;
+
+        #[test]
+        fn test_parse_guests_and_reason() {
+            let expected = Ok(GuestsAndReason(2, "bringing friends".to_string()));
+            assert_eq!(expected, "+2 bringing friends".parse::<GuestsAndReason>());
+
+            let expected = Ok(GuestsAndReason(1, "".to_string()));
+            assert_eq!(expected, "+1".parse::<GuestsAndReason>());
+        }
+
+        #[test]
+        fn test_parse_reason_without_guests() {
+            let expected = Ok(GuestsAndReason(0, "running late".to_string()));
+            assert_eq!(expected, "running late".parse::<GuestsAndReason>());
+
+            let expected = Ok(GuestsAndReason(0, "+2people".to_string()));
+            assert_eq!(expected, "+2people".parse::<GuestsAndReason>());
+        }
+
+        #[test]
+        fn test_parse_too_many_guests() {
+            let expected = Err(CommandParseError::InvalidGuests);
+            assert_eq!(expected, "+100".parse::<GuestsAndReason>());
+            assert_eq!(expected, "+99999999999".parse::<GuestsAndReason>());
+        }
+    }
+
+    mod schedule_tests {
+        use super::super::*;
+
+        fn at(date: &str, time: &str) -> NaiveDateTime {
+            NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+        }
+
+        #[test]
+        fn test_parse_start_time_and_title() {
+            let expected = Ok(Schedule(
+                at("2019-03-20", "18:00"),
+                None,
+                "Football".to_string(),
+            ));
+            let actual = "2019-03-20 18:00 Football".parse::<Schedule>();
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_parse_end_time() {
+            let expected = Ok(Schedule(
+                at("2019-03-20", "18:00"),
+                Some(at("2019-03-20", "20:30")),
+                "Football match".to_string(),
+            ));
+            let actual = "2019-03-20 18:00-20:30 Football match".parse::<Schedule>();
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_parse_end_time_on_next_day() {
+            let expected = Ok(Schedule(
+                at("2019-03-20", "22:00"),
+                Some(at("2019-03-21", "01:00")),
+                "".to_string(),
+            ));
+            let actual = "2019-03-20 22:00 - 1:00".parse::<Schedule>();
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_parse_invalid_schedule() {
+            let expected = Err(CommandParseError::InvalidSchedule);
+            assert_eq!(expected, "".parse::<Schedule>());
+            assert_eq!(expected, "18:00 Football".parse::<Schedule>());
+            assert_eq!(expected, "2019-02-30 18:00 Football".parse::<Schedule>());
+            assert_eq!(expected, "2019-03-20 25:00 Football".parse::<Schedule>());
+            assert_eq!(expected, "2019-03-20 18:00Football".parse::<Schedule>());
+        }
+    }
+
+    mod recurrence_tests {
+        use super::super::*;
+
+        #[test]
+        fn test_parse_weekly() {
+            let expected = Ok(Recurrence::Weekly(
+                Weekday::Tue,
+                NaiveTime::from_hms(18, 0, 0),
+                "Five-a-side football".to_string(),
+            ));
+            assert_eq!(expected, "weekly tue 18:00 Five-a-side football".parse());
+            assert_eq!(
+                expected,
+                "Weekly Tuesday 18:00 Five-a-side football".parse()
+            );
+        }
+
+        #[test]
+        fn test_parse_list_and_cancel() {
+            assert_eq!(Ok(Recurrence::List), "list".parse());
+            assert_eq!(Ok(Recurrence::Cancel(3)), "cancel 3".parse());
+            assert_eq!(Ok(Recurrence::Cancel(3)), "cancel #3".parse());
+        }
+
+        #[test]
+        fn test_parse_invalid_recurrence() {
+            let expected = Err(CommandParseError::InvalidRecurrence);
+            assert_eq!(expected, "".parse::<Recurrence>());
+            assert_eq!(expected, "daily 18:00 Football".parse::<Recurrence>());
+            assert_eq!(
+                expected,
+                "weekly someday 18:00 Football".parse::<Recurrence>()
+            );
+            assert_eq!(expected, "weekly tue 6pm Football".parse::<Recurrence>());
+            assert_eq!(expected, "cancel".parse::<Recurrence>());
+            assert_eq!(expected, "cancel all".parse::<Recurrence>());
+        }
+    }
+}
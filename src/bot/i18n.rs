@@ -0,0 +1,856 @@
+//! The replies of the bot in every language it speaks, looked up by message id by the `Translator`
+//! for the language of the chat being answered. Messages that are missing in a language fall back
+//! to English.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::models::LANGUAGES;
+
+/// Looks up a message with a `Translator`, replacing the `{name}` placeholders in it with the
+/// given values, e.g. `tr!(t, "announce.in", name = "Dave")`.
+macro_rules! tr {
+    ($translator:expr, $id:expr $(, $name:ident = $value:expr)* $(,)*) => {
+        $translator.translate(
+            $id,
+            None,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*],
+        )
+    };
+}
+
+/// Looks up a message like `tr!`, picking its form for the number of things it counts, which
+/// also replaces the `{count}` placeholder, e.g. `tr_n!(t, "guests", 2)`.
+macro_rules! tr_n {
+    ($translator:expr, $id:expr, $count:expr $(, $name:ident = $value:expr)* $(,)*) => {{
+        let count = $count as i64;
+        $translator.translate(
+            $id,
+            Some(count),
+            &[
+                ("count", &count as &dyn ::std::fmt::Display)
+                $(, (stringify!($name), &$value as &dyn ::std::fmt::Display))*
+            ],
+        )
+    }};
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Message {
+    Text(&'static str),
+    /// The forms of a message for one thing and for any other number of things.
+    Plural(&'static str, &'static str),
+}
+
+use self::Message::*;
+
+const EN: &[(&str, Message)] = &[
+    ("commands.header", Text("Available commands:")),
+    ("help.group.basic", Text("Basic commands:")),
+    ("help.group.attendance", Text("Attendance commands:")),
+    ("help.group.other", Text("Other commands:")),
+    ("help.footer", Text("Use /help <command> for more, e.g. /help set_in_for.")),
+    ("help.also", Text("Also: {names}")),
+    ("help.examples", Text("Examples:")),
+    (
+        "help.selectable",
+        Text("Put #12 or #title first to pick another open roll call than the latest."),
+    ),
+    ("help.unknown", Text("There is no command {command}, see /help.")),
+    ("help.see", Text("See /help {command}.")),
+    (
+        "help.start_roll_call",
+        Text("Start a new roll call, with an optional title, ending the open ones unless it runs --alongside them"),
+    ),
+    ("help.end_roll_call", Text("End the current roll call")),
+    ("help.reopen", Text("Reopen the roll call that just ended")),
+    (
+        "help.restart_roll_call",
+        Text("Start a new roll call with the previous title, keeping everyone as Maybe with --keep"),
+    ),
+    (
+        "help.schedule_roll_call",
+        Text("Start a roll call at a time in the timezone of the chat, and optionally end it"),
+    ),
+    ("help.recurring", Text("Start a roll call every week, or list and cancel them")),
+    ("help.set_title", Text("Set the title of the current roll call")),
+    ("help.shh", Text("Stop listing everyone after every response")),
+    ("help.louder", Text("List everyone after every response again")),
+    ("help.set_limit", Text("Limit the number of places, with a waitlist for everyone else")),
+    ("help.in", Text("Let everyone know you'll be attending")),
+    ("help.out", Text("Let everyone know you won't be attending")),
+    ("help.maybe", Text("Let everyone know you might be coming")),
+    ("help.set_in_for", Text("Let everyone know that someone else will be attending")),
+    ("help.set_out_for", Text("Let everyone know that someone else won't be attending")),
+    ("help.set_maybe_for", Text("Let everyone know that someone else might be coming")),
+    ("help.remove_me", Text("Take yourself off the list altogether")),
+    ("help.remove_for", Text("Take someone else off the list")),
+    ("help.whos_in", Text("List everyone's responses")),
+    ("help.board", Text("Post a list of everyone's responses that is kept up to date")),
+    ("help.pin", Text("Pin new roll calls, if the bot may pin messages")),
+    ("help.answer_unknown", Text("Answer unknown commands that don't name the bot")),
+    ("help.settings", Text("Show or change the settings of the chat")),
+    ("help.alias", Text("Give commands other names in this chat")),
+    ("help.history", Text("List the roll calls that have ended")),
+    ("help.show_call", Text("Show everyone's responses to a roll call that has ended")),
+    ("help.help", Text("Show how to use a command")),
+    ("help.available_commands", Text("List the commands")),
+    ("schedule.from_until", Text("Roll call scheduled from {start} until {end} ({timezone}).")),
+    ("schedule.at", Text("Roll call scheduled for {start} ({timezone}).")),
+    ("schedule.passed", Text("That time has already passed.")),
+    ("weekday.mon", Text("Monday")),
+    ("weekday.tue", Text("Tuesday")),
+    ("weekday.wed", Text("Wednesday")),
+    ("weekday.thu", Text("Thursday")),
+    ("weekday.fri", Text("Friday")),
+    ("weekday.sat", Text("Saturday")),
+    ("weekday.sun", Text("Sunday")),
+    ("recurring.every", Text("every {weekday} at {time} ({timezone})")),
+    (
+        "recurring.added",
+        Text("Roll call will start {recurrence}, next on {date}. Use /recurring cancel {id} to stop it."),
+    ),
+    ("recurring.none", Text("No recurring roll calls.")),
+    ("recurring.header", Text("Recurring roll calls:")),
+    ("recurring.line", Text(" #{id} Starts {recurrence}")),
+    ("recurring.line_titled", Text(" #{id} {title}, starts {recurrence}")),
+    ("recurring.cancelled", Text("Recurring roll call cancelled.")),
+    ("recurring.no_such", Text("No such recurring roll call.")),
+    ("call.started", Text("Roll call #{id} started.")),
+    ("call.started_titled", Text("Roll call #{id} started: {title}")),
+    ("call.none", Text("No roll call in progress.")),
+    ("call.no_such", Text("No such roll call in progress.")),
+    ("call.ended", Text("Roll call ended.")),
+    ("error.generic", Text("An error has occurred.")),
+    ("call.no_restart", Text("There is no roll call to restart yet.")),
+    ("call.no_reopen", Text("Only the latest roll call can be reopened, once it has ended.")),
+    ("call.reopened", Text("Roll call reopened.")),
+    ("call.title_set", Text("Roll call title set.")),
+    ("call.quiet", Text("Ok fine, I'll be quiet. 🤐")),
+    ("call.louder", Text("Sure. 😃")),
+    ("button.in", Text("In")),
+    ("button.out", Text("Out")),
+    ("button.maybe", Text("Maybe")),
+    ("announce.in", Plural("{name} is in!", "{name} are in!")),
+    ("announce.out", Plural("{name} is out!", "{name} are out!")),
+    ("announce.maybe", Text("{name} might come!")),
+    ("announce.waitlist", Plural("{name} is on the waitlist!", "{name} are on the waitlist!")),
+    ("names.and", Text("{names} and {last}")),
+    ("response.not_listed", Text("{name} is not on the list.")),
+    ("response.removed", Text("{name} is off the list.")),
+    ("response.promoted", Text("{name} is off the waitlist and in!")),
+    ("limit.set", Text("Limit set to {limit}.")),
+    ("limit.removed", Text("Limit removed.")),
+    (
+        "limit.kept",
+        Text("The {count} people who are in keep their places, newcomers go on the waitlist until there is room."),
+    ),
+    (
+        "limit.guests_refused",
+        Plural(
+            "{name} stays in, but there is no room for {count} more guest.",
+            "{name} stays in, but there is no room for {count} more guests.",
+        ),
+    ),
+    ("board.open", Text("Roll call #{id}")),
+    ("board.open_titled", Text("Roll call #{id}: {title}")),
+    ("board.closed", Text("Roll call #{id} ended")),
+    ("board.closed_titled", Text("Roll call #{id} ended: {title}")),
+    ("board.retired", Text("This list is no longer updated.")),
+    ("board.removed", Text("Ok, I'll list everyone after every response again.")),
+    ("settings.header", Text("Settings:")),
+    (
+        "settings.footer",
+        Text("Use /settings <name> <value> to change one, e.g. /settings quiet on."),
+    ),
+    ("setting.updated", Text("Ok, {name} is now {value}.")),
+    ("setting.usage", Text("Please set {name} to {usage}.")),
+    ("setting.usage.language", Text("one of {languages}")),
+    ("setting.usage.timezone", Text("a timezone, e.g. Europe/Berlin or America/New_York")),
+    ("setting.usage.reply_style", Text("list or board")),
+    ("setting.usage.on_off", Text("on or off")),
+    ("setting.invalid", Text("There is no setting called {name}, try one of {names}.")),
+    ("answer_unknown.on", Text("Ok, I'll answer commands I don't know.")),
+    (
+        "answer_unknown.off",
+        Text("Ok, I'll only answer commands I don't know when they're addressed to me."),
+    ),
+    ("pin.on", Text("Ok, I'll pin new roll calls, if I'm allowed to pin messages.")),
+    ("pin.off", Text("Ok, I'll stop pinning roll calls.")),
+    ("alias.added", Text("Ok, {alias} now does the same as {command}.")),
+    ("alias.removed", Text("Alias {alias} removed.")),
+    ("alias.no_such", Text("There is no alias {alias}.")),
+    ("alias.none", Text("No aliases yet. Use /alias add /yes /in to add one.")),
+    ("alias.header", Text("Aliases:")),
+    ("history.none", Text("No past roll calls.")),
+    ("history.header", Text("Past roll calls:")),
+    ("history.footer", Text("Use /show_call <number> to see everyone's responses.")),
+    ("history.counts", Text("{in_count} in, {out_count} out, {maybe_count} maybe")),
+    ("history.no_such", Text("No such past roll call. Use /history to list them.")),
+    ("past_call.header", Text("Roll call #{id} ({date})")),
+    ("past_call.header_titled", Text("Roll call #{id}: {title} ({date})")),
+    ("total", Text("Total: {counts}.")),
+    ("total.in", Text("{count} in")),
+    ("total.out", Text("{count} out")),
+    ("total.maybe", Text("{count} might come")),
+    ("total.waitlist", Text("{count} on the waitlist")),
+    ("total.with_guests", Text("{total} (incl. {guests})")),
+    ("status.in", Text("In ({count})")),
+    ("status.out", Text("Out ({count})")),
+    ("status.maybe", Text("Maybe ({count})")),
+    ("status.waitlist", Text("Waitlist ({count})")),
+    ("status.with_guests", Text("{count}, incl. {guests}")),
+    ("responses.none", Text("No responses yet. 😢")),
+    ("guests", Plural("{count} guest", "{count} guests")),
+    ("error.missing_title", Text("Please provide a title.")),
+    ("error.missing_username", Text("Please provide the person's name.")),
+    (
+        "error.invalid_limit",
+        Text("Please provide the number of places, e.g. /set_limit 10, or /set_limit none to remove the limit."),
+    ),
+    ("error.invalid_guests", Text("Please bring at most 99 guests, e.g. /in +2.")),
+    (
+        "error.unterminated_quote",
+        Text("Please put quotes on both sides of the name, e.g. /set_in_for \"Mary Jane\" running late."),
+    ),
+    (
+        "error.invalid_mention",
+        Text("{mention} is not a username, please mention people like @maryjane."),
+    ),
+    (
+        "error.invalid_call_number",
+        Text("Please provide the number of a past roll call, e.g. /show_call 12."),
+    ),
+    (
+        "error.invalid_recurrence",
+        Text("Please use /recurring weekly <day> <HH:MM> <title>, /recurring list or /recurring cancel <number>."),
+    ),
+    (
+        "error.invalid_schedule",
+        Text("Please provide the date and time, e.g. 2019-03-20 18:00 Football, optionally with an end time, e.g. 2019-03-20 18:00-20:00 Football."),
+    ),
+    (
+        "error.invalid_alias",
+        Text("Please use /alias add /yes /in, /alias remove /yes or /alias list. Aliases are made of a-z, 0-9 and _."),
+    ),
+    ("error.alias_taken", Text("{alias} is already a command.")),
+    (
+        "error.invalid_alias_command",
+        Text("There is no command {command}, see /help."),
+    ),
+    ("error.unknown_command", Text("I don't understand that.")),
+];
+
+const DE: &[(&str, Message)] = &[
+    ("commands.header", Text("Verfügbare Befehle:")),
+    ("help.group.basic", Text("Grundlegende Befehle:")),
+    ("help.group.attendance", Text("Befehle zur Teilnahme:")),
+    ("help.group.other", Text("Weitere Befehle:")),
+    ("help.footer", Text("Mehr mit /help <Befehl>, z.B. /help set_in_for.")),
+    ("help.also", Text("Auch: {names}")),
+    ("help.examples", Text("Beispiele:")),
+    (
+        "help.selectable",
+        Text("Mit #12 oder #Titel davor wählst du eine andere offene Umfrage als die neueste."),
+    ),
+    ("help.unknown", Text("Es gibt keinen Befehl {command}, siehe /help.")),
+    ("help.see", Text("Siehe /help {command}.")),
+    (
+        "help.start_roll_call",
+        Text("Eine neue Umfrage starten, optional mit Titel, und die offenen beenden, außer mit --alongside"),
+    ),
+    ("help.end_roll_call", Text("Die aktuelle Umfrage beenden")),
+    ("help.reopen", Text("Die gerade beendete Umfrage wieder öffnen")),
+    (
+        "help.restart_roll_call",
+        Text("Eine neue Umfrage mit dem vorigen Titel starten, mit --keep bleiben alle als Vielleicht"),
+    ),
+    (
+        "help.schedule_roll_call",
+        Text("Eine Umfrage zu einer Uhrzeit in der Zeitzone des Chats starten und optional beenden"),
+    ),
+    (
+        "help.recurring",
+        Text("Jede Woche eine Umfrage starten, oder sie auflisten und absagen"),
+    ),
+    ("help.set_title", Text("Den Titel der aktuellen Umfrage setzen")),
+    ("help.shh", Text("Nicht mehr nach jeder Antwort alle auflisten")),
+    ("help.louder", Text("Wieder nach jeder Antwort alle auflisten")),
+    ("help.set_limit", Text("Die Anzahl der Plätze begrenzen, mit Warteliste für alle weiteren")),
+    ("help.in", Text("Allen sagen, dass du dabei bist")),
+    ("help.out", Text("Allen sagen, dass du nicht dabei bist")),
+    ("help.maybe", Text("Allen sagen, dass du vielleicht kommst")),
+    ("help.set_in_for", Text("Allen sagen, dass jemand anderes dabei ist")),
+    ("help.set_out_for", Text("Allen sagen, dass jemand anderes nicht dabei ist")),
+    ("help.set_maybe_for", Text("Allen sagen, dass jemand anderes vielleicht kommt")),
+    ("help.remove_me", Text("Dich ganz von der Liste nehmen")),
+    ("help.remove_for", Text("Jemand anderen von der Liste nehmen")),
+    ("help.whos_in", Text("Die Antworten aller auflisten")),
+    ("help.board", Text("Eine Liste der Antworten posten, die aktuell gehalten wird")),
+    ("help.pin", Text("Neue Umfragen anheften, falls der Bot Nachrichten anheften darf")),
+    ("help.answer_unknown", Text("Auf unbekannte Befehle antworten, die den Bot nicht nennen")),
+    ("help.settings", Text("Die Einstellungen des Chats anzeigen oder ändern")),
+    ("help.alias", Text("Befehlen in diesem Chat andere Namen geben")),
+    ("help.history", Text("Die beendeten Umfragen auflisten")),
+    ("help.show_call", Text("Die Antworten zu einer beendeten Umfrage anzeigen")),
+    ("help.help", Text("Zeigen, wie ein Befehl benutzt wird")),
+    ("help.available_commands", Text("Die Befehle auflisten")),
+    ("schedule.from_until", Text("Umfrage geplant von {start} bis {end} ({timezone}).")),
+    ("schedule.at", Text("Umfrage geplant für {start} ({timezone}).")),
+    ("schedule.passed", Text("Dieser Zeitpunkt ist schon vorbei.")),
+    ("weekday.mon", Text("Montag")),
+    ("weekday.tue", Text("Dienstag")),
+    ("weekday.wed", Text("Mittwoch")),
+    ("weekday.thu", Text("Donnerstag")),
+    ("weekday.fri", Text("Freitag")),
+    ("weekday.sat", Text("Samstag")),
+    ("weekday.sun", Text("Sonntag")),
+    ("recurring.every", Text("jeden {weekday} um {time} ({timezone})")),
+    (
+        "recurring.added",
+        Text("Die Umfrage startet {recurrence}, das nächste Mal am {date}. Mit /recurring cancel {id} wird sie beendet."),
+    ),
+    ("recurring.none", Text("Keine wiederkehrenden Umfragen.")),
+    ("recurring.header", Text("Wiederkehrende Umfragen:")),
+    ("recurring.line", Text(" #{id} Startet {recurrence}")),
+    ("recurring.line_titled", Text(" #{id} {title}, startet {recurrence}")),
+    ("recurring.cancelled", Text("Wiederkehrende Umfrage abgesagt.")),
+    ("recurring.no_such", Text("Diese wiederkehrende Umfrage gibt es nicht.")),
+    ("call.started", Text("Umfrage #{id} gestartet.")),
+    ("call.started_titled", Text("Umfrage #{id} gestartet: {title}")),
+    ("call.none", Text("Gerade läuft keine Umfrage.")),
+    ("call.no_such", Text("Diese Umfrage läuft gerade nicht.")),
+    ("call.ended", Text("Umfrage beendet.")),
+    ("error.generic", Text("Ein Fehler ist aufgetreten.")),
+    ("call.no_restart", Text("Es gibt noch keine Umfrage zum Neustarten.")),
+    (
+        "call.no_reopen",
+        Text("Nur die letzte Umfrage kann wieder geöffnet werden, sobald sie beendet ist."),
+    ),
+    ("call.reopened", Text("Umfrage wieder geöffnet.")),
+    ("call.title_set", Text("Titel der Umfrage gesetzt.")),
+    ("call.quiet", Text("Na gut, ich bin still. 🤐")),
+    ("call.louder", Text("Klar. 😃")),
+    ("button.in", Text("Dabei")),
+    ("button.out", Text("Nicht dabei")),
+    ("button.maybe", Text("Vielleicht")),
+    ("announce.in", Plural("{name} ist dabei!", "{name} sind dabei!")),
+    ("announce.out", Plural("{name} ist nicht dabei!", "{name} sind nicht dabei!")),
+    ("announce.maybe", Plural("{name} kommt vielleicht!", "{name} kommen vielleicht!")),
+    (
+        "announce.waitlist",
+        Plural("{name} steht auf der Warteliste!", "{name} stehen auf der Warteliste!"),
+    ),
+    ("names.and", Text("{names} und {last}")),
+    ("response.not_listed", Text("{name} steht nicht auf der Liste.")),
+    ("response.removed", Text("{name} steht nicht mehr auf der Liste.")),
+    ("response.promoted", Text("{name} ist von der Warteliste nachgerückt und dabei!")),
+    ("limit.set", Text("Limit auf {limit} gesetzt.")),
+    ("limit.removed", Text("Limit entfernt.")),
+    (
+        "limit.kept",
+        Text("Die {count} Leute, die dabei sind, behalten ihre Plätze, Neue kommen auf die Warteliste, bis wieder Platz ist."),
+    ),
+    (
+        "limit.guests_refused",
+        Plural(
+            "{name} bleibt dabei, aber für {count} weiteren Gast ist kein Platz.",
+            "{name} bleibt dabei, aber für {count} weitere Gäste ist kein Platz.",
+        ),
+    ),
+    ("board.open", Text("Umfrage #{id}")),
+    ("board.open_titled", Text("Umfrage #{id}: {title}")),
+    ("board.closed", Text("Umfrage #{id} beendet")),
+    ("board.closed_titled", Text("Umfrage #{id} beendet: {title}")),
+    ("board.retired", Text("Diese Liste wird nicht mehr aktualisiert.")),
+    ("board.removed", Text("Ok, ich liste wieder nach jeder Antwort alle auf.")),
+    ("settings.header", Text("Einstellungen:")),
+    (
+        "settings.footer",
+        Text("Mit /settings <name> <wert> änderst du eine, z.B. /settings quiet on."),
+    ),
+    ("setting.updated", Text("Ok, {name} ist jetzt {value}.")),
+    ("setting.usage", Text("Bitte setze {name} auf {usage}.")),
+    ("setting.usage.language", Text("einen der Werte {languages}")),
+    ("setting.usage.timezone", Text("eine Zeitzone, z.B. Europe/Berlin oder America/New_York")),
+    ("setting.usage.reply_style", Text("list oder board")),
+    ("setting.usage.on_off", Text("on oder off")),
+    (
+        "setting.invalid",
+        Text("Es gibt keine Einstellung namens {name}, versuche eine von {names}."),
+    ),
+    ("answer_unknown.on", Text("Ok, ich antworte auf Befehle, die ich nicht kenne.")),
+    (
+        "answer_unknown.off",
+        Text("Ok, ich antworte auf unbekannte Befehle nur, wenn sie an mich gerichtet sind."),
+    ),
+    (
+        "pin.on",
+        Text("Ok, ich hefte neue Umfragen an, falls ich Nachrichten anheften darf."),
+    ),
+    ("pin.off", Text("Ok, ich hefte keine Umfragen mehr an.")),
+    ("alias.added", Text("Ok, {alias} macht jetzt dasselbe wie {command}.")),
+    ("alias.removed", Text("Alias {alias} entfernt.")),
+    ("alias.no_such", Text("Es gibt keinen Alias {alias}.")),
+    (
+        "alias.none",
+        Text("Noch keine Aliase. Mit /alias add /yes /in fügst du einen hinzu."),
+    ),
+    ("alias.header", Text("Aliase:")),
+    ("history.none", Text("Keine vergangenen Umfragen.")),
+    ("history.header", Text("Vergangene Umfragen:")),
+    ("history.footer", Text("Mit /show_call <nummer> siehst du die Antworten aller.")),
+    ("history.counts", Text("{in_count} dabei, {out_count} nicht dabei, {maybe_count} vielleicht")),
+    (
+        "history.no_such",
+        Text("Diese vergangene Umfrage gibt es nicht. Mit /history werden sie aufgelistet."),
+    ),
+    ("past_call.header", Text("Umfrage #{id} ({date})")),
+    ("past_call.header_titled", Text("Umfrage #{id}: {title} ({date})")),
+    ("total", Text("Gesamt: {counts}.")),
+    ("total.in", Text("{count} dabei")),
+    ("total.out", Text("{count} nicht dabei")),
+    ("total.maybe", Text("{count} vielleicht")),
+    ("total.waitlist", Text("{count} auf der Warteliste")),
+    ("total.with_guests", Text("{total} (inkl. {guests})")),
+    ("status.in", Text("Dabei ({count})")),
+    ("status.out", Text("Nicht dabei ({count})")),
+    ("status.maybe", Text("Vielleicht ({count})")),
+    ("status.waitlist", Text("Warteliste ({count})")),
+    ("status.with_guests", Text("{count}, inkl. {guests}")),
+    ("responses.none", Text("Noch keine Antworten. 😢")),
+    ("guests", Plural("{count} Gast", "{count} Gäste")),
+    ("error.missing_title", Text("Bitte gib einen Titel an.")),
+    ("error.missing_username", Text("Bitte gib den Namen der Person an.")),
+    (
+        "error.invalid_limit",
+        Text("Bitte gib die Anzahl der Plätze an, z.B. /set_limit 10, oder /set_limit none, um das Limit zu entfernen."),
+    ),
+    ("error.invalid_guests", Text("Bitte bring höchstens 99 Gäste mit, z.B. /in +2.")),
+    (
+        "error.unterminated_quote",
+        Text("Bitte setze den Namen auf beiden Seiten in Anführungszeichen, z.B. /set_in_for \"Mary Jane\" kommt später."),
+    ),
+    (
+        "error.invalid_mention",
+        Text("{mention} ist kein Benutzername, bitte erwähne Leute wie @maryjane."),
+    ),
+    (
+        "error.invalid_call_number",
+        Text("Bitte gib die Nummer einer vergangenen Umfrage an, z.B. /show_call 12."),
+    ),
+    (
+        "error.invalid_recurrence",
+        Text("Bitte nutze /recurring weekly <tag> <HH:MM> <titel>, /recurring list oder /recurring cancel <nummer>."),
+    ),
+    (
+        "error.invalid_schedule",
+        Text("Bitte gib Datum und Uhrzeit an, z.B. 2019-03-20 18:00 Fußball, optional mit einer Endzeit, z.B. 2019-03-20 18:00-20:00 Fußball."),
+    ),
+    (
+        "error.invalid_alias",
+        Text("Bitte nutze /alias add /yes /in, /alias remove /yes oder /alias list. Aliase bestehen aus a-z, 0-9 und _."),
+    ),
+    ("error.alias_taken", Text("{alias} ist schon ein Befehl.")),
+    (
+        "error.invalid_alias_command",
+        Text("Es gibt keinen Befehl {command}, siehe /help."),
+    ),
+    ("error.unknown_command", Text("Das verstehe ich nicht.")),
+];
+
+const VI: &[(&str, Message)] = &[
+    ("commands.header", Text("Các lệnh có sẵn:")),
+    ("help.group.basic", Text("Lệnh cơ bản:")),
+    ("help.group.attendance", Text("Lệnh điểm danh:")),
+    ("help.group.other", Text("Lệnh khác:")),
+    ("help.footer", Text("Xem thêm với /help <lệnh>, ví dụ /help set_in_for.")),
+    ("help.also", Text("Tên khác: {names}")),
+    ("help.examples", Text("Ví dụ:")),
+    (
+        "help.selectable",
+        Text("Thêm #12 hoặc #tiêu đề ở đầu để chọn một điểm danh đang mở khác với điểm danh mới nhất."),
+    ),
+    ("help.unknown", Text("Không có lệnh {command}, xem /help.")),
+    ("help.see", Text("Xem /help {command}.")),
+    (
+        "help.start_roll_call",
+        Text("Bắt đầu một điểm danh mới, có thể kèm tiêu đề, và kết thúc các điểm danh đang mở, trừ khi dùng --alongside"),
+    ),
+    ("help.end_roll_call", Text("Kết thúc điểm danh hiện tại")),
+    ("help.reopen", Text("Mở lại điểm danh vừa kết thúc")),
+    (
+        "help.restart_roll_call",
+        Text("Bắt đầu điểm danh mới với tiêu đề trước, với --keep mọi người được giữ lại là Có thể"),
+    ),
+    (
+        "help.schedule_roll_call",
+        Text("Bắt đầu điểm danh vào một thời điểm theo múi giờ của nhóm, và có thể kết thúc nó"),
+    ),
+    ("help.recurring", Text("Bắt đầu điểm danh hằng tuần, hoặc liệt kê và hủy chúng")),
+    ("help.set_title", Text("Đặt tiêu đề cho điểm danh hiện tại")),
+    ("help.shh", Text("Ngừng liệt kê mọi người sau mỗi câu trả lời")),
+    ("help.louder", Text("Liệt kê lại mọi người sau mỗi câu trả lời")),
+    ("help.set_limit", Text("Giới hạn số chỗ, những người còn lại vào danh sách chờ")),
+    ("help.in", Text("Cho mọi người biết bạn sẽ tham gia")),
+    ("help.out", Text("Cho mọi người biết bạn sẽ không tham gia")),
+    ("help.maybe", Text("Cho mọi người biết bạn có thể sẽ đến")),
+    ("help.set_in_for", Text("Cho mọi người biết người khác sẽ tham gia")),
+    ("help.set_out_for", Text("Cho mọi người biết người khác sẽ không tham gia")),
+    ("help.set_maybe_for", Text("Cho mọi người biết người khác có thể sẽ đến")),
+    ("help.remove_me", Text("Xóa bạn khỏi danh sách")),
+    ("help.remove_for", Text("Xóa người khác khỏi danh sách")),
+    ("help.whos_in", Text("Liệt kê câu trả lời của mọi người")),
+    ("help.board", Text("Đăng danh sách câu trả lời luôn được cập nhật")),
+    ("help.pin", Text("Ghim các điểm danh mới, nếu bot được phép ghim tin nhắn")),
+    ("help.answer_unknown", Text("Trả lời các lệnh lạ không gọi tên bot")),
+    ("help.settings", Text("Xem hoặc thay đổi cài đặt của nhóm")),
+    ("help.alias", Text("Đặt tên khác cho các lệnh trong nhóm này")),
+    ("help.history", Text("Liệt kê các điểm danh đã kết thúc")),
+    ("help.show_call", Text("Xem câu trả lời cho một điểm danh đã kết thúc")),
+    ("help.help", Text("Xem cách dùng một lệnh")),
+    ("help.available_commands", Text("Liệt kê các lệnh")),
+    ("schedule.from_until", Text("Đã lên lịch điểm danh từ {start} đến {end} ({timezone}).")),
+    ("schedule.at", Text("Đã lên lịch điểm danh vào {start} ({timezone}).")),
+    ("schedule.passed", Text("Thời điểm đó đã qua rồi.")),
+    ("weekday.mon", Text("Thứ Hai")),
+    ("weekday.tue", Text("Thứ Ba")),
+    ("weekday.wed", Text("Thứ Tư")),
+    ("weekday.thu", Text("Thứ Năm")),
+    ("weekday.fri", Text("Thứ Sáu")),
+    ("weekday.sat", Text("Thứ Bảy")),
+    ("weekday.sun", Text("Chủ Nhật")),
+    ("recurring.every", Text("vào mỗi {weekday} lúc {time} ({timezone})")),
+    (
+        "recurring.added",
+        Text("Điểm danh sẽ bắt đầu {recurrence}, lần tới vào {date}. Dùng /recurring cancel {id} để dừng."),
+    ),
+    ("recurring.none", Text("Không có điểm danh định kỳ nào.")),
+    ("recurring.header", Text("Các điểm danh định kỳ:")),
+    ("recurring.line", Text(" #{id} Bắt đầu {recurrence}")),
+    ("recurring.line_titled", Text(" #{id} {title}, bắt đầu {recurrence}")),
+    ("recurring.cancelled", Text("Đã hủy điểm danh định kỳ.")),
+    ("recurring.no_such", Text("Không có điểm danh định kỳ này.")),
+    ("call.started", Text("Đã bắt đầu điểm danh #{id}.")),
+    ("call.started_titled", Text("Đã bắt đầu điểm danh #{id}: {title}")),
+    ("call.none", Text("Không có điểm danh nào đang diễn ra.")),
+    ("call.no_such", Text("Điểm danh này không đang diễn ra.")),
+    ("call.ended", Text("Đã kết thúc điểm danh.")),
+    ("error.generic", Text("Đã xảy ra lỗi.")),
+    ("call.no_restart", Text("Chưa có điểm danh nào để bắt đầu lại.")),
+    (
+        "call.no_reopen",
+        Text("Chỉ có thể mở lại điểm danh gần nhất, sau khi nó đã kết thúc."),
+    ),
+    ("call.reopened", Text("Đã mở lại điểm danh.")),
+    ("call.title_set", Text("Đã đặt tiêu đề điểm danh.")),
+    ("call.quiet", Text("Được rồi, tôi sẽ im lặng. 🤐")),
+    ("call.louder", Text("Được thôi. 😃")),
+    ("button.in", Text("Có")),
+    ("button.out", Text("Không")),
+    ("button.maybe", Text("Có thể")),
+    ("announce.in", Text("{name} tham gia!")),
+    ("announce.out", Text("{name} không tham gia!")),
+    ("announce.maybe", Text("{name} có thể sẽ đến!")),
+    ("announce.waitlist", Text("{name} đang trong danh sách chờ!")),
+    ("names.and", Text("{names} và {last}")),
+    ("response.not_listed", Text("{name} không có trong danh sách.")),
+    ("response.removed", Text("{name} đã rời khỏi danh sách.")),
+    ("response.promoted", Text("{name} đã rời danh sách chờ và tham gia!")),
+    ("limit.set", Text("Đã đặt giới hạn là {limit}.")),
+    ("limit.removed", Text("Đã bỏ giới hạn.")),
+    (
+        "limit.kept",
+        Text("{count} người đã tham gia vẫn giữ chỗ, người mới sẽ vào danh sách chờ cho đến khi có chỗ."),
+    ),
+    (
+        "limit.guests_refused",
+        Text("{name} vẫn tham gia, nhưng không còn chỗ cho thêm {count} khách."),
+    ),
+    ("board.open", Text("Điểm danh #{id}")),
+    ("board.open_titled", Text("Điểm danh #{id}: {title}")),
+    ("board.closed", Text("Điểm danh #{id} đã kết thúc")),
+    ("board.closed_titled", Text("Điểm danh #{id} đã kết thúc: {title}")),
+    ("board.retired", Text("Danh sách này không còn được cập nhật.")),
+    (
+        "board.removed",
+        Text("Được, tôi sẽ lại liệt kê mọi người sau mỗi câu trả lời."),
+    ),
+    ("settings.header", Text("Cài đặt:")),
+    (
+        "settings.footer",
+        Text("Dùng /settings <tên> <giá trị> để thay đổi, ví dụ /settings quiet on."),
+    ),
+    ("setting.updated", Text("Được, {name} giờ là {value}.")),
+    ("setting.usage", Text("Vui lòng đặt {name} thành {usage}.")),
+    ("setting.usage.language", Text("một trong {languages}")),
+    ("setting.usage.timezone", Text("một múi giờ, ví dụ Asia/Ho_Chi_Minh hoặc Europe/Berlin")),
+    ("setting.usage.reply_style", Text("list hoặc board")),
+    ("setting.usage.on_off", Text("on hoặc off")),
+    (
+        "setting.invalid",
+        Text("Không có cài đặt nào tên {name}, hãy thử một trong {names}."),
+    ),
+    ("answer_unknown.on", Text("Được, tôi sẽ trả lời các lệnh mà tôi không biết.")),
+    (
+        "answer_unknown.off",
+        Text("Được, tôi sẽ chỉ trả lời các lệnh mà tôi không biết khi chúng được gửi cho tôi."),
+    ),
+    (
+        "pin.on",
+        Text("Được, tôi sẽ ghim các điểm danh mới, nếu tôi được phép ghim tin nhắn."),
+    ),
+    ("pin.off", Text("Được, tôi sẽ ngừng ghim các điểm danh.")),
+    ("alias.added", Text("Được, {alias} giờ có tác dụng giống {command}.")),
+    ("alias.removed", Text("Đã xóa bí danh {alias}.")),
+    ("alias.no_such", Text("Không có bí danh {alias}.")),
+    ("alias.none", Text("Chưa có bí danh nào. Dùng /alias add /yes /in để thêm.")),
+    ("alias.header", Text("Bí danh:")),
+    ("history.none", Text("Chưa có điểm danh nào trước đây.")),
+    ("history.header", Text("Các điểm danh trước đây:")),
+    (
+        "history.footer",
+        Text("Dùng /show_call <số> để xem câu trả lời của mọi người."),
+    ),
+    ("history.counts", Text("{in_count} có, {out_count} không, {maybe_count} có thể")),
+    (
+        "history.no_such",
+        Text("Không có điểm danh trước đây này. Dùng /history để liệt kê chúng."),
+    ),
+    ("past_call.header", Text("Điểm danh #{id} ({date})")),
+    ("past_call.header_titled", Text("Điểm danh #{id}: {title} ({date})")),
+    ("total", Text("Tổng: {counts}.")),
+    ("total.in", Text("{count} có")),
+    ("total.out", Text("{count} không")),
+    ("total.maybe", Text("{count} có thể đến")),
+    ("total.waitlist", Text("{count} trong danh sách chờ")),
+    ("total.with_guests", Text("{total} (gồm {guests})")),
+    ("status.in", Text("Có ({count})")),
+    ("status.out", Text("Không ({count})")),
+    ("status.maybe", Text("Có thể ({count})")),
+    ("status.waitlist", Text("Danh sách chờ ({count})")),
+    ("status.with_guests", Text("{count}, gồm {guests}")),
+    ("responses.none", Text("Chưa có ai trả lời. 😢")),
+    ("guests", Text("{count} khách")),
+    ("error.missing_title", Text("Vui lòng nhập tiêu đề.")),
+    ("error.missing_username", Text("Vui lòng nhập tên của người đó.")),
+    (
+        "error.invalid_limit",
+        Text("Vui lòng nhập số chỗ, ví dụ /set_limit 10, hoặc /set_limit none để bỏ giới hạn."),
+    ),
+    ("error.invalid_guests", Text("Vui lòng dẫn theo tối đa 99 khách, ví dụ /in +2.")),
+    (
+        "error.unterminated_quote",
+        Text("Vui lòng đặt dấu ngoặc kép ở cả hai bên tên, ví dụ /set_in_for \"Mary Jane\" đến muộn."),
+    ),
+    (
+        "error.invalid_mention",
+        Text("{mention} không phải là tên người dùng, vui lòng nhắc đến mọi người như @maryjane."),
+    ),
+    (
+        "error.invalid_call_number",
+        Text("Vui lòng nhập số của một điểm danh trước đây, ví dụ /show_call 12."),
+    ),
+    (
+        "error.invalid_recurrence",
+        Text("Vui lòng dùng /recurring weekly <ngày> <HH:MM> <tiêu đề>, /recurring list hoặc /recurring cancel <số>."),
+    ),
+    (
+        "error.invalid_schedule",
+        Text("Vui lòng nhập ngày và giờ, ví dụ 2019-03-20 18:00 Bóng đá, có thể kèm giờ kết thúc, ví dụ 2019-03-20 18:00-20:00 Bóng đá."),
+    ),
+    (
+        "error.invalid_alias",
+        Text("Vui lòng dùng /alias add /yes /in, /alias remove /yes hoặc /alias list. Bí danh chỉ gồm a-z, 0-9 và _."),
+    ),
+    ("error.alias_taken", Text("{alias} đã là một lệnh.")),
+    (
+        "error.invalid_alias_command",
+        Text("Không có lệnh {command}, xem /help."),
+    ),
+    ("error.unknown_command", Text("Tôi không hiểu lệnh đó.")),
+];
+
+lazy_static! {
+    static ref CATALOGUE: HashMap<&'static str, HashMap<&'static str, Message>> = hashmap! {
+        "en" => EN.iter().cloned().collect(),
+        "de" => DE.iter().cloned().collect(),
+        "vi" => VI.iter().cloned().collect(),
+    };
+}
+
+/// Looks up the messages of one language, the one of the chat being answered, see `tr!` and
+/// `tr_n!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Translator {
+    language: &'static str,
+}
+
+impl Translator {
+    /// Translates into the given language, or into the default one if the bot does not speak it.
+    pub fn new(language: &str) -> Translator {
+        let language = LANGUAGES
+            .iter()
+            .find(|&&known| known == language)
+            .cloned()
+            .unwrap_or(LANGUAGES[0]);
+        Translator { language }
+    }
+
+    /// Looks up a message, see `tr!` and `tr_n!`. Unknown ids are returned as they are, so that a
+    /// missing message is noticed without failing the reply.
+    pub fn translate(self, id: &str, count: Option<i64>, args: &[(&str, &dyn Display)]) -> String {
+        let (language, message) = match lookup(self.language, id) {
+            Some(message) => (self.language, message),
+            None => match lookup(LANGUAGES[0], id) {
+                Some(message) => (LANGUAGES[0], message),
+                None => {
+                    error!("Missing message {}", id);
+                    return id.to_owned();
+                }
+            },
+        };
+
+        let template = match (message, count) {
+            (Text(text), _) => text,
+            (Plural(one, _), Some(count)) if is_one(language, count) => one,
+            (Plural(_, other), _) => other,
+        };
+
+        fill(template, args)
+    }
+}
+
+impl Default for Translator {
+    fn default() -> Translator {
+        Translator {
+            language: LANGUAGES[0],
+        }
+    }
+}
+
+/// Whether a count takes the form of a message for one thing, which Vietnamese does not have.
+fn is_one(language: &str, count: i64) -> bool {
+    match language {
+        "vi" => false,
+        _ => count == 1,
+    }
+}
+
+fn lookup(language: &str, id: &str) -> Option<Message> {
+    CATALOGUE
+        .get(language)
+        .and_then(|messages| messages.get(id))
+        .cloned()
+}
+
+/// Replaces the `{name}` placeholders of a template in a single pass, so that values which
+/// contain braces themselves are left alone.
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            args.iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| (end, value.to_string()))
+        });
+        match value {
+            Some((end, value)) => {
+                result.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use regex::Regex;
+
+    use super::*;
+
+    fn placeholders(message: Message) -> HashSet<String> {
+        let regex = Regex::new(r"\{(\w+)\}").unwrap();
+        let texts = match message {
+            Text(text) => vec![text],
+            Plural(one, other) => vec![one, other],
+        };
+        texts
+            .iter()
+            .flat_map(|text| regex.captures_iter(text))
+            .map(|captures| captures[1].to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_translations_match_english() {
+        for &language in LANGUAGES.iter() {
+            let messages = &CATALOGUE[language];
+            for (id, &message) in messages.iter() {
+                let english = CATALOGUE["en"].get(id).cloned();
+                let english = english.unwrap_or_else(|| panic!("{} is not in English", id));
+                assert_eq!(
+                    placeholders(english),
+                    placeholders(message),
+                    "{} in {}",
+                    id,
+                    language
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_commands_are_described_in_every_language() {
+        for &language in LANGUAGES.iter() {
+            for spec in crate::bot::commands::COMMANDS.iter() {
+                assert!(
+                    CATALOGUE[language].contains_key(spec.help),
+                    "{} in {}",
+                    spec.help,
+                    language
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_picks_language_and_falls_back_to_english() {
+        assert_eq!("Roll call ended.", tr!(Translator::default(), "call.ended"));
+        assert_eq!("Umfrage beendet.", tr!(Translator::new("de"), "call.ended"));
+        assert_eq!("Roll call ended.", tr!(Translator::new("xx"), "call.ended"));
+
+        assert_eq!("missing.id", tr!(Translator::new("vi"), "missing.id"));
+    }
+
+    #[test]
+    fn test_translate_fills_placeholders_and_plurals() {
+        let t = Translator::default();
+        assert_eq!("Dave is in!", tr_n!(t, "announce.in", 1, name = "Dave"));
+        assert_eq!(
+            "Dave and Tom are in!",
+            tr_n!(t, "announce.in", 2, name = "Dave and Tom")
+        );
+        assert_eq!("1 guest", tr_n!(t, "guests", 1));
+        assert_eq!("2 guests", tr_n!(t, "guests", 2));
+        assert_eq!("2 Gäste", tr_n!(Translator::new("de"), "guests", 2));
+        assert_eq!("1 khách", tr_n!(Translator::new("vi"), "guests", 1));
+        assert_eq!(
+            "Roll call #1 started: {title} {",
+            tr!(t, "call.started_titled", id = 1, title = "{title} {")
+        );
+    }
+}
@@ -0,0 +1,9 @@
+pub use transport::*;
+pub use whosin::*;
+
+#[macro_use]
+mod i18n;
+mod commands;
+mod transport;
+mod views;
+mod whosin;
@@ -0,0 +1,253 @@
+use regex::Regex;
+use slog_scope;
+
+use crate::models::{CallId, ChatId, MessageId, UserId};
+
+use super::i18n::Translator;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatCommand {
+    pub chat_id: ChatId,
+    pub user_id: UserId,
+    pub username: String,
+    pub command: String,
+    pub command_params: String,
+    /// Whether the command was meant for this bot in particular, by naming it as in
+    /// `/in@WhosInBot` or by being sent in a private chat with it.
+    pub addressed: bool,
+}
+
+lazy_static! {
+    static ref COMMAND_REGEX: Regex =
+        Regex::new(r"(?s)^(/[^@[:space:]]+)(@\S*)?\s*(.*)$").expect("Failed to create Regex");
+}
+
+impl ChatCommand {
+    /// Parses a text message such as `/in running late`, returning `None` if it is not a command
+    /// or if it names another bot than `bot_username`, as in `/in@OtherBot`. Without a username,
+    /// a command naming any bot is taken as addressed to this one.
+    pub fn parse(
+        chat_id: ChatId,
+        user_id: UserId,
+        username: &str,
+        text: &str,
+        bot_username: Option<&str>,
+    ) -> Option<Self> {
+        let captures = COMMAND_REGEX.captures(text)?;
+        let addressed = match (captures.get(2), bot_username) {
+            (Some(mention), Some(bot_username)) => {
+                if !mention.as_str()[1..].eq_ignore_ascii_case(bot_username) {
+                    return None;
+                }
+                true
+            }
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        Some(ChatCommand {
+            chat_id,
+            user_id,
+            username: username.to_owned(),
+            command: captures[1].to_owned(),
+            command_params: captures[3].trim_end().to_owned(),
+            addressed,
+        })
+    }
+}
+
+/// A button shown under a message. Pressing it sends `command` on behalf of whoever pressed it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatButton {
+    pub label: String,
+    pub command: String,
+}
+
+/// Identifies a message the bot needs to refer to later. The transport reports such messages back
+/// with their id once they are sent, see `ChatTransport`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageTag {
+    /// The announcement that a call has started.
+    Started(CallId),
+    /// The board of a call, which lists its responses and is edited whenever they change.
+    Board(CallId),
+}
+
+impl MessageTag {
+    pub fn call_id(self) -> CallId {
+        match self {
+            MessageTag::Started(call_id) | MessageTag::Board(call_id) => call_id,
+        }
+    }
+}
+
+/// A change to the pinned message of a chat, made once the reply is sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinChange {
+    /// Pins the message sent for the reply.
+    Reply,
+    /// Pins a message sent earlier.
+    Message(MessageId),
+    Unpin,
+}
+
+/// A new text and buttons for a message the bot has sent before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatEdit {
+    pub message_id: MessageId,
+    pub text: String,
+    pub buttons: Vec<ChatButton>,
+}
+
+/// What the bot says in a chat, either in reply to a command or on its own initiative. A reply may
+/// also edit messages sent earlier, in which case its text can be left empty to send nothing new.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reply {
+    pub text: String,
+    pub buttons: Vec<ChatButton>,
+    pub edits: Vec<ChatEdit>,
+    pub tag: Option<MessageTag>,
+    pub pin: Option<PinChange>,
+}
+
+impl Reply {
+    pub fn with_buttons(self, buttons: Vec<ChatButton>) -> Reply {
+        Reply { buttons, ..self }
+    }
+
+    pub fn with_edits(self, edits: Vec<ChatEdit>) -> Reply {
+        Reply { edits, ..self }
+    }
+
+    pub fn with_tag(self, tag: MessageTag) -> Reply {
+        Reply {
+            tag: Some(tag),
+            ..self
+        }
+    }
+
+    pub fn with_pin(self, pin: PinChange) -> Reply {
+        Reply {
+            pin: Some(pin),
+            ..self
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.edits.is_empty() && self.pin.is_none()
+    }
+}
+
+impl From<String> for Reply {
+    fn from(text: String) -> Reply {
+        Reply {
+            text,
+            buttons: vec![],
+            edits: vec![],
+            tag: None,
+            pin: None,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Reply {
+    fn from(text: &'a str) -> Reply {
+        Reply::from(text.to_owned())
+    }
+}
+
+/// A tagged message that the transport has sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SentMessage {
+    pub chat_id: ChatId,
+    pub message_id: MessageId,
+    pub tag: MessageTag,
+    /// Whether the transport was asked to pin the message. Pinning can still fail, e.g. when the
+    /// bot is not allowed to pin messages in the chat.
+    pub pinned: bool,
+}
+
+/// A message the bot sends on its own initiative rather than as a reply to a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub chat_id: ChatId,
+    pub reply: Reply,
+}
+
+pub type HandlerResult = Result<Option<Reply>, failure::Error>;
+
+pub type TickResult = Result<Vec<ChatMessage>, failure::Error>;
+
+pub type BotResult = Result<(), failure::Error>;
+
+/// A chat system that the bot can be attached to. A transport turns incoming messages into
+/// `ChatCommand`s, passes them to the handler, and delivers the handler's replies back to the chat
+/// the command came from. It also calls `ticker` every now and then, and sends the messages it
+/// returns, so the bot can act on scheduled events. Replies with a `MessageTag` are passed to
+/// `on_sent` along with their message id once sent.
+pub trait ChatTransport {
+    fn run<F, T, S>(&self, handler: F, ticker: T, on_sent: S) -> BotResult
+    where
+        F: Fn(ChatCommand) -> HandlerResult,
+        T: Fn() -> TickResult,
+        S: Fn(SentMessage) -> BotResult;
+}
+
+/// Runs `handler` with the command's chat and user attached to the log and Sentry scopes. Errors
+/// are reported and turned into a generic reply, so transports only need to deliver the result.
+/// The reply is in the default language, as the error may well have kept the chat's from loading.
+pub fn dispatch<F>(handler: &F, command: ChatCommand, logger: &slog::Logger) -> Option<Reply>
+where
+    F: Fn(ChatCommand) -> HandlerResult,
+{
+    let chat_id = command.chat_id;
+    let user_id = command.user_id;
+
+    let sentry_scope = |scope: &mut sentry::Scope| {
+        scope.set_tag("chat_id", chat_id);
+        scope.set_tag("user_id", user_id);
+    };
+
+    let logger_scope = &logger.new(o!(
+        "chat_id" => chat_id,
+        "user_id" => user_id,
+    ));
+
+    let handle = || match handler(command) {
+        Ok(Some(reply)) => Some(reply),
+        Ok(None) => None,
+        Err(err) => {
+            error!("An error has occurred: {}", err; "details" => format!("{:?}", err));
+            sentry::integrations::failure::capture_error(&err);
+            Some(tr!(Translator::default(), "error.generic").into())
+        }
+    };
+
+    sentry::with_scope(sentry_scope, || slog_scope::scope(logger_scope, handle))
+}
+
+/// Runs `ticker`, reporting any error so that a failed tick does not stop the transport.
+pub fn tick<T>(ticker: &T) -> Vec<ChatMessage>
+where
+    T: Fn() -> TickResult,
+{
+    match ticker() {
+        Ok(messages) => messages,
+        Err(err) => {
+            error!("A scheduled task has failed: {}", err; "details" => format!("{:?}", err));
+            sentry::integrations::failure::capture_error(&err);
+            vec![]
+        }
+    }
+}
+
+/// Runs `on_sent` for a message that was sent for a tagged reply, reporting any error.
+pub fn report_sent<S>(on_sent: &S, message: SentMessage)
+where
+    S: Fn(SentMessage) -> BotResult,
+{
+    if let Err(err) = on_sent(message) {
+        error!("Failed to record a sent message: {}", err; "details" => format!("{:?}", err));
+        sentry::integrations::failure::capture_error(&err);
+    }
+}
@@ -0,0 +1,1102 @@
+use chrono::Weekday;
+use chrono_tz::Tz;
+
+use crate::models::{AttendanceStatus::*, *};
+use crate::util::collections::CollectionTools;
+
+use super::commands::{CommandGroup, CommandSpec, Setting, COMMANDS};
+use super::i18n::Translator;
+use super::transport::{ChatButton, ChatEdit, Reply};
+
+/// Lists the commands by group, along with their other names, including the aliases the chat gave
+/// them.
+pub fn render_available_commands(t: Translator, aliases: &[ChatAlias]) -> String {
+    let groups = CommandGroup::ALL
+        .iter()
+        .map(|&group| {
+            let list = COMMANDS
+                .iter()
+                .filter(|spec| spec.group == group)
+                .map(|spec| {
+                    let name = match other_names(spec, aliases).as_slice() {
+                        [] => spec.name.to_string(),
+                        other_names => format!("{} ({})", spec.name, other_names.join(", ")),
+                    };
+                    format!(" 🍺 {} - {}", name, tr!(t, spec.help))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{}\n{}", render_group(t, group), list)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!(
+        "{}\n\n{}\n\n{}",
+        tr!(t, "commands.header"),
+        groups,
+        tr!(t, "help.footer")
+    )
+}
+
+/// Explains how to use a command, as in `/help set_in_for`.
+pub fn render_command_help(t: Translator, spec: &CommandSpec, aliases: &[ChatAlias]) -> String {
+    let mut lines = vec![spec.usage.to_string(), tr!(t, spec.help)];
+    let other_names = other_names(spec, aliases);
+    if !other_names.is_empty() {
+        lines.push(tr!(t, "help.also", names = other_names.join(", ")));
+    }
+    if spec.selectable {
+        lines.push(tr!(t, "help.selectable"));
+    }
+    if !spec.examples.is_empty() {
+        lines.push(String::new());
+        lines.push(tr!(t, "help.examples"));
+        lines.extend(spec.examples.iter().map(|example| format!(" {}", example)));
+    }
+    lines.join("\n")
+}
+
+fn render_group(t: Translator, group: CommandGroup) -> String {
+    match group {
+        CommandGroup::Basic => tr!(t, "help.group.basic"),
+        CommandGroup::Attendance => tr!(t, "help.group.attendance"),
+        CommandGroup::Other => tr!(t, "help.group.other"),
+    }
+}
+
+fn other_names<'a>(spec: &'a CommandSpec, aliases: &'a [ChatAlias]) -> Vec<&'a str> {
+    spec.aliases
+        .iter()
+        .cloned()
+        .chain(
+            aliases
+                .iter()
+                .filter(|alias| alias.command == spec.name)
+                .map(|alias| alias.alias.as_str()),
+        )
+        .collect()
+}
+
+pub fn render_aliases(t: Translator, aliases: &[ChatAlias]) -> String {
+    if aliases.is_empty() {
+        return tr!(t, "alias.none");
+    }
+
+    let lines = aliases
+        .iter()
+        .map(|alias| format!(" {} → {}", alias.alias, alias.command))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("{}\n{}", tr!(t, "alias.header"), lines)
+}
+
+const SCHEDULE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Confirms a scheduled call with its times in the timezone of the chat.
+pub fn render_schedule(t: Translator, scheduled: &ScheduledCall, tz: Tz) -> String {
+    let start_at = utc_to_local(tz, scheduled.start_at).format(SCHEDULE_FORMAT);
+    match scheduled.end_at {
+        Some(end_at) => tr!(
+            t,
+            "schedule.from_until",
+            start = start_at,
+            end = utc_to_local(tz, end_at).format(SCHEDULE_FORMAT),
+            timezone = tz
+        ),
+        None => tr!(t, "schedule.at", start = start_at, timezone = tz),
+    }
+}
+
+fn render_weekday(t: Translator, weekday: Weekday) -> String {
+    match weekday {
+        Weekday::Mon => tr!(t, "weekday.mon"),
+        Weekday::Tue => tr!(t, "weekday.tue"),
+        Weekday::Wed => tr!(t, "weekday.wed"),
+        Weekday::Thu => tr!(t, "weekday.thu"),
+        Weekday::Fri => tr!(t, "weekday.fri"),
+        Weekday::Sat => tr!(t, "weekday.sat"),
+        Weekday::Sun => tr!(t, "weekday.sun"),
+    }
+}
+
+fn render_recurrence(t: Translator, recurring: &RecurringCall) -> String {
+    tr!(
+        t,
+        "recurring.every",
+        weekday = render_weekday(t, recurring.weekday()),
+        time = recurring.start_time.format("%H:%M"),
+        timezone = recurring.tz()
+    )
+}
+
+pub fn render_recurring_call_added(t: Translator, recurring: &RecurringCall) -> String {
+    tr!(
+        t,
+        "recurring.added",
+        recurrence = render_recurrence(t, recurring),
+        date = utc_to_local(recurring.tz(), recurring.next_at).format("%Y-%m-%d"),
+        id = recurring.id
+    )
+}
+
+pub fn render_recurring_calls(t: Translator, recurring_calls: &[RecurringCall]) -> String {
+    if recurring_calls.is_empty() {
+        return tr!(t, "recurring.none");
+    }
+
+    let lines = recurring_calls
+        .iter()
+        .map(|recurring| {
+            let recurrence = render_recurrence(t, recurring);
+            match recurring.title.as_str() {
+                "" => tr!(
+                    t,
+                    "recurring.line",
+                    id = recurring.id,
+                    recurrence = recurrence
+                ),
+                title => tr!(
+                    t,
+                    "recurring.line_titled",
+                    id = recurring.id,
+                    title = title,
+                    recurrence = recurrence
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("{}\n{}", tr!(t, "recurring.header"), lines)
+}
+
+/// Announces a new call along with its number, which picks the call while others are open too,
+/// and buttons to respond to it.
+pub fn render_call_started(t: Translator, call: &RollCall) -> Reply {
+    let text = if call.title.is_empty() {
+        tr!(t, "call.started", id = call.id)
+    } else {
+        tr!(t, "call.started_titled", id = call.id, title = call.title)
+    };
+
+    Reply::from(text).with_buttons(render_attendance_buttons(t, call))
+}
+
+pub fn render_attendance_buttons(t: Translator, call: &RollCall) -> Vec<ChatButton> {
+    [
+        ("button.in", "/in"),
+        ("button.out", "/out"),
+        ("button.maybe", "/maybe"),
+    ]
+    .iter()
+    .map(|&(label, command)| ChatButton {
+        label: tr!(t, label),
+        command: format!("{} #{}", command, call.id),
+    })
+    .collect()
+}
+
+pub fn render_no_call(t: Translator, selector: &CallSelector) -> String {
+    match *selector {
+        CallSelector::Latest => tr!(t, "call.none"),
+        _ => tr!(t, "call.no_such"),
+    }
+}
+
+pub fn render_scheduled_end(t: Translator, call_with_responses: &CallWithResponses) -> String {
+    let (call, responses) = call_with_responses;
+    format!(
+        "{}\n\n{}\n\n{}",
+        tr!(t, "call.ended"),
+        call.title,
+        render_responses_full(t, responses)
+    )
+}
+
+pub fn render_announcement(t: Translator, username: &str, status: AttendanceStatus) -> String {
+    render_group_announcement(t, &[username], status)
+}
+
+/// Announces a response. The responses are listed after it, unless the call has a board to show
+/// them, in which case the board is edited instead and a quiet call only announces promotions.
+pub fn render_response_update(t: Translator, username: &str, update: &ResponseUpdate) -> Reply {
+    let call_with_responses = &update.call_with_responses;
+    let (call, _) = call_with_responses;
+
+    let mut lines = vec![];
+    if call.board_message_id.is_none() || !call.quiet {
+        lines.push(render_announcement(t, username, update.status));
+    }
+    if update.refused_guests > 0 {
+        lines.push(render_refused_guests(t, username, update.refused_guests));
+    }
+    if !update.promoted.is_empty() {
+        lines.push(render_promotions(t, &update.promoted));
+    }
+
+    render_list_update(t, lines, call_with_responses)
+}
+
+/// Announces the responses set for several names at once like `render_response_update`, with
+/// one line for each status, e.g. "Dave, Sarah and Tom are in!".
+pub fn render_responses_update(
+    t: Translator,
+    usernames: &[String],
+    updates: &[ResponseUpdate],
+) -> Reply {
+    let call_with_responses = match updates.last() {
+        Some(update) => &update.call_with_responses,
+        None => return Reply::from(""),
+    };
+    let (call, _) = call_with_responses;
+
+    let mut lines = vec![];
+    if call.board_message_id.is_none() || !call.quiet {
+        for &status in [In, Waitlist, Maybe, Out].iter() {
+            let names: Vec<&str> = usernames
+                .iter()
+                .zip(updates.iter())
+                .filter(|(_, update)| update.status == status)
+                .map(|(username, _)| username.as_str())
+                .collect();
+            if !names.is_empty() {
+                lines.push(render_group_announcement(t, &names, status));
+            }
+        }
+    }
+
+    for (username, update) in usernames.iter().zip(updates.iter()) {
+        if update.refused_guests > 0 {
+            lines.push(render_refused_guests(t, username, update.refused_guests));
+        }
+    }
+
+    let promoted: Vec<RollCallResponse> = updates
+        .iter()
+        .flat_map(|update| update.promoted.iter().cloned())
+        .collect();
+    if !promoted.is_empty() {
+        lines.push(render_promotions(t, &promoted));
+    }
+
+    render_list_update(t, lines, call_with_responses)
+}
+
+fn render_group_announcement(t: Translator, names: &[&str], status: AttendanceStatus) -> String {
+    let joined = match names.split_last() {
+        Some((last, others)) if !others.is_empty() => {
+            tr!(t, "names.and", names = others.join(", "), last = last)
+        }
+        _ => names.join(""),
+    };
+
+    let id = match status {
+        In => "announce.in",
+        Out => "announce.out",
+        Maybe => "announce.maybe",
+        Waitlist => "announce.waitlist",
+    };
+    tr_n!(t, id, names.len(), name = joined)
+}
+
+/// Announces a deleted response the same way as `render_response_update`.
+pub fn render_response_removal(t: Translator, username: &str, removal: &ResponseRemoval) -> Reply {
+    let call_with_responses = &removal.call_with_responses;
+    let (call, _) = call_with_responses;
+
+    if !removal.removed {
+        return Reply::from(tr!(t, "response.not_listed", name = username));
+    }
+
+    let mut lines = vec![];
+    if call.board_message_id.is_none() || !call.quiet {
+        lines.push(tr!(t, "response.removed", name = username));
+    }
+    if !removal.promoted.is_empty() {
+        lines.push(render_promotions(t, &removal.promoted));
+    }
+
+    render_list_update(t, lines, call_with_responses)
+}
+
+fn render_list_update(
+    t: Translator,
+    lines: Vec<String>,
+    call_with_responses: &CallWithResponses,
+) -> Reply {
+    let (call, _) = call_with_responses;
+    match call.board_message_id {
+        Some(_) => {
+            Reply::from(lines.join("\n")).with_edits(render_board_edits(t, call_with_responses))
+        }
+        None => Reply::from(format!(
+            "{}\n\n{}",
+            lines.join("\n"),
+            render_responses(t, call_with_responses)
+        )),
+    }
+}
+
+pub fn render_promotions(t: Translator, promoted: &[RollCallResponse]) -> String {
+    promoted
+        .iter()
+        .map(|response| {
+            let user_name = response.user_name.as_ref().map_or("", |s| s.as_str());
+            tr!(t, "response.promoted", name = user_name)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_refused_guests(t: Translator, username: &str, refused_guests: i32) -> String {
+    tr_n!(t, "limit.guests_refused", refused_guests, name = username)
+}
+
+pub fn render_limit(t: Translator, limit: Option<i32>) -> String {
+    match limit {
+        Some(limit) => tr!(t, "limit.set", limit = limit),
+        None => tr!(t, "limit.removed"),
+    }
+}
+
+/// Confirms a new limit, along with whoever it moved off the waitlist. A limit below the places
+/// already taken does not move anyone onto the waitlist, which the confirmation points out.
+pub fn render_limit_update(
+    t: Translator,
+    limit: Option<i32>,
+    call_with_responses: &CallWithResponses,
+    promoted: &[RollCallResponse],
+) -> Reply {
+    let (call, responses) = call_with_responses;
+    let responses_in: Vec<_> = responses
+        .iter()
+        .filter(|response| response.status == In)
+        .collect();
+    let (taken, _) = count_places(&responses_in);
+    let limit_text = match limit {
+        Some(limit) if taken > i64::from(limit) => format!(
+            "{} {}",
+            render_limit(t, Some(limit)),
+            tr!(t, "limit.kept", count = taken)
+        ),
+        _ => render_limit(t, limit),
+    };
+
+    let text = match (promoted.is_empty(), call.board_message_id) {
+        (true, _) => limit_text,
+        (false, Some(_)) => format!("{}\n{}", limit_text, render_promotions(t, promoted)),
+        (false, None) => format!(
+            "{}\n{}\n\n{}",
+            limit_text,
+            render_promotions(t, promoted),
+            render_responses(t, call_with_responses)
+        ),
+    };
+
+    Reply::from(text).with_edits(render_board_edits(t, call_with_responses))
+}
+
+/// The board of a call, which lists all its responses in a single message that is edited as they
+/// change. Buttons to respond are shown while the call is open.
+pub fn render_board(t: Translator, call_with_responses: &CallWithResponses) -> Reply {
+    let (call, responses) = call_with_responses;
+    let header = match (call.status, call.title.as_str()) {
+        (CallStatus::Open, "") => tr!(t, "board.open", id = call.id),
+        (CallStatus::Open, title) => tr!(t, "board.open_titled", id = call.id, title = title),
+        (CallStatus::Closed, "") => tr!(t, "board.closed", id = call.id),
+        (CallStatus::Closed, title) => tr!(t, "board.closed_titled", id = call.id, title = title),
+    };
+
+    let reply = Reply::from(format!(
+        "{}\n\n{}",
+        header,
+        render_responses_full(t, responses)
+    ));
+    match call.status {
+        CallStatus::Open => reply.with_buttons(render_attendance_buttons(t, call)),
+        CallStatus::Closed => reply,
+    }
+}
+
+/// Brings the board of the call up to date, if it has one.
+pub fn render_board_edits(t: Translator, call_with_responses: &CallWithResponses) -> Vec<ChatEdit> {
+    let (call, _) = call_with_responses;
+    call.board_message_id
+        .map(|message_id| {
+            let board = render_board(t, call_with_responses);
+            ChatEdit {
+                message_id,
+                text: board.text,
+                buttons: board.buttons,
+            }
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Marks the board of the call as out of date, if it has one, once it is no longer edited.
+pub fn render_retired_board(t: Translator, call: &RollCall) -> Vec<ChatEdit> {
+    call.board_message_id
+        .map(|message_id| ChatEdit {
+            message_id,
+            text: tr!(t, "board.retired"),
+            buttons: vec![],
+        })
+        .into_iter()
+        .collect()
+}
+
+fn render_on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn render_setting_value(name: &str, settings: &ChatSettings) -> String {
+    match name {
+        "quiet" => render_on_off(settings.quiet).to_owned(),
+        "language" => settings.language.clone(),
+        "timezone" => settings.timezone.clone(),
+        "reply_style" => match settings.reply_style {
+            ReplyStyle::List => "list".to_owned(),
+            ReplyStyle::Board => "board".to_owned(),
+        },
+        "pin" => render_on_off(settings.pin_calls).to_owned(),
+        "answer_unknown" => render_on_off(settings.answer_unknown_commands).to_owned(),
+        _ => String::new(),
+    }
+}
+
+pub fn render_settings(t: Translator, settings: &ChatSettings) -> String {
+    let lines = Setting::NAMES
+        .iter()
+        .map(|name| format!(" {}: {}", name, render_setting_value(name, settings)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "{}\n{}\n\n{}",
+        tr!(t, "settings.header"),
+        lines,
+        tr!(t, "settings.footer")
+    )
+}
+
+pub fn render_setting_update(t: Translator, name: &str, settings: &ChatSettings) -> String {
+    tr!(
+        t,
+        "setting.updated",
+        name = name,
+        value = render_setting_value(name, settings)
+    )
+}
+
+/// Explains the values a setting takes, for when it was given another one.
+pub fn render_setting_usage(t: Translator, name: &str) -> String {
+    let usage = match name {
+        "language" => tr!(
+            t,
+            "setting.usage.language",
+            languages = LANGUAGES.join(", ")
+        ),
+        "timezone" => tr!(t, "setting.usage.timezone"),
+        "reply_style" => tr!(t, "setting.usage.reply_style"),
+        _ => tr!(t, "setting.usage.on_off"),
+    };
+
+    tr!(t, "setting.usage", name = name, usage = usage)
+}
+
+/// Lists the calls that have ended with the number that shows them again with /show_call.
+pub fn render_history(t: Translator, calls_with_responses: &[CallWithResponses], tz: Tz) -> String {
+    if calls_with_responses.is_empty() {
+        return tr!(t, "history.none");
+    }
+
+    let lines = calls_with_responses
+        .iter()
+        .map(|(call, responses)| {
+            let date = utc_to_local(tz, call.created_at).format("%Y-%m-%d");
+            let counts = render_counts(t, responses);
+            match call.title.as_str() {
+                "" => format!(" #{} {}: {}", call.id, date, counts),
+                title => format!(" #{} {} ({}): {}", call.id, title, date, counts),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "{}\n{}\n\n{}",
+        tr!(t, "history.header"),
+        lines,
+        tr!(t, "history.footer")
+    )
+}
+
+pub fn render_past_call(t: Translator, call_with_responses: &CallWithResponses, tz: Tz) -> String {
+    let (call, responses) = call_with_responses;
+    let date = utc_to_local(tz, call.created_at).format("%Y-%m-%d");
+    let header = match call.title.as_str() {
+        "" => tr!(t, "past_call.header", id = call.id, date = date),
+        title => tr!(
+            t,
+            "past_call.header_titled",
+            id = call.id,
+            title = title,
+            date = date
+        ),
+    };
+
+    format!("{}\n\n{}", header, render_responses_full(t, responses))
+}
+
+/// Counts the places taken by those who are in, out or maybe, leaving out the waitlist.
+fn render_counts(t: Translator, responses: &[RollCallResponse]) -> String {
+    let count = |status: AttendanceStatus| {
+        let responses: Vec<&RollCallResponse> = responses
+            .iter()
+            .filter(|response| response.status == status)
+            .collect();
+        count_places(&responses).0
+    };
+
+    tr!(
+        t,
+        "history.counts",
+        in_count = count(In),
+        out_count = count(Out),
+        maybe_count = count(Maybe)
+    )
+}
+
+pub fn render_responses(t: Translator, call_with_responses: &CallWithResponses) -> String {
+    let (call, responses) = call_with_responses;
+
+    if call.quiet {
+        render_responses_short(t, responses)
+    } else {
+        render_responses_full(t, responses)
+    }
+}
+
+pub fn render_responses_short(t: Translator, responses: &[RollCallResponse]) -> String {
+    let responses_by_status = responses.iter().into_groups_by(|response| response.status);
+    let count_by_status = responses_by_status.map_values(|responses| count_places(&responses));
+
+    let render_count = |status: AttendanceStatus, id: &str| {
+        let (count, guests) = count_by_status.get(&status).cloned().unwrap_or((0, 0));
+        match guests {
+            0 => tr_n!(t, id, count),
+            guests => tr!(
+                t,
+                "total.with_guests",
+                total = tr_n!(t, id, count),
+                guests = render_guests(t, guests)
+            ),
+        }
+    };
+
+    use AttendanceStatus::*;
+    let mut counts = vec![
+        render_count(In, "total.in"),
+        render_count(Out, "total.out"),
+        render_count(Maybe, "total.maybe"),
+    ];
+    if count_by_status.contains_key(&Waitlist) {
+        counts.push(render_count(Waitlist, "total.waitlist"));
+    }
+
+    tr!(t, "total", counts = counts.join(", "))
+}
+
+pub fn render_responses_full(t: Translator, responses: &[RollCallResponse]) -> String {
+    fn get_response_line(response: &RollCallResponse) -> String {
+        let user_name = response.user_name.as_ref().map_or("", |s| s.as_str());
+        let user_name = match response.guests {
+            0 => user_name.to_owned(),
+            guests => format!("{} +{}", user_name, guests),
+        };
+
+        match response.reason.as_ref() {
+            Some(reason) if !reason.is_empty() => format!(" - {} ({})", user_name, reason),
+            _ => format!(" - {}", user_name),
+        }
+    }
+
+    fn get_status_line(
+        t: Translator,
+        status: AttendanceStatus,
+        (count, guests): (i64, i64),
+    ) -> String {
+        let count = match guests {
+            0 => count.to_string(),
+            guests => tr!(
+                t,
+                "status.with_guests",
+                count = count,
+                guests = render_guests(t, guests)
+            ),
+        };
+
+        match status {
+            AttendanceStatus::In => tr!(t, "status.in", count = count),
+            AttendanceStatus::Out => tr!(t, "status.out", count = count),
+            AttendanceStatus::Maybe => tr!(t, "status.maybe", count = count),
+            AttendanceStatus::Waitlist => tr!(t, "status.waitlist", count = count),
+        }
+    }
+
+    let responses_by_status = responses.iter().into_groups_by(|response| response.status);
+    let responses_by_status = responses_by_status.map_values(|mut value| {
+        value.sort_by_key(|response| response.updated_at);
+        value
+    });
+
+    use AttendanceStatus::*;
+    let result: Vec<String> = vec![In, Waitlist, Out, Maybe]
+        .into_iter()
+        .filter(|status| responses_by_status.contains_key(&status))
+        .map(|status| {
+            let responses = &responses_by_status[&status];
+            let count = count_places(responses);
+            let response_lines = responses
+                .iter()
+                .map(|&res| get_response_line(res))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let status_line = get_status_line(t, status, count);
+            format!("{}\n{}", status_line, response_lines)
+        })
+        .collect();
+
+    if result.is_empty() {
+        return tr!(t, "responses.none");
+    }
+
+    result.join("\n\n")
+}
+
+/// Counts the places taken by the responses, and how many of them are guests.
+fn count_places(responses: &[&RollCallResponse]) -> (i64, i64) {
+    let guests: i64 = responses
+        .iter()
+        .map(|response| i64::from(response.guests))
+        .sum();
+
+    (responses.len() as i64 + guests, guests)
+}
+
+fn render_guests(t: Translator, guests: i64) -> String {
+    tr_n!(t, "guests", guests)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::util::testutil::factories::*;
+
+    use super::*;
+
+    #[test]
+    fn test_render_available_commands_contains_all_commands() {
+        let t = Translator::default();
+        let actual = render_available_commands(t, &[]);
+        let cmds = vec![
+            "/start_roll_call",
+            "/end_roll_call",
+            "/reopen",
+            "/restart_roll_call",
+            "/schedule_roll_call",
+            "/recurring",
+            "/set_title",
+            "/shh",
+            "/louder",
+            "/set_limit",
+            "/in",
+            "/out",
+            "/maybe",
+            "/set_in_for",
+            "/set_out_for",
+            "/set_maybe_for",
+            "/remove_me",
+            "/remove_for",
+            "/whos_in",
+            "/board",
+            "/pin",
+            "/alias",
+            "/history",
+            "/show_call",
+            "/help",
+            "/available_commands",
+        ];
+
+        for cmd in cmds.iter() {
+            assert!(actual.contains(cmd));
+        }
+    }
+
+    #[test]
+    fn test_render_available_commands_shows_some_beers() {
+        let t = Translator::default();
+        assert!(render_available_commands(t, &[]).contains('🍺'));
+    }
+
+    #[test]
+    fn test_render_available_commands_by_group() {
+        let t = Translator::default();
+        let actual = render_available_commands(t, &[]);
+        let basic = actual
+            .find("Basic commands:\n 🍺 /start_roll_call - ")
+            .unwrap();
+        let attendance = actual.find("Attendance commands:\n 🍺 /in ").unwrap();
+        let other = actual.find("Other commands:\n 🍺 /shh - ").unwrap();
+        assert!(basic < attendance && attendance < other);
+        assert!(actual[basic..attendance].contains("/set_title"));
+        assert!(actual[attendance..other].contains("/set_in_for"));
+        assert!(actual.ends_with("\n\nUse /help <command> for more, e.g. /help set_in_for."));
+    }
+
+    #[test]
+    fn test_render_command_help() {
+        let t = Translator::default();
+        let spec = COMMANDS
+            .iter()
+            .find(|spec| spec.name == "/set_in_for")
+            .unwrap();
+        let expected = "/set_in_for <name> [+guests] [reason]\n\
+                        Let everyone know that someone else will be attending\n\
+                        Put #12 or #title first to pick another open roll call than the latest.\n\
+                        \n\
+                        Examples:\n \
+                        /set_in_for Dave\n \
+                        /set_in_for \"Mary Jane\" +1 running late\n \
+                        /set_in_for Dave, Sarah, Tom";
+        assert_eq!(expected, render_command_help(t, spec, &[]));
+
+        let spec = COMMANDS.iter().find(|spec| spec.name == "/out").unwrap();
+        let actual = render_command_help(Translator::new("de"), spec, &[]);
+        assert!(actual.starts_with(
+            "/out [reason]\nAllen sagen, dass du nicht dabei bist\nAuch: /nein, /khong\n"
+        ));
+    }
+
+    #[test]
+    fn test_render_available_commands_lists_aliases() {
+        let t = Translator::default();
+        let alias = ChatAlias {
+            chat_id: 1,
+            alias: "/yes".to_string(),
+            command: "/in".to_string(),
+            created_at: NaiveDate::from_ymd(2019, 5, 28).and_hms(18, 0, 0),
+        };
+
+        let actual = render_available_commands(t, std::slice::from_ref(&alias));
+        assert!(actual
+            .contains(" 🍺 /in (/dabei, /co, /yes) - Let everyone know you'll be attending\n"));
+        assert!(actual.contains(" 🍺 /available_commands (/start) - List the commands"));
+        assert_eq!("Aliases:\n /yes → /in", render_aliases(t, &[alias]));
+    }
+
+    #[test]
+    fn test_render_call_started() {
+        let t = Translator::default();
+        let reply = render_call_started(t, &create_call());
+        assert_eq!("Roll call #1 started: call title", reply.text);
+
+        let commands: Vec<&str> = reply.buttons.iter().map(|b| b.command.as_str()).collect();
+        assert_eq!(vec!["/in #1", "/out #1", "/maybe #1"], commands);
+    }
+
+    #[test]
+    fn test_render_board() {
+        let t = Translator::default();
+        let call = RollCall {
+            board_message_id: Some(10),
+            ..create_call()
+        };
+        let board = render_board(t, &(call.clone(), create_responses()));
+        assert!(board.text.starts_with("Roll call #1: call title\n\nIn (2)"));
+        assert_eq!(3, board.buttons.len());
+
+        let ended = RollCall {
+            status: CallStatus::Closed,
+            ..call
+        };
+        let edits = render_board_edits(t, &(ended, vec![]));
+        assert_eq!(10, edits[0].message_id);
+        assert_eq!(
+            "Roll call #1 ended: call title\n\nNo responses yet. 😢",
+            edits[0].text
+        );
+        assert!(edits[0].buttons.is_empty());
+
+        assert!(render_board_edits(t, &(create_call(), vec![])).is_empty());
+    }
+
+    #[test]
+    fn test_render_history() {
+        let t = Translator::default();
+        assert_eq!("No past roll calls.", render_history(t, &[], Tz::UTC));
+
+        let created_at = NaiveDate::from_ymd(2019, 5, 7).and_hms(18, 0, 0);
+        let football = RollCall {
+            id: 3,
+            status: CallStatus::Closed,
+            title: "Football".to_string(),
+            created_at,
+            ..create_call()
+        };
+        let untitled = RollCall {
+            title: String::new(),
+            ..football.clone()
+        };
+
+        let calls = [(football, create_responses()), (untitled, vec![])];
+        assert_eq!(
+            "Past roll calls:\n \
+             #3 Football (2019-05-07): 2 in, 1 out, 1 maybe\n \
+             #3 2019-05-07: 0 in, 0 out, 0 maybe\n\n\
+             Use /show_call <number> to see everyone's responses.",
+            render_history(t, &calls, Tz::UTC)
+        );
+
+        // the dates are those of the chat
+        let history = render_history(t, &calls, Tz::Asia__Singapore);
+        assert!(history.contains("#3 Football (2019-05-08)"));
+    }
+
+    #[test]
+    fn test_render_past_call() {
+        let t = Translator::default();
+        let call = RollCall {
+            status: CallStatus::Closed,
+            created_at: NaiveDate::from_ymd(2019, 5, 7).and_hms(18, 0, 0),
+            ..create_call()
+        };
+
+        let text = render_past_call(t, &(call, create_responses()), Tz::UTC);
+        assert!(text.starts_with("Roll call #1: call title (2019-05-07)\n\nIn (2)"));
+    }
+
+    #[test]
+    fn test_render_schedule() {
+        let t = Translator::default();
+        let start_at = NaiveDate::from_ymd(2019, 3, 20).and_hms(18, 0, 0);
+        let scheduled = create_scheduled_call(start_at, None);
+        assert_eq!(
+            "Roll call scheduled for 2019-03-20 18:00 (UTC).",
+            render_schedule(t, &scheduled, Tz::UTC)
+        );
+
+        let end_at = NaiveDate::from_ymd(2019, 3, 21).and_hms(1, 30, 0);
+        let scheduled = create_scheduled_call(start_at, Some(end_at));
+        assert_eq!(
+            "Roll call scheduled from 2019-03-20 18:00 until 2019-03-21 01:30 (UTC).",
+            render_schedule(t, &scheduled, Tz::UTC)
+        );
+
+        assert_eq!(
+            "Roll call scheduled from 2019-03-20 14:00 until 2019-03-20 21:30 (America/New_York).",
+            render_schedule(t, &scheduled, Tz::America__New_York)
+        );
+    }
+
+    #[test]
+    fn test_render_settings() {
+        let t = Translator::default();
+        let settings = ChatSettings {
+            reply_style: ReplyStyle::Board,
+            pin_calls: true,
+            ..ChatSettings::default_for(1)
+        };
+
+        let expected = "Settings:\n quiet: off\n language: en\n timezone: UTC\n \
+                        reply_style: board\n pin: on\n answer_unknown: off\n\n\
+                        Use /settings <name> <value> to change one, e.g. /settings quiet on.";
+        assert_eq!(expected, render_settings(t, &settings));
+        assert_eq!(
+            "Ok, pin is now on.",
+            render_setting_update(t, "pin", &settings)
+        );
+    }
+
+    #[test]
+    fn test_render_recurring_calls() {
+        let t = Translator::default();
+        assert_eq!("No recurring roll calls.", render_recurring_calls(t, &[]));
+
+        let football = create_recurring_call();
+        let untitled = RecurringCall {
+            id: 2,
+            title: "".to_string(),
+            weekday: 6,
+            ..create_recurring_call()
+        };
+
+        let berlin = RecurringCall {
+            id: 3,
+            timezone: "Europe/Berlin".to_string(),
+            ..create_recurring_call()
+        };
+
+        let expected = "Recurring roll calls:\n #1 Football, starts every Tuesday at 18:00 (UTC)\n #2 Starts every Sunday at 18:00 (UTC)\n #3 Football, starts every Tuesday at 18:00 (Europe/Berlin)";
+        let recurring_calls = [football, untitled, berlin];
+        assert_eq!(expected, render_recurring_calls(t, &recurring_calls));
+    }
+
+    #[test]
+    fn test_render_announcement() {
+        let t = Translator::default();
+        assert!(render_announcement(t, "Henry", In).contains("Henry is in!"));
+        assert!(render_announcement(t, "David", Out).contains("David is out!"));
+        assert!(render_announcement(t, "Daniel", Maybe).contains("Daniel might come!"));
+        assert!(render_announcement(t, "Albert", Waitlist).contains("Albert is on the waitlist!"));
+    }
+
+    #[test]
+    fn test_render_responses_update_groups_names() {
+        let t = Translator::default();
+        let responses = create_responses();
+        let update = |status| create_response_update((create_call(), responses.clone()), status);
+        let usernames = vec!["Dave".to_string(), "Sarah".to_string(), "Tom".to_string()];
+
+        let reply = render_responses_update(t, &usernames, &[update(In), update(Out), update(In)]);
+        assert!(reply
+            .text
+            .starts_with("Dave and Tom are in!\nSarah is out!\n\n"));
+        assert!(reply.text.contains(&render_responses_full(t, &responses)));
+
+        let reply = render_responses_update(
+            t,
+            &usernames,
+            &[update(Maybe), update(Maybe), update(Maybe)],
+        );
+        assert!(reply.text.starts_with("Dave, Sarah and Tom might come!"));
+    }
+
+    #[test]
+    fn test_render_response_update_with_refused_guests() {
+        let t = Translator::default();
+        let update = ResponseUpdate {
+            refused_guests: 2,
+            ..create_response_update((create_call(), create_responses()), In)
+        };
+
+        let reply = render_response_update(t, "Dave", &update);
+        assert!(reply.text.starts_with(
+            "Dave is in!\nDave stays in, but there is no room for 2 more guests.\n\n"
+        ));
+    }
+
+    #[test]
+    fn test_render_limit_update_keeps_those_already_in() {
+        let t = Translator::default();
+        let call_with_responses = (create_call(), create_responses());
+
+        let reply = render_limit_update(t, Some(2), &call_with_responses, &[]);
+        assert_eq!("Limit set to 2.", reply.text);
+
+        let reply = render_limit_update(t, Some(1), &call_with_responses, &[]);
+        assert_eq!(
+            "Limit set to 1. The 2 people who are in keep their places, newcomers go on the \
+             waitlist until there is room.",
+            reply.text
+        );
+    }
+
+    #[test]
+    fn test_render_responses_update_in_other_languages() {
+        let responses = create_responses();
+        let update = |status| create_response_update((create_call(), responses.clone()), status);
+        let usernames = vec!["Dave".to_string(), "Tom".to_string()];
+
+        let reply =
+            render_responses_update(Translator::new("de"), &usernames, &[update(In), update(In)]);
+        assert!(reply
+            .text
+            .starts_with("Dave und Tom sind dabei!\n\nDabei (2)"));
+
+        let reply = render_response_update(Translator::new("vi"), "Dave", &update(Out));
+        assert!(reply.text.starts_with("Dave không tham gia!\n\nCó (2)"));
+    }
+
+    #[test]
+    fn test_render_responses_short() {
+        let t = Translator::default();
+        let expected = "Total: 2 in, 1 out, 1 might come.";
+        let actual = render_responses_short(t, &create_responses());
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_render_responses_full() {
+        let t = Translator::default();
+        let actual = render_responses_full(t, &create_responses());
+        let without_space = actual.replace(char::is_whitespace, "");
+
+        let expected = "In(2)-David(willcome)-Henry(alsowillcome)Out(1)-Daniel(won'tcome)Maybe(1)-Albert(mightcome)";
+        assert_eq!(expected, without_space);
+    }
+
+    #[test]
+    fn test_render_responses_with_waitlist() {
+        let t = Translator::default();
+        let mut responses = create_responses();
+        responses[0].status = Waitlist;
+        responses[2].status = Waitlist;
+        responses[2].updated_at = responses[0].updated_at - chrono::Duration::minutes(1);
+
+        let actual = render_responses_full(t, &responses);
+        let without_space = actual.replace(char::is_whitespace, "");
+        let expected = "Waitlist(2)-Henry(alsowillcome)-David(willcome)Out(1)-Daniel(won'tcome)Maybe(1)-Albert(mightcome)";
+        assert_eq!(expected, without_space);
+
+        let expected = "Total: 0 in, 1 out, 1 might come, 2 on the waitlist.";
+        assert_eq!(expected, render_responses_short(t, &responses));
+    }
+
+    #[test]
+    fn test_render_responses_with_guests() {
+        let t = Translator::default();
+        let mut responses = create_responses();
+        responses[0].guests = 2;
+        responses[2].guests = 1;
+        responses[3].guests = 1;
+
+        let actual = render_responses_full(t, &responses);
+        assert!(actual.contains("In (5, incl. 3 guests)"));
+        assert!(actual.contains(" - David +2 (will come)"));
+        assert!(actual.contains("Maybe (2, incl. 1 guest)"));
+
+        let expected = "Total: 5 in (incl. 3 guests), 1 out, 2 might come (incl. 1 guest).";
+        assert_eq!(expected, render_responses_short(t, &responses));
+    }
+
+    #[test]
+    fn test_render_responses_for_quiet_call() {
+        let t = Translator::default();
+        let call_with_response = (create_quiet_call(), create_responses());
+        let actual = render_responses(t, &call_with_response);
+        let expected = "Total: 2 in, 1 out, 1 might come.";
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_render_responses_for_non_quiet_call() {
+        let t = Translator::default();
+        let call_with_response = (create_call(), create_responses());
+        let actual = render_responses(t, &call_with_response);
+        let without_space = actual.replace(char::is_whitespace, "");
+
+        let expected = "In(2)-David(willcome)-Henry(alsowillcome)Out(1)-Daniel(won'tcome)Maybe(1)-Albert(mightcome)";
+        assert_eq!(expected, without_space);
+    }
+}
@@ -0,0 +1,2236 @@
+use std::cell::Cell;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use failure::Error;
+
+use crate::db::Repository;
+use crate::models::{
+    local_to_utc, Attendance, CallStatus, ChatId, ChatSettings, ChatUser, NewRollCallResponse,
+    ReplyStyle, Retention, RollCall, UpdateChatSettings,
+};
+
+use super::commands::{
+    find_command,
+    Command::{self, *},
+    CommandParseError::{self, *},
+    CommandSpec, Setting,
+};
+use super::i18n::Translator;
+use super::transport::{
+    BotResult, ChatCommand, ChatMessage, ChatTransport, HandlerResult, MessageTag, PinChange,
+    Reply, SentMessage, TickResult,
+};
+use super::views::*;
+
+pub struct WhosInBot<T> {
+    transport: T,
+    repository: Box<dyn Repository>,
+    retention: Retention,
+    prune_interval: Duration,
+    next_prune_at: Cell<NaiveDateTime>,
+}
+
+impl<T: ChatTransport> WhosInBot<T> {
+    pub fn new(transport: T, repository: Box<dyn Repository>) -> WhosInBot<T> {
+        let prune_interval = Duration::hours(1);
+        WhosInBot {
+            transport,
+            repository,
+            retention: Retention::default(),
+            prune_interval,
+            next_prune_at: Cell::new(Utc::now().naive_local() + prune_interval),
+        }
+    }
+
+    /// Sets which calls to keep, and how often to delete the others. The first ones are deleted
+    /// once the interval has passed.
+    pub fn with_retention(self, retention: Retention, prune_interval: Duration) -> WhosInBot<T> {
+        let next_prune_at = Utc::now().naive_local() + prune_interval;
+        WhosInBot {
+            retention,
+            prune_interval,
+            next_prune_at: Cell::new(next_prune_at),
+            ..self
+        }
+    }
+
+    pub fn run(&self) -> BotResult {
+        self.transport.run(
+            |cmd| self.handle(cmd),
+            || self.run_scheduled_tasks(),
+            |message| self.record_sent(message),
+        )
+    }
+
+    /// Answers a command in the language of its chat.
+    pub fn handle(&self, chat_command: ChatCommand) -> HandlerResult {
+        let (chat_id, addressed) = (chat_command.chat_id, chat_command.addressed);
+        let settings = self.repository.get_settings(chat_id)?;
+        // the aliases of the chat only matter for names the bot doesn't know
+        let aliases = match find_command(&chat_command.command, &[]) {
+            Some(_) => vec![],
+            None => self.repository.get_aliases(chat_id)?,
+        };
+        let spec = find_command(&chat_command.command, &aliases);
+        // a `#<name>` in front of the parameters only selects a call that goes by that name
+        let open_calls = match spec {
+            Some(spec) if spec.may_select_by_name(&chat_command.command_params) => {
+                Some(self.repository.get_calls(chat_id, CallStatus::Open)?)
+            }
+            _ => None,
+        };
+
+        let t = Translator::new(&settings.language);
+        let parsed = match open_calls {
+            Some(ref open_calls) => {
+                Command::from_chat_with_open_calls(chat_command, &aliases, open_calls)
+            }
+            None => Command::from_chat(chat_command, &aliases),
+        };
+        match parsed {
+            Ok(command) => self
+                .handle_command(command, &settings)
+                .map(|reply| Some(reply).filter(|reply| !reply.is_empty())),
+            Err(UnknownCommand(ref command)) if !addressed && !settings.answer_unknown_commands => {
+                // it may well be for another bot in the chat
+                debug!("Ignoring unknown command {}", command);
+                Ok(None)
+            }
+            Err(parse_error) => Ok(Some(self.handle_parse_error(t, parse_error, spec).into())),
+        }
+    }
+
+    /// Starts and ends the scheduled calls that are due, announcing them in their chats and in
+    /// their languages, and deletes old calls from time to time. The calls are already started or
+    /// ended once they are returned, so a failure after that is reported rather than returned, to
+    /// still announce them.
+    pub fn run_scheduled_tasks(&self) -> TickResult {
+        let now = Utc::now().naive_local();
+        let mut messages = vec![];
+
+        let started_calls = self
+            .repository
+            .start_scheduled_calls(now)
+            .unwrap_or_else(|err| {
+                report_task_error("Failed to start scheduled roll calls", &err.into());
+                vec![]
+            });
+        for call in started_calls {
+            info!("Started scheduled roll call with title '{}'", call.title; "chat_id" => call.chat_id);
+            let settings = self.get_settings_or_default(call.chat_id);
+            messages.push(ChatMessage {
+                chat_id: call.chat_id,
+                reply: self.announce_call(&call, &settings),
+            });
+        }
+
+        let recurring_calls = self
+            .repository
+            .start_recurring_calls(now)
+            .unwrap_or_else(|err| {
+                report_task_error("Failed to start recurring roll calls", &err.into());
+                vec![]
+            });
+        for call in recurring_calls {
+            info!("Started recurring roll call with title '{}'", call.title; "chat_id" => call.chat_id);
+            let settings = self.get_settings_or_default(call.chat_id);
+            messages.push(ChatMessage {
+                chat_id: call.chat_id,
+                reply: self.announce_call(&call, &settings),
+            });
+        }
+
+        let ended_calls = self
+            .repository
+            .end_scheduled_calls(now)
+            .unwrap_or_else(|err| {
+                report_task_error("Failed to end scheduled roll calls", &err.into());
+                vec![]
+            });
+        for call_with_responses in ended_calls {
+            let chat_id = call_with_responses.0.chat_id;
+            info!("Ended scheduled roll call"; "chat_id" => chat_id);
+            let settings = self.get_settings_or_default(chat_id);
+            let pin = self
+                .unpin_ended_call(&call_with_responses.0)
+                .unwrap_or_else(|err| {
+                    report_task_error("Failed to unpin an ended roll call", &err);
+                    None
+                });
+            let t = Translator::new(&settings.language);
+            let reply = Reply {
+                pin,
+                ..Reply::from(render_scheduled_end(t, &call_with_responses))
+                    .with_edits(render_board_edits(t, &call_with_responses))
+            };
+            messages.push(ChatMessage { chat_id, reply });
+        }
+
+        if now >= self.next_prune_at.get() {
+            self.next_prune_at.set(now + self.prune_interval);
+            if let Err(err) = self.prune_old_calls(now) {
+                report_task_error("Failed to delete old roll calls", &err);
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// The settings of a chat for announcing a scheduled call, or the default ones if they fail to
+    /// load, as the call has started or ended either way.
+    fn get_settings_or_default(&self, chat_id: ChatId) -> ChatSettings {
+        self.repository.get_settings(chat_id).unwrap_or_else(|err| {
+            report_task_error("Failed to load the chat settings", &err.into());
+            ChatSettings::default_for(chat_id)
+        })
+    }
+
+    fn prune_old_calls(&self, now: NaiveDateTime) -> Result<(), Error> {
+        let pruned = self.repository.delete_old_calls(&self.retention, now)?;
+        info!(
+            "Deleted {} old roll calls with {} responses",
+            pruned.calls, pruned.responses
+        );
+        Ok(())
+    }
+
+    /// Remembers the messages the bot needs to edit or unpin later.
+    pub fn record_sent(&self, message: SentMessage) -> BotResult {
+        if message.pinned {
+            let call_id = message.tag.call_id();
+            info!("Pinned message {} for roll call {}", message.message_id, call_id; "chat_id" => message.chat_id);
+            self.repository.update_pinned_message(
+                message.chat_id,
+                call_id,
+                Some(message.message_id),
+            )?;
+        }
+
+        match message.tag {
+            MessageTag::Started(_) => {}
+            MessageTag::Board(call_id) => {
+                info!("Showing the board of roll call {} in message {}", call_id, message.message_id; "chat_id" => message.chat_id);
+                self.repository
+                    .update_board(message.chat_id, call_id, Some(message.message_id))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Announces a new call, pinning the announcement if the chat wants calls pinned. Chats that
+    /// prefer boards get the board of the call as the announcement.
+    fn announce_call(&self, call: &RollCall, settings: &ChatSettings) -> Reply {
+        let t = Translator::new(&settings.language);
+        let reply = match settings.reply_style {
+            // the board is recorded once it is sent, see `record_sent`
+            ReplyStyle::Board => {
+                render_board(t, &(call.clone(), vec![])).with_tag(MessageTag::Board(call.id))
+            }
+            ReplyStyle::List if settings.pin_calls => {
+                render_call_started(t, call).with_tag(MessageTag::Started(call.id))
+            }
+            ReplyStyle::List => render_call_started(t, call),
+        };
+
+        if settings.pin_calls {
+            reply.with_pin(PinChange::Reply)
+        } else {
+            reply
+        }
+    }
+
+    /// Unpins the message of a call that has ended. Since a chat only has one pinned message, the
+    /// message of the latest other open call that had one is pinned again instead, if any.
+    fn unpin_ended_call(&self, call: &RollCall) -> Result<Option<PinChange>, Error> {
+        if call.pinned_message_id.is_none() {
+            return Ok(None);
+        }
+
+        let open_calls = self.repository.get_calls(call.chat_id, CallStatus::Open)?;
+        let pin = match open_calls.iter().find_map(|call| call.pinned_message_id) {
+            Some(message_id) => PinChange::Message(message_id),
+            None => PinChange::Unpin,
+        };
+
+        Ok(Some(pin))
+    }
+
+    fn handle_command(&self, command: Command, settings: &ChatSettings) -> Result<Reply, Error> {
+        let t = Translator::new(&settings.language);
+        let response: String = match command {
+            StartRollCall {
+                chat_id,
+                ref title,
+                alongside,
+            } => {
+                info!("Starting roll call with title '{}'", title);
+                let call = if alongside {
+                    self.repository.add_call(chat_id, title)?
+                } else {
+                    self.repository.create_call(chat_id, title)?
+                };
+                return Ok(self.announce_call(&call, settings));
+            }
+
+            EndRollCall {
+                chat_id,
+                ref selector,
+            } => {
+                info!("Ending roll call {:?}", selector);
+                match self.repository.end_call(chat_id, selector)? {
+                    None => render_no_call(t, selector),
+                    Some(ref call_with_responses) => {
+                        let edits = render_board_edits(t, call_with_responses);
+                        return Ok(Reply {
+                            pin: self.unpin_ended_call(&call_with_responses.0)?,
+                            ..Reply::from(tr!(t, "call.ended")).with_edits(edits)
+                        });
+                    }
+                }
+            }
+
+            RestartRollCall {
+                chat_id,
+                ref title,
+                keep,
+            } => {
+                info!(
+                    "Restarting the latest roll call, keeping responses: {}",
+                    keep
+                );
+                match self.repository.restart_call(chat_id, title, keep)? {
+                    None => tr!(t, "call.no_restart"),
+                    Some(ref restarted) => {
+                        let (call, responses) = &restarted.call_with_responses;
+                        let (previous_call, _) = &restarted.previous_call;
+
+                        // the previous call ends as with /end_roll_call, unless the new call is
+                        // pinned in its place
+                        let reply = self
+                            .announce_call(call, settings)
+                            .with_edits(render_board_edits(t, &restarted.previous_call));
+                        let reply = match reply.pin {
+                            Some(_) => reply,
+                            None => Reply {
+                                pin: self.unpin_ended_call(previous_call)?,
+                                ..reply
+                            },
+                        };
+                        if responses.is_empty() {
+                            return Ok(reply);
+                        }
+
+                        let text = format!(
+                            "{}\n\n{}",
+                            reply.text,
+                            render_responses(t, &restarted.call_with_responses)
+                        );
+                        return Ok(Reply { text, ..reply });
+                    }
+                }
+            }
+
+            ReopenRollCall { chat_id } => {
+                info!("Reopening the latest roll call");
+                match self.repository.reopen_call(chat_id)? {
+                    None => tr!(t, "call.no_reopen"),
+                    Some(ref call_with_responses) => {
+                        let (call, _) = call_with_responses;
+                        let text = format!(
+                            "{}\n\n{}",
+                            tr!(t, "call.reopened"),
+                            render_responses(t, call_with_responses)
+                        );
+                        let reply = Reply::from(text)
+                            .with_edits(render_board_edits(t, call_with_responses));
+                        return match call.pinned_message_id {
+                            Some(message_id) if settings.pin_calls => {
+                                Ok(reply.with_pin(PinChange::Message(message_id)))
+                            }
+                            _ => Ok(reply),
+                        };
+                    }
+                }
+            }
+
+            ScheduleRollCall {
+                chat_id,
+                ref title,
+                start_at,
+                end_at,
+            } => {
+                // the times were given in the timezone of the chat
+                let tz = settings.tz();
+                let to_utc = |at| local_to_utc(tz, at);
+                let (start_at, end_at) = (to_utc(start_at), end_at.map(to_utc));
+                if start_at <= Utc::now().naive_local() {
+                    tr!(t, "schedule.passed")
+                } else {
+                    info!(
+                        "Scheduling roll call with title '{}' at {}",
+                        title, start_at
+                    );
+                    let scheduled = self
+                        .repository
+                        .create_scheduled_call(chat_id, title, start_at, end_at)?;
+                    render_schedule(t, &scheduled, tz)
+                }
+            }
+
+            AddRecurringCall {
+                chat_id,
+                ref title,
+                weekday,
+                start_time,
+            } => {
+                // the time was given in the timezone of the chat, and stays that local time
+                info!(
+                    "Adding weekly roll call with title '{}' on {:?} at {}",
+                    title, weekday, start_time
+                );
+                let recurring = self
+                    .repository
+                    .create_recurring_call(chat_id, title, weekday, start_time)?;
+                render_recurring_call_added(t, &recurring)
+            }
+
+            ListRecurringCalls { chat_id } => {
+                let recurring_calls = self.repository.get_recurring_calls(chat_id)?;
+                render_recurring_calls(t, &recurring_calls)
+            }
+
+            CancelRecurringCall { chat_id, id } => {
+                info!("Cancelling recurring roll call {}", id);
+                match self.repository.delete_recurring_call(chat_id, id)? {
+                    true => tr!(t, "recurring.cancelled"),
+                    false => tr!(t, "recurring.no_such"),
+                }
+            }
+
+            UpdateTitle {
+                chat_id,
+                ref selector,
+                ref title,
+            } => {
+                info!("Updating roll call title to '{}'", title);
+                match self.repository.update_title(chat_id, selector, title)? {
+                    None => render_no_call(t, selector),
+                    Some(ref call_with_responses) => {
+                        let edits = render_board_edits(t, call_with_responses);
+                        return Ok(Reply::from(tr!(t, "call.title_set")).with_edits(edits));
+                    }
+                }
+            }
+
+            UpdateQuiet { chat_id, quiet } => {
+                info!("Updating roll call quiet to '{}'", quiet);
+                match (self.repository.update_quiet(chat_id, quiet)?, quiet) {
+                    (None, _) => tr!(t, "call.none"),
+                    (Some(_), true) => tr!(t, "call.quiet"),
+                    (Some((ref call, _)), false) if call.board_message_id.is_some() => {
+                        tr!(t, "call.louder")
+                    }
+                    (Some(ref call_with_responses), false) => format!(
+                        "{}\n\n{}",
+                        tr!(t, "call.louder"),
+                        render_responses(t, call_with_responses)
+                    ),
+                }
+            }
+
+            UpdateLimit { chat_id, limit } => {
+                info!("Updating roll call limit to {:?}", limit);
+                match self.repository.update_limit(chat_id, limit)? {
+                    None => tr!(t, "call.none"),
+                    Some((ref call_with_responses, ref promoted)) => {
+                        return Ok(render_limit_update(t, limit, call_with_responses, promoted));
+                    }
+                }
+            }
+
+            UpdateAttendanceSelf {
+                chat_id,
+                selector,
+                user_id,
+                username,
+                status,
+                reason,
+                guests,
+            } => {
+                info!("Setting own attendance for {} to '{}'", username, status);
+                let attendance = Attendance::new(status, reason).with_guests(guests);
+                match self.repository.set_response(
+                    chat_id,
+                    &selector,
+                    &ChatUser::new(user_id, &username),
+                    &attendance,
+                )? {
+                    None => render_no_call(t, &selector),
+                    Some(ref update) => return Ok(render_response_update(t, &username, update)),
+                }
+            }
+
+            UpdateAttendanceFor {
+                chat_id,
+                selector,
+                username,
+                status,
+                reason,
+                guests,
+            } => {
+                info!("Setting attendance for {} to '{}'", username, status);
+                let attendance = Attendance::new(status, reason).with_guests(guests);
+                match self.repository.set_response_for(
+                    chat_id,
+                    &selector,
+                    &username,
+                    &attendance,
+                )? {
+                    None => render_no_call(t, &selector),
+                    Some(ref update) => return Ok(render_response_update(t, &username, update)),
+                }
+            }
+
+            UpdateAttendanceForAll {
+                chat_id,
+                selector,
+                status,
+                attendees,
+            } => {
+                let usernames: Vec<String> = attendees
+                    .iter()
+                    .map(|attendee| attendee.username.clone())
+                    .collect();
+                info!(
+                    "Setting attendance for {} to '{}'",
+                    usernames.join(", "),
+                    status
+                );
+                let responses: Vec<(String, Attendance)> = attendees
+                    .into_iter()
+                    .map(|attendee| {
+                        let attendance =
+                            Attendance::new(status, attendee.reason).with_guests(attendee.guests);
+                        (attendee.username, attendance)
+                    })
+                    .collect();
+                match self
+                    .repository
+                    .set_responses_for(chat_id, &selector, &responses)?
+                {
+                    None => render_no_call(t, &selector),
+                    Some(ref updates) => {
+                        return Ok(render_responses_update(t, &usernames, updates))
+                    }
+                }
+            }
+
+            RemoveResponseSelf {
+                chat_id,
+                selector,
+                user_id,
+                username,
+            } => {
+                info!("Removing own response of {}", username);
+                let token = NewRollCallResponse::self_token(user_id);
+                match self
+                    .repository
+                    .delete_response(chat_id, &selector, &token)?
+                {
+                    None => render_no_call(t, &selector),
+                    Some(ref removal) => return Ok(render_response_removal(t, &username, removal)),
+                }
+            }
+
+            RemoveResponseFor {
+                chat_id,
+                selector,
+                username,
+            } => {
+                info!("Removing response for {}", username);
+                let token = NewRollCallResponse::for_token(&username);
+                match self
+                    .repository
+                    .delete_response(chat_id, &selector, &token)?
+                {
+                    None => render_no_call(t, &selector),
+                    Some(ref removal) => return Ok(render_response_removal(t, &username, removal)),
+                }
+            }
+
+            GetAllAttendances {
+                chat_id,
+                ref selector,
+            } => match self.repository.get_call_with_responses(chat_id, selector)? {
+                None => render_no_call(t, selector),
+                Some((ref call, ref responses)) => {
+                    format!("{}\n\n{}", call.title, render_responses_full(t, responses))
+                }
+            },
+
+            UpdateBoard {
+                chat_id,
+                ref selector,
+                enabled,
+            } => match self.repository.get_call_with_responses(chat_id, selector)? {
+                None => render_no_call(t, selector),
+                Some(ref call_with_responses) if enabled => {
+                    // the board is recorded once it is sent, see `record_sent`
+                    let (call, _) = call_with_responses;
+                    info!("Posting the board of roll call {}", call.id);
+                    let board = render_board(t, call_with_responses)
+                        .with_edits(render_retired_board(t, call))
+                        .with_tag(MessageTag::Board(call.id));
+                    if settings.pin_calls {
+                        return Ok(board.with_pin(PinChange::Reply));
+                    }
+                    return Ok(board);
+                }
+                Some((ref call, _)) => {
+                    info!("Removing the board of roll call {}", call.id);
+                    self.repository.update_board(chat_id, call.id, None)?;
+                    let edits = render_retired_board(t, call);
+                    return Ok(Reply::from(tr!(t, "board.removed")).with_edits(edits));
+                }
+            },
+
+            UpdateAnswerUnknown { chat_id, answer } => {
+                info!("Updating answering of unknown commands to '{}'", answer);
+                let update = UpdateChatSettings::new().with_answer_unknown_commands(answer);
+                self.repository.update_settings(chat_id, &update)?;
+                match answer {
+                    true => tr!(t, "answer_unknown.on"),
+                    false => tr!(t, "answer_unknown.off"),
+                }
+            }
+
+            ShowSettings { .. } => render_settings(t, settings),
+
+            UpdateSetting {
+                chat_id,
+                ref setting,
+            } => {
+                info!("Updating setting {:?}", setting);
+                let updated = self
+                    .repository
+                    .update_settings(chat_id, &setting.to_update())?;
+                // a new language is confirmed in that language
+                let t = Translator::new(&updated.language);
+                render_setting_update(t, setting.name(), &updated)
+            }
+
+            UpdatePinCalls { chat_id, pin_calls } => {
+                info!("Updating pinning of roll calls to '{}'", pin_calls);
+                let update = UpdateChatSettings::new().with_pin_calls(pin_calls);
+                self.repository.update_settings(chat_id, &update)?;
+                match pin_calls {
+                    true => tr!(t, "pin.on"),
+                    false => tr!(t, "pin.off"),
+                }
+            }
+
+            ShowHistory { chat_id } => {
+                let calls_with_responses = self
+                    .repository
+                    .get_calls_with_responses(chat_id, CallStatus::Closed)?;
+                render_history(t, &calls_with_responses, settings.tz())
+            }
+
+            ShowCall { chat_id, call_id } => {
+                match self.repository.get_closed_call(chat_id, call_id)? {
+                    Some(ref call_with_responses) => {
+                        render_past_call(t, call_with_responses, settings.tz())
+                    }
+                    None => tr!(t, "history.no_such"),
+                }
+            }
+
+            ListAliases { chat_id } => render_aliases(t, &self.repository.get_aliases(chat_id)?),
+
+            AddAlias {
+                chat_id,
+                ref alias,
+                ref command,
+            } => {
+                info!("Adding alias {} for {}", alias, command);
+                self.repository.add_alias(chat_id, alias, command)?;
+                tr!(t, "alias.added", alias = alias, command = command)
+            }
+
+            RemoveAlias { chat_id, ref alias } => {
+                info!("Removing alias {}", alias);
+                match self.repository.delete_alias(chat_id, alias)? {
+                    true => tr!(t, "alias.removed", alias = alias),
+                    false => tr!(t, "alias.no_such", alias = alias),
+                }
+            }
+
+            ListAvailableCommands { chat_id } => {
+                render_available_commands(t, &self.repository.get_aliases(chat_id)?)
+            }
+
+            ShowHelp { chat_id, command } => {
+                let aliases = self.repository.get_aliases(chat_id)?;
+                match find_command(&command, &aliases) {
+                    Some(spec) => render_command_help(t, spec, &aliases),
+                    None => tr!(t, "help.unknown", command = command),
+                }
+            }
+        };
+
+        Ok(response.into())
+    }
+
+    /// Explains what was wrong with a command, pointing to the help of the command if the bot knows
+    /// it.
+    fn handle_parse_error(
+        &self,
+        t: Translator,
+        parse_error: CommandParseError,
+        spec: Option<&CommandSpec>,
+    ) -> String {
+        let message = match parse_error {
+            MissingTitle => tr!(t, "error.missing_title"),
+            MissingUsername => tr!(t, "error.missing_username"),
+            InvalidLimit => tr!(t, "error.invalid_limit"),
+            InvalidGuests => tr!(t, "error.invalid_guests"),
+            UnterminatedQuote => tr!(t, "error.unterminated_quote"),
+            InvalidSetting(name) => tr!(
+                t,
+                "setting.invalid",
+                name = name,
+                names = Setting::NAMES.join(", ")
+            ),
+            InvalidSettingValue(name) => render_setting_usage(t, &name),
+            InvalidMention(mention) => tr!(t, "error.invalid_mention", mention = mention),
+            InvalidCallNumber => tr!(t, "error.invalid_call_number"),
+            InvalidRecurrence => tr!(t, "error.invalid_recurrence"),
+            InvalidSchedule => tr!(t, "error.invalid_schedule"),
+            InvalidAlias => tr!(t, "error.invalid_alias"),
+            AliasTaken(alias) => tr!(t, "error.alias_taken", alias = alias),
+            InvalidAliasCommand(command) => {
+                tr!(t, "error.invalid_alias_command", command = command)
+            }
+            InvalidCommand(_command) | UnknownCommand(_command) => tr!(t, "error.unknown_command"),
+        };
+        match spec {
+            Some(spec) => format!(
+                "{} {}",
+                message,
+                tr!(t, "help.see", command = &spec.name[1..])
+            ),
+            None => message,
+        }
+    }
+}
+
+/// Reports an error of a scheduled task, which does not stop the other tasks.
+fn report_task_error(message: &str, err: &Error) {
+    error!("{}: {}", message, err; "details" => format!("{:?}", err));
+    sentry::integrations::failure::capture_error(err);
+}
+
+#[cfg(test)]
+mod tests {
+    use mockers::{
+        matchers::{check, ANY},
+        Scenario,
+    };
+
+    use crate::db::{DatabaseError, RepositoryMock};
+    use crate::models::*;
+    use crate::util::testutil::{factories::*, FakeTransport};
+
+    use super::*;
+
+    /// Creates a repository for chats with the default settings and no aliases, unless a test
+    /// expects others.
+    fn create_repo(scenario: &Scenario) -> RepositoryMock {
+        let repo = scenario.create_mock_for::<Repository>();
+        scenario.expect(
+            repo.get_settings_call(ANY)
+                .and_call_clone(|chat_id| Ok(ChatSettings::default_for(chat_id)))
+                .times(..),
+        );
+        scenario.expect(
+            repo.get_aliases_call(ANY)
+                .and_call_clone(|_| Ok(vec![]))
+                .times(..),
+        );
+        repo
+    }
+
+    #[test]
+    fn run_replies_through_transport() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.end_call_call(2, &CallSelector::Latest)
+                .and_return(Ok(None)),
+        );
+        scenario.expect(repo.start_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.start_recurring_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.end_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+
+        let transport = FakeTransport::new(vec![
+            create_command("/end_roll_call", ""),
+            ChatCommand {
+                addressed: true,
+                ..create_command("/unknown@WhosInBot", "")
+            },
+        ]);
+
+        let replies = transport.replies.clone();
+        let bot = WhosInBot::new(transport, Box::new(repo));
+        bot.run().unwrap();
+
+        let replies: Vec<String> = replies.borrow().iter().map(|r| r.text.clone()).collect();
+        assert_eq!(
+            vec!["No roll call in progress.", "I don't understand that."],
+            replies
+        );
+    }
+
+    #[test]
+    fn run_sends_scheduled_messages() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let started = RollCall {
+            chat_id: 3,
+            ..create_call()
+        };
+        let recurring = RollCall {
+            chat_id: 4,
+            title: "Football".to_string(),
+            ..create_call()
+        };
+        let ended = RollCall {
+            status: CallStatus::Closed,
+            ..create_call()
+        };
+
+        scenario.expect(
+            repo.start_scheduled_calls_call(ANY)
+                .and_return(Ok(vec![started])),
+        );
+        scenario.expect(
+            repo.start_recurring_calls_call(ANY)
+                .and_return(Ok(vec![recurring])),
+        );
+        scenario.expect(
+            repo.end_scheduled_calls_call(ANY)
+                .and_return(Ok(vec![(ended, create_responses())])),
+        );
+        scenario.expect(
+            repo.get_settings_call(3)
+                .and_return(Ok(ChatSettings::default_for(3))),
+        );
+        scenario.expect(repo.get_settings_call(4).and_return(Ok(ChatSettings {
+            language: "de".to_string(),
+            ..ChatSettings::default_for(4)
+        })));
+
+        let transport = FakeTransport::default();
+        let messages = transport.messages.clone();
+        let bot = WhosInBot::new(transport, Box::new(repo));
+        bot.run().unwrap();
+
+        let messages = messages.borrow();
+        assert_eq!(3, messages.len());
+        assert_eq!(3, messages[0].chat_id);
+        assert_eq!("Roll call #1 started: call title", messages[0].reply.text);
+        assert_eq!(4, messages[1].chat_id);
+        assert_eq!("Umfrage #1 gestartet: Football", messages[1].reply.text);
+        assert_eq!(2, messages[2].chat_id);
+        assert!(messages[2]
+            .reply
+            .text
+            .starts_with("Roll call ended.\n\ncall title\n\nIn (2)"));
+    }
+
+    #[test]
+    fn run_announces_started_calls_despite_later_failures() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let started = RollCall {
+            chat_id: 3,
+            ..create_call()
+        };
+        let ended = RollCall {
+            status: CallStatus::Closed,
+            pinned_message_id: Some(10),
+            ..create_call()
+        };
+        let failure = || DatabaseError::QueryError(diesel::result::Error::NotFound);
+
+        scenario.expect(
+            repo.start_scheduled_calls_call(ANY)
+                .and_return(Ok(vec![started])),
+        );
+        scenario.expect(
+            repo.start_recurring_calls_call(ANY)
+                .and_return(Err(failure())),
+        );
+        scenario.expect(
+            repo.end_scheduled_calls_call(ANY)
+                .and_return(Ok(vec![(ended, create_responses())])),
+        );
+        scenario.expect(repo.get_settings_call(3).and_return(Err(failure())));
+        scenario.expect(
+            repo.get_calls_call(2, CallStatus::Open)
+                .and_return(Err(failure())),
+        );
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let messages = bot.run_scheduled_tasks().unwrap();
+
+        assert_eq!(2, messages.len());
+        assert_eq!("Roll call #1 started: call title", messages[0].reply.text);
+        assert_eq!(None, messages[1].reply.pin);
+        assert!(messages[1].reply.text.starts_with("Roll call ended."));
+    }
+
+    #[test]
+    fn run_deletes_old_calls_once_due() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let started = RollCall {
+            chat_id: 3,
+            ..create_call()
+        };
+        scenario.expect(
+            repo.start_scheduled_calls_call(ANY)
+                .and_return(Ok(vec![started])),
+        );
+        scenario.expect(repo.start_recurring_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.end_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(
+            repo.get_settings_call(3)
+                .and_return(Ok(ChatSettings::default_for(3))),
+        );
+        scenario.expect(repo.delete_old_calls_call(ANY, ANY).and_return(Err(
+            DatabaseError::QueryError(diesel::result::Error::NotFound),
+        )));
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo))
+            .with_retention(Retention::default(), Duration::zero());
+
+        // a failure to delete does not hold back the announcements
+        let messages = bot.run_scheduled_tasks().unwrap();
+        assert_eq!(1, messages.len());
+    }
+
+    #[test]
+    fn handle_start_roll_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        scenario.expect(
+            repo.create_call_call(2, arg!("call title"))
+                .and_return(Ok(call)),
+        );
+        scenario.expect(
+            repo.get_settings_call(2)
+                .and_return(Ok(ChatSettings::default_for(2))),
+        );
+
+        let command = create_command("/start_roll_call", "call title");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!("Roll call #1 started: call title", reply.text);
+        assert_eq!(3, reply.buttons.len());
+        assert_eq!("/in #1", reply.buttons[0].command);
+        assert_eq!(None, reply.pin);
+    }
+
+    #[test]
+    fn handle_start_roll_call_alongside_open_calls() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.add_call_call(2, arg!("call title"))
+                .and_return(Ok(create_call())),
+        );
+
+        let command = create_command("/start_roll_call", "call title --alongside");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!("Roll call #1 started: call title", reply.text);
+    }
+
+    #[test]
+    fn run_pins_started_roll_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let settings = ChatSettings {
+            pin_calls: true,
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(
+            repo.create_call_call(2, arg!("call title"))
+                .and_return(Ok(create_call())),
+        );
+        scenario.expect(repo.get_settings_call(2).and_return(Ok(settings)));
+        scenario.expect(
+            repo.update_pinned_message_call(2, 1, Some(1))
+                .and_return(Ok(true)),
+        );
+        scenario.expect(repo.start_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.start_recurring_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.end_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+
+        let transport = FakeTransport::new(vec![create_command("/start_roll_call", "call title")]);
+
+        let replies = transport.replies.clone();
+        let bot = WhosInBot::new(transport, Box::new(repo));
+        bot.run().unwrap();
+
+        let replies = replies.borrow();
+        assert_eq!(Some(MessageTag::Started(1)), replies[0].tag);
+        assert_eq!(Some(PinChange::Reply), replies[0].pin);
+    }
+
+    #[test]
+    fn run_posts_board_of_started_roll_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let settings = ChatSettings {
+            reply_style: ReplyStyle::Board,
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(
+            repo.create_call_call(2, arg!("call title"))
+                .and_return(Ok(create_call())),
+        );
+        scenario.expect(repo.get_settings_call(2).and_return(Ok(settings)));
+        scenario.expect(repo.update_board_call(2, 1, Some(1)).and_return(Ok(true)));
+        scenario.expect(repo.start_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.start_recurring_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.end_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+
+        let transport = FakeTransport::new(vec![create_command("/start_roll_call", "call title")]);
+
+        let replies = transport.replies.clone();
+        let bot = WhosInBot::new(transport, Box::new(repo));
+        bot.run().unwrap();
+
+        let replies = replies.borrow();
+        assert_eq!(Some(MessageTag::Board(1)), replies[0].tag);
+        assert_eq!(None, replies[0].pin);
+    }
+
+    #[test]
+    fn handle_update_setting() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let settings = ChatSettings {
+            quiet: true,
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(
+            repo.update_settings_call(
+                2,
+                check(|update: &&UpdateChatSettings| update.quiet == Some(true)),
+            )
+            .and_return(Ok(settings)),
+        );
+
+        let command = create_command("/settings", "quiet on");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert_eq!("Ok, quiet is now on.", result);
+    }
+
+    #[test]
+    fn handle_replies_in_the_language_of_the_chat() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let settings = ChatSettings {
+            language: "de".to_string(),
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(
+            repo.get_settings_call(2)
+                .and_call_clone(move |_| Ok(settings.clone()))
+                .times(2),
+        );
+        scenario.expect(
+            repo.update_settings_call(
+                2,
+                check(|update: &&UpdateChatSettings| update.language == Some("vi".to_string())),
+            )
+            .and_return(Ok(ChatSettings {
+                language: "vi".to_string(),
+                ..ChatSettings::default_for(2)
+            })),
+        );
+        scenario.expect(
+            repo.end_call_call(3, &CallSelector::Latest)
+                .and_return(Ok(None)),
+        );
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = |command| bot.handle(command).unwrap().unwrap().text;
+        assert_eq!(
+            "Bitte gib einen Titel an. Siehe /help set_title.",
+            reply(create_command("/set_title", ""))
+        );
+        assert_eq!(
+            "Được, language giờ là vi.",
+            reply(create_command("/settings", "language vi"))
+        );
+        assert_eq!(
+            "No roll call in progress.",
+            reply(ChatCommand {
+                chat_id: 3,
+                ..create_command("/end_roll_call", "")
+            })
+        );
+    }
+
+    #[test]
+    fn handle_commands_by_the_aliases_of_the_chat() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let alias = ChatAlias {
+            chat_id: 2,
+            alias: "/yes".to_string(),
+            command: "/whos_in".to_string(),
+            created_at: Utc::now().naive_local(),
+        };
+        scenario.expect(
+            repo.add_alias_call(2, "/yes", "/whos_in")
+                .and_return(Ok(alias.clone())),
+        );
+        scenario.expect(repo.get_aliases_call(2).and_return(Ok(vec![alias])));
+        scenario.expect(
+            repo.get_call_with_responses_call(2, &CallSelector::Latest)
+                .and_return(Ok(None)),
+        );
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = |command| bot.handle(command).unwrap().unwrap().text;
+        assert_eq!(
+            "Ok, /yes now does the same as /whos_in.",
+            reply(create_command("/alias", "add yes whos_in"))
+        );
+        assert_eq!(
+            "No roll call in progress.",
+            reply(create_command("/yes", ""))
+        );
+    }
+
+    #[test]
+    fn handle_help_for_a_command() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let alias = ChatAlias {
+            chat_id: 2,
+            alias: "/yes".to_string(),
+            command: "/in".to_string(),
+            created_at: Utc::now().naive_local(),
+        };
+        scenario.expect(
+            repo.get_aliases_call(2)
+                .and_call_clone(move |_| Ok(vec![alias.clone()]))
+                .times(2),
+        );
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = |command| bot.handle(command).unwrap().unwrap().text;
+        assert!(reply(create_command("/help", "yes"))
+            .starts_with("/in [+guests] [reason]\nLet everyone know you'll be attending\nAlso: /dabei, /co, /yes\n"));
+        assert_eq!(
+            "There is no command /nope, see /help.",
+            reply(create_command("/help", "nope"))
+        );
+    }
+
+    #[test]
+    fn handle_end_roll_call_unpins_its_message() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            status: CallStatus::Closed,
+            pinned_message_id: Some(5),
+            ..create_call()
+        };
+        scenario.expect(
+            repo.end_call_call(2, &CallSelector::Latest)
+                .and_return(Ok(Some((call, vec![])))),
+        );
+        scenario.expect(
+            repo.get_calls_call(2, CallStatus::Open)
+                .and_return(Ok(vec![create_call()])),
+        );
+
+        let command = create_command("/end_roll_call", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!(Some(PinChange::Unpin), reply.pin);
+    }
+
+    #[test]
+    fn handle_end_roll_call_pins_other_open_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            status: CallStatus::Closed,
+            pinned_message_id: Some(5),
+            ..create_call()
+        };
+        let other_call = RollCall {
+            id: 2,
+            pinned_message_id: Some(3),
+            ..create_call()
+        };
+        scenario.expect(
+            repo.end_call_call(2, &CallSelector::Latest)
+                .and_return(Ok(Some((call, vec![])))),
+        );
+        scenario.expect(
+            repo.get_calls_call(2, CallStatus::Open)
+                .and_return(Ok(vec![create_call(), other_call])),
+        );
+
+        let command = create_command("/end_roll_call", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!(Some(PinChange::Message(3)), reply.pin);
+    }
+
+    #[test]
+    fn handle_pin() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let settings = ChatSettings {
+            pin_calls: true,
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(
+            repo.update_settings_call(
+                2,
+                check(|update: &&UpdateChatSettings| update.pin_calls == Some(true)),
+            )
+            .and_return(Ok(settings)),
+        );
+
+        let command = create_command("/pin", "on");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert!(reply.text.starts_with("Ok, I'll pin new roll calls"));
+    }
+
+    #[test]
+    fn handle_end_roll_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            status: CallStatus::Closed,
+            ..create_call()
+        };
+
+        scenario.expect(
+            repo.end_call_call(2, &CallSelector::Latest)
+                .and_return(Ok(Some((call, vec![])))),
+        );
+
+        let command = create_command("/end_roll_call", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("Roll call ended.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_restart_roll_call_keeping_responses() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let responses = create_responses()
+            .into_iter()
+            .map(|response| RollCallResponse {
+                status: AttendanceStatus::Maybe,
+                ..response
+            })
+            .collect();
+        let previous_call = RollCall {
+            status: CallStatus::Closed,
+            board_message_id: Some(5),
+            ..create_call()
+        };
+        let restarted = RestartedCall {
+            call_with_responses: (create_call(), responses),
+            previous_call: (previous_call, create_responses()),
+        };
+        scenario.expect(
+            repo.restart_call_call(2, arg!(""), true)
+                .and_return(Ok(Some(restarted))),
+        );
+        scenario.expect(
+            repo.get_settings_call(2)
+                .and_return(Ok(ChatSettings::default_for(2))),
+        );
+
+        let command = create_command("/restart_roll_call", "--keep");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert!(reply
+            .text
+            .starts_with("Roll call #1 started: call title\n\nMaybe (4)"));
+        assert_eq!(3, reply.buttons.len());
+        // the board of the restarted call is brought up to date as it ends
+        assert_eq!(5, reply.edits[0].message_id);
+        assert_eq!(None, reply.pin);
+    }
+
+    #[test]
+    fn handle_remove_me() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let removal = ResponseRemoval {
+            call_with_responses: (create_call(), create_responses()),
+            removed: true,
+            promoted: vec![],
+        };
+        let token = NewRollCallResponse::self_token(1);
+        scenario.expect(
+            repo.delete_response_call(2, &CallSelector::Latest, check(move |t: &&str| *t == token))
+                .and_return(Ok(Some(removal))),
+        );
+
+        let command = create_command("/remove_me", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert!(reply.text.starts_with("User 1 is off the list.\n\nIn (2)"));
+    }
+
+    #[test]
+    fn handle_remove_for_not_on_list() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let removal = ResponseRemoval {
+            call_with_responses: (create_call(), vec![]),
+            removed: false,
+            promoted: vec![],
+        };
+        let token = NewRollCallResponse::for_token("peter");
+        scenario.expect(
+            repo.delete_response_call(2, &CallSelector::Id(1), check(move |t: &&str| *t == token))
+                .and_return(Ok(Some(removal))),
+        );
+
+        let command = create_command("/remove_for", "#1 Peter");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!("Peter is not on the list.", reply.text);
+    }
+
+    #[test]
+    fn handle_remove_me_no_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.delete_response_call(2, &CallSelector::Latest, ANY)
+                .and_return(Ok(None)),
+        );
+
+        let command = create_command("/remove_me", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!("No roll call in progress.", reply.text);
+    }
+
+    #[test]
+    fn handle_reopen() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            board_message_id: Some(4),
+            ..create_call()
+        };
+        scenario.expect(
+            repo.reopen_call_call(2)
+                .and_return(Ok(Some((call, create_responses())))),
+        );
+
+        let command = create_command("/reopen", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert!(reply.text.starts_with("Roll call reopened.\n\nIn (2)"));
+        assert_eq!(4, reply.edits[0].message_id);
+        assert_eq!(3, reply.edits[0].buttons.len());
+        assert_eq!(None, reply.pin);
+    }
+
+    #[test]
+    fn handle_reopen_pins_again() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            pinned_message_id: Some(5),
+            ..create_call()
+        };
+        let settings = ChatSettings {
+            pin_calls: true,
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(
+            repo.reopen_call_call(2)
+                .and_return(Ok(Some((call, vec![])))),
+        );
+        scenario.expect(repo.get_settings_call(2).and_return(Ok(settings)));
+
+        let command = create_command("/reopen", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!(Some(PinChange::Message(5)), reply.pin);
+    }
+
+    #[test]
+    fn handle_end_roll_call_no_in_progress() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.end_call_call(2, &CallSelector::Latest)
+                .and_return(Ok(None)),
+        );
+
+        let command = create_command("/end_roll_call", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("No roll call in progress.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_end_roll_call_with_selector() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.end_call_call(2, &CallSelector::Id(5))
+                .and_return(Ok(None)),
+        );
+
+        let command = create_command("/end_roll_call", "#5");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("No such roll call in progress.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_schedule_roll_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let settings = ChatSettings {
+            timezone: "Europe/Berlin".to_string(),
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(repo.get_settings_call(2).and_return(Ok(settings)));
+
+        let start_at = chrono::NaiveDate::from_ymd(2099, 3, 20).and_hms(19, 0, 0);
+        let scheduled = create_scheduled_call(start_at, None);
+        scenario.expect(
+            repo.create_scheduled_call_call(2, arg!("call title"), start_at, None)
+                .and_return(Ok(scheduled)),
+        );
+
+        let command = create_command("/schedule_roll_call", "2099-03-20 20:00 call title");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("Roll call scheduled for 2099-03-20 20:00 (Europe/Berlin).".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_schedule_roll_call_in_the_past() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.get_settings_call(2)
+                .and_return(Ok(ChatSettings::default_for(2))),
+        );
+
+        let command = create_command("/schedule_roll_call", "2019-03-20 18:00 call title");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("That time has already passed.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_add_recurring_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let start_time = chrono::NaiveTime::from_hms(18, 0, 0);
+        scenario.expect(
+            repo.create_recurring_call_call(2, arg!("Football"), chrono::Weekday::Tue, start_time)
+                .and_return(Ok(create_recurring_call())),
+        );
+
+        let command = create_command("/recurring", "weekly tue 18:00 Football");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some(
+                "Roll call will start every Tuesday at 18:00 (UTC), next on 2019-04-09. \
+                 Use /recurring cancel 1 to stop it."
+                    .to_string()
+            ),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_add_recurring_call_in_the_timezone_of_the_chat() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let settings = ChatSettings {
+            timezone: "Europe/Berlin".to_string(),
+            ..ChatSettings::default_for(2)
+        };
+        scenario.expect(repo.get_settings_call(2).and_return(Ok(settings)));
+
+        // kept as the local time, in the timezone of the chat
+        let start_time = chrono::NaiveTime::from_hms(1, 0, 0);
+        scenario.expect(
+            repo.create_recurring_call_call(2, arg!("Football"), chrono::Weekday::Tue, start_time)
+                .and_return(Ok(RecurringCall {
+                    start_time,
+                    timezone: "Europe/Berlin".to_string(),
+                    ..create_recurring_call()
+                })),
+        );
+
+        let command = create_command("/recurring", "weekly tue 01:00 Football");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert!(reply
+            .text
+            .starts_with("Roll call will start every Tuesday at 01:00 (Europe/Berlin)"));
+    }
+
+    #[test]
+    fn handle_cancel_recurring_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(repo.delete_recurring_call_call(2, 1).and_return(Ok(true)));
+        scenario.expect(repo.delete_recurring_call_call(2, 5).and_return(Ok(false)));
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        assert_eq!(
+            Some("Recurring roll call cancelled.".to_string()),
+            bot.handle(create_command("/recurring", "cancel 1"))
+                .unwrap()
+                .map(|reply| reply.text)
+        );
+        assert_eq!(
+            Some("No such recurring roll call.".to_string()),
+            bot.handle(create_command("/recurring", "cancel 5"))
+                .unwrap()
+                .map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_update_title() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            title: "new title".to_string(),
+            ..create_call()
+        };
+
+        scenario.expect(
+            repo.update_title_call(2, &CallSelector::Latest, arg!("new title"))
+                .and_return(Ok(Some((call, vec![])))),
+        );
+
+        let command = create_command("/set_title", "new title");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("Roll call title set.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_update_title_empty_title() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let command = create_command("/set_title", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("Please provide a title. See /help set_title.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_update_title_no_in_progress() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.update_title_call(2, &CallSelector::Latest, arg!("new title"))
+                .and_return(Ok(None)),
+        );
+
+        let command = create_command("/set_title", "new title");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("No roll call in progress.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_set_quiet() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            quiet: true,
+            ..create_call()
+        };
+
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.update_quiet_call(2, arg!(true))
+                .and_return(Ok(Some((call, responses)))),
+        );
+
+        let command = create_command("/shh", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("Ok fine, I\'ll be quiet. 🤐".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_set_loud() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.update_quiet_call(2, arg!(false))
+                .and_return(Ok(Some((call, responses.clone())))),
+        );
+
+        let command = create_command("/louder", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_update_quiet_no_in_progress() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(repo.update_quiet_call(2, arg!(true)).and_return(Ok(None)));
+
+        let command = create_command("/shh", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("No roll call in progress.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_set_attendance_in() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.set_response_call(
+                2,
+                &CallSelector::Latest,
+                check(|user: &&ChatUser| user.name == "David"),
+                ANY,
+            )
+            .and_return(Ok(Some(create_response_update(
+                (call, responses.clone()),
+                AttendanceStatus::In,
+            )))),
+        );
+
+        let command = ChatCommand {
+            username: "David".to_string(),
+            ..create_command("/in", "will come")
+        };
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains("David (will come)"));
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_set_attendance_with_a_tag_for_no_call() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.get_calls_call(2, CallStatus::Open)
+                .and_return(Ok(vec![call.clone()])),
+        );
+        scenario.expect(
+            repo.set_response_call(
+                2,
+                &CallSelector::Latest,
+                ANY,
+                check(|attendance: &&Attendance| attendance.reason == "#sometag"),
+            )
+            .and_return(Ok(Some(create_response_update(
+                (call, responses),
+                AttendanceStatus::In,
+            )))),
+        );
+
+        let command = ChatCommand {
+            username: "David".to_string(),
+            ..create_command("/in", "#sometag")
+        };
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        assert!(bot.handle(command).unwrap().is_some());
+    }
+
+    #[test]
+    fn handle_set_attendance_out() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.set_response_call(
+                2,
+                &CallSelector::Latest,
+                check(|user: &&ChatUser| user.name == "Daniel"),
+                ANY,
+            )
+            .and_return(Ok(Some(create_response_update(
+                (call, responses.clone()),
+                AttendanceStatus::Out,
+            )))),
+        );
+
+        let command = ChatCommand {
+            username: "Daniel".to_string(),
+            ..create_command("/out", "won't come")
+        };
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains("Daniel (won't come)"));
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_set_attendance_maybe() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.set_response_call(
+                2,
+                &CallSelector::Latest,
+                check(|user: &&ChatUser| user.name == "Albert"),
+                ANY,
+            )
+            .and_return(Ok(Some(create_response_update(
+                (call, responses.clone()),
+                AttendanceStatus::Maybe,
+            )))),
+        );
+
+        let command = ChatCommand {
+            username: "Albert".to_string(),
+            ..create_command("/maybe", "might come")
+        };
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains("David (will come)"));
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_set_attendance_no_in_progress() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.set_response_call(
+                2,
+                &CallSelector::Latest,
+                check(|user: &&ChatUser| user.name == "User 1"),
+                ANY,
+            )
+            .and_return(Ok(None)),
+        );
+
+        let command = create_command("/in", "will come");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("No roll call in progress.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_set_attendance_for_in() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.set_response_for_call(2, &CallSelector::Latest, "David", ANY)
+                .and_return(Ok(Some(create_response_update(
+                    (call, responses.clone()),
+                    AttendanceStatus::In,
+                )))),
+        );
+
+        let command = create_command("/set_in_for", "David will come");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains("David (will come)"));
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_set_attendance_for_several_names() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call_with_responses = (create_call(), create_responses());
+        let update = create_response_update(call_with_responses, AttendanceStatus::In);
+        scenario.expect(
+            repo.set_responses_for_call(2, &CallSelector::Latest, ANY)
+                .and_return(Ok(Some(vec![update.clone(), update]))),
+        );
+
+        let command = create_command("/set_in_for", "Dave, Sarah");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.starts_with("Dave and Sarah are in!"));
+    }
+
+    #[test]
+    fn handle_set_attendance_for_out() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.set_response_for_call(2, &CallSelector::Latest, "Daniel", ANY)
+                .and_return(Ok(Some(create_response_update(
+                    (call, responses.clone()),
+                    AttendanceStatus::Out,
+                )))),
+        );
+
+        let command = create_command("/set_out_for", "Daniel won't come");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains("Daniel (won't come)"));
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_set_attendance_for_maybe() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.set_response_for_call(2, &CallSelector::Latest, "Albert", ANY)
+                .and_return(Ok(Some(create_response_update(
+                    (call, responses.clone()),
+                    AttendanceStatus::Maybe,
+                )))),
+        );
+
+        let command = create_command("/set_maybe_for", "Albert might come");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains("Albert (might come)"));
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_set_attendance_for_empty() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let command = create_command("/set_maybe_for", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("Please provide the person's name. See /help set_maybe_for.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_unknown_command_only_when_addressed_or_enabled() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let answering = ChatSettings {
+            answer_unknown_commands: true,
+            ..ChatSettings::default_for(3)
+        };
+        scenario.expect(repo.get_settings_call(3).and_return(Ok(answering)));
+
+        let command = |chat_id, addressed| ChatCommand {
+            chat_id,
+            addressed,
+            ..create_command("/weather", "")
+        };
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        assert_eq!(None, bot.handle(command(2, false)).unwrap());
+
+        let understood = Some("I don't understand that.".to_string());
+        let reply = |chat_id, addressed| {
+            let result = bot.handle(command(chat_id, addressed));
+            result.unwrap().map(|reply| reply.text)
+        };
+        assert_eq!(understood, reply(2, true));
+        assert_eq!(understood, reply(3, false));
+    }
+
+    #[test]
+    fn handle_set_attendance_for_invalid_mention() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let command = create_command("/set_in_for", "@mary-jane");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.starts_with("@mary-jane is not a username"));
+    }
+
+    #[test]
+    fn handle_set_attendance_with_waitlist() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let responses = create_responses();
+        let update = ResponseUpdate {
+            call_with_responses: (create_call(), responses.clone()),
+            status: AttendanceStatus::Out,
+            promoted: vec![responses[3].clone()],
+            refused_guests: 0,
+        };
+
+        scenario.expect(
+            repo.set_response_call(
+                2,
+                &CallSelector::Latest,
+                check(|user: &&ChatUser| user.name == "David"),
+                ANY,
+            )
+            .and_return(Ok(Some(update))),
+        );
+
+        let command = ChatCommand {
+            username: "David".to_string(),
+            ..create_command("/out", "")
+        };
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.starts_with("David is out!\nAlbert is off the waitlist and in!\n\n"));
+    }
+
+    #[test]
+    fn handle_set_limit() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            attendance_limit: Some(10),
+            ..create_call()
+        };
+
+        scenario.expect(
+            repo.update_limit_call(2, Some(10))
+                .and_return(Ok(Some(((call, create_responses()), vec![])))),
+        );
+
+        let command = create_command("/set_limit", "10");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("Limit set to 10.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+
+    #[test]
+    fn handle_get_all_attendances() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = create_call();
+        let responses = create_responses();
+
+        scenario.expect(
+            repo.get_call_with_responses_call(2, &CallSelector::Latest)
+                .and_return(Ok(Some((call, responses.clone())))),
+        );
+
+        let command = create_command("/whos_in", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command).unwrap().unwrap().text;
+        assert!(result.contains(&render_responses_full(Translator::default(), &responses)));
+    }
+
+    #[test]
+    fn handle_set_attendance_with_board() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            board_message_id: Some(10),
+            ..create_call()
+        };
+        let quiet_call = RollCall {
+            quiet: true,
+            ..call.clone()
+        };
+
+        scenario.expect(
+            repo.set_response_call(2, &CallSelector::Latest, ANY, ANY)
+                .and_return(Ok(Some(create_response_update(
+                    (call, create_responses()),
+                    AttendanceStatus::In,
+                )))),
+        );
+        scenario.expect(
+            repo.set_response_call(2, &CallSelector::Id(1), ANY, ANY)
+                .and_return(Ok(Some(create_response_update(
+                    (quiet_call, create_responses()),
+                    AttendanceStatus::In,
+                )))),
+        );
+
+        let command = ChatCommand {
+            username: "David".to_string(),
+            ..create_command("/in", "will come")
+        };
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command.clone()).unwrap().unwrap();
+        assert_eq!("David is in!", reply.text);
+        assert_eq!(1, reply.edits.len());
+        assert_eq!(10, reply.edits[0].message_id);
+        assert!(reply.edits[0].text.contains("David (will come)"));
+
+        let command = ChatCommand {
+            command_params: "#1 will come".to_string(),
+            addressed: false,
+            ..command
+        };
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!("", reply.text);
+        assert_eq!(1, reply.edits.len());
+    }
+
+    #[test]
+    fn run_records_posted_board() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.get_call_with_responses_call(2, &CallSelector::Latest)
+                .and_return(Ok(Some((create_call(), create_responses())))),
+        );
+        scenario.expect(repo.update_board_call(2, 1, Some(1)).and_return(Ok(true)));
+        scenario.expect(
+            repo.get_settings_call(2)
+                .and_return(Ok(ChatSettings::default_for(2))),
+        );
+        scenario.expect(repo.start_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.start_recurring_calls_call(ANY).and_return(Ok(vec![])));
+        scenario.expect(repo.end_scheduled_calls_call(ANY).and_return(Ok(vec![])));
+
+        let transport = FakeTransport::new(vec![create_command("/board", "")]);
+
+        let replies = transport.replies.clone();
+        let bot = WhosInBot::new(transport, Box::new(repo));
+        bot.run().unwrap();
+
+        let replies = replies.borrow();
+        assert_eq!(Some(MessageTag::Board(1)), replies[0].tag);
+        assert!(replies[0].text.starts_with("Roll call #1: call title"));
+        assert_eq!(3, replies[0].buttons.len());
+    }
+
+    #[test]
+    fn handle_history() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        let call = RollCall {
+            status: CallStatus::Closed,
+            ..create_call()
+        };
+        scenario.expect(
+            repo.get_calls_with_responses_call(2, CallStatus::Closed)
+                .and_return(Ok(vec![(call, create_responses())])),
+        );
+
+        let command = create_command("/history", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert!(reply.text.starts_with("Past roll calls:\n #1 call title"));
+    }
+
+    #[test]
+    fn handle_show_call_not_found() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(repo.get_closed_call_call(2, 7).and_return(Ok(None)));
+
+        let command = create_command("/show_call", "7");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let reply = bot.handle(command).unwrap().unwrap();
+        assert_eq!(
+            "No such past roll call. Use /history to list them.",
+            reply.text
+        );
+    }
+
+    #[test]
+    fn handle_get_all_attendances_no_in_progress() {
+        let scenario = Scenario::new();
+        let repo = create_repo(&scenario);
+
+        scenario.expect(
+            repo.get_call_with_responses_call(2, &CallSelector::Latest)
+                .and_return(Ok(None)),
+        );
+
+        let command = create_command("/whos_in", "");
+
+        let bot = WhosInBot::new(FakeTransport::default(), Box::new(repo));
+        let result = bot.handle(command);
+        assert_eq!(
+            Some("No roll call in progress.".to_string()),
+            result.unwrap().map(|reply| reply.text)
+        );
+    }
+}
@@ -1,219 +1,1034 @@
 use std::time::Duration;
 
-use diesel::dsl::sql;
+#[cfg(feature = "sqlite")]
+use diesel::connection::{SimpleConnection, TransactionManager};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
-use diesel::r2d2::ConnectionManager;
-use diesel::sql_types::*;
+use diesel::r2d2::{self as diesel_r2d2, ConnectionManager, CustomizeConnection};
+use diesel::result::{DatabaseErrorKind, Error};
+#[cfg(feature = "sqlite")]
+use diesel::sqlite::SqliteConnection;
 use r2d2;
 
-use crate::models::*;
-use crate::schema;
-use crate::util::collections::CollectionTools;
+pub type Manager<C> = ConnectionManager<C>;
+pub type Pool<C> = r2d2::Pool<Manager<C>>;
+pub type PooledConnection<C> = r2d2::PooledConnection<Manager<C>>;
+pub type PoolResult<C> = Result<Pool<C>, r2d2::Error>;
 
-pub type Manager = ConnectionManager<PgConnection>;
-pub type Pool = r2d2::Pool<Manager>;
-pub type PooledConnection = r2d2::PooledConnection<Manager>;
-pub type PoolResult = Result<Pool, r2d2::Error>;
-pub type ConnectionResult = diesel::ConnectionResult<PgConnection>;
+/// The parts of a connection that differ between the backends.
+pub trait BackendConnection: Connection + 'static {
+    /// Prepares a new connection of a pool, given how long to wait for the other connections.
+    fn setup(&self, _timeout: Duration) -> QueryResult<()> {
+        Ok(())
+    }
+
+    /// Runs a transaction that writes to the database.
+    fn write_transaction<T, F>(&self, f: F) -> QueryResult<T>
+    where
+        F: FnOnce() -> QueryResult<T>,
+    {
+        self.transaction(f)
+    }
+}
+
+impl BackendConnection for PgConnection {}
+
+#[cfg(feature = "sqlite")]
+impl BackendConnection for SqliteConnection {
+    fn setup(&self, timeout: Duration) -> QueryResult<()> {
+        // the scheduled tasks write while commands are handled, so wait for the lock of the
+        // database instead of failing with SQLITE_BUSY
+        let millis = timeout.as_secs() * 1000 + u64::from(timeout.subsec_millis());
+        self.batch_execute(&format!("PRAGMA busy_timeout = {};", millis))
+    }
+
+    fn write_transaction<T, F>(&self, f: F) -> QueryResult<T>
+    where
+        F: FnOnce() -> QueryResult<T>,
+    {
+        // a deferred transaction that has read fails right away instead of waiting when another
+        // connection writes first, so take the write lock up front
+        let depth = TransactionManager::<Self>::get_transaction_depth(self.transaction_manager());
+        if depth == 0 {
+            self.immediate_transaction(f)
+        } else {
+            self.transaction(f)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ConnectionSetup {
+    timeout: Duration,
+}
+
+impl<C: BackendConnection> CustomizeConnection<C, diesel_r2d2::Error> for ConnectionSetup {
+    fn on_acquire(&self, conn: &mut C) -> Result<(), diesel_r2d2::Error> {
+        conn.setup(self.timeout)
+            .map_err(diesel_r2d2::Error::QueryError)
+    }
+}
 
-pub fn create_pool(database_url: &str, timeout: Duration) -> PoolResult {
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
+pub fn create_pool<C>(database_url: &str, timeout: Duration) -> PoolResult<C>
+where
+    C: BackendConnection,
+{
+    let manager = ConnectionManager::<C>::new(database_url);
     r2d2::Pool::builder()
         .connection_timeout(timeout)
+        .connection_customizer(Box::new(ConnectionSetup { timeout }))
         .build(manager)
 }
 
-pub fn create_connection(database_url: &str) -> ConnectionResult {
-    PgConnection::establish(database_url)
+/// Runs a transaction that inserts a row if it does not exist yet a second time when another
+/// connection inserted the same row in the meantime, so that the row is found and updated.
+pub fn retry_on_unique_violation<T, F>(transaction: F) -> QueryResult<T>
+where
+    F: Fn() -> QueryResult<T>,
+{
+    match transaction() {
+        Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, info)) => {
+            debug!("Retrying after a unique violation: {}", info.message());
+            transaction()
+        }
+        result => result,
+    }
 }
 
-pub fn create_call(conn: &PgConnection, chat_id: ChatId, title: &str) -> QueryResult<RollCall> {
-    use schema::w_roll_calls::{dsl, table};
+pub fn create_connection<C: Connection>(database_url: &str) -> diesel::ConnectionResult<C> {
+    C::establish(database_url)
+}
 
-    let close_all_calls = || -> QueryResult<usize> {
-        let open_calls = table
-            .filter(dsl::chat_id.eq(chat_id))
-            .filter(dsl::status.ne(CallStatus::Closed));
+/// Generates the query helpers for one connection type. The queries only use constructs that
+/// both PostgreSQL and SQLite understand, so the same code serves every diesel backend.
+macro_rules! define_helpers {
+    ($conn:ty) => {
+        use chrono::{NaiveDateTime, NaiveTime, Weekday};
+        use diesel::prelude::*;
 
-        let update_close = UpdateRollCall::new().with_status(CallStatus::Closed);
+        use crate::models::*;
+        use crate::schema;
 
-        let updated = diesel::update(open_calls).set(update_close).execute(conn)?;
+        use super::BackendConnection;
 
-        debug!("Closed {} open calls", updated);
-        Ok(updated)
-    };
+        pub fn create_call(conn: &$conn, chat_id: ChatId, title: &str) -> QueryResult<RollCall> {
+            open_new_call(conn, chat_id, title, false)
+        }
 
-    let delete_old_calls = || -> QueryResult<usize> {
-        const LIMIT: i32 = 10;
-        let deleted = diesel::sql_query(
-            "DELETE \
-                FROM w_roll_calls \
-                WHERE chat_id = $1 \
-                  AND created_at < ( \
-                  SELECT MIN(latest.created_at) \
-                  FROM (SELECT created_at \
-                        FROM w_roll_calls \
-                        WHERE chat_id = $1 \
-                        ORDER BY created_at DESC \
-                        LIMIT $2) AS latest \
-                )")
-            .bind::<BigInt, _>(chat_id)
-            .bind::<Integer, _>(LIMIT)
-            .execute(conn)?;
-
-        debug!("Deleted {} old closed calls", deleted);
-        Ok(deleted)
-    };
+        pub fn add_call(conn: &$conn, chat_id: ChatId, title: &str) -> QueryResult<RollCall> {
+            open_new_call(conn, chat_id, title, true)
+        }
 
-    let insert_new_call = || -> QueryResult<RollCall> {
-        let new_call = NewRollCall::new(chat_id, title);
-        let result = diesel::insert_into(table)
-            .values(new_call)
-            .get_result(conn)?;
+        /// Closes the open calls of the chat, unless the new call runs `alongside` them, and opens
+        /// the new call.
+        fn open_new_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            title: &str,
+            alongside: bool,
+        ) -> QueryResult<RollCall> {
+            use schema::w_roll_calls::{dsl, table};
 
-        debug!("Inserted new call: {:?}", result);
-        Ok(result)
-    };
+            let close_open_calls = || -> QueryResult<usize> {
+                let open_calls = table
+                    .filter(dsl::chat_id.eq(chat_id))
+                    .filter(dsl::status.ne(CallStatus::Closed));
 
-    conn.transaction(|| {
-        close_all_calls()?;
-        delete_old_calls()?;
-        insert_new_call()
-    })
-}
+                let update_close = UpdateRollCall::new().with_status(CallStatus::Closed);
+                let updated = diesel::update(open_calls).set(update_close).execute(conn)?;
 
-pub fn end_call(conn: &PgConnection, chat_id: ChatId) -> QueryResult<Option<RollCall>> {
-    let update = UpdateRollCall::new().with_status(CallStatus::Closed);
-    update_call(conn, chat_id, update)
-}
+                debug!("Closed {} open calls", updated);
+                Ok(updated)
+            };
 
-pub fn update_title(
-    conn: &PgConnection,
-    chat_id: ChatId,
-    new_title: &str,
-) -> QueryResult<Option<RollCall>> {
-    let update = UpdateRollCall::new().with_title(new_title);
-    update_call(conn, chat_id, update)
-}
+            let insert_new_call = || -> QueryResult<RollCall> {
+                let settings = get_settings(conn, chat_id)?;
+                let new_call = NewRollCall::new(&settings, title);
+                diesel::insert_into(table).values(new_call).execute(conn)?;
 
-pub fn update_quiet(
-    conn: &PgConnection,
-    chat_id: ChatId,
-    quiet: bool,
-) -> QueryResult<Option<CallWithResponses>> {
-    let update = UpdateRollCall::new().with_quiet(quiet);
-    match update_call(conn, chat_id, update)? {
-        None => Ok(None),
-        Some(call) => get_responses(conn, call.id).map(|responses| Some((call, responses))),
-    }
-}
+                let result = table
+                    .filter(dsl::chat_id.eq(chat_id))
+                    .order(dsl::id.desc())
+                    .first(conn)?;
 
-fn update_call(
-    conn: &PgConnection,
-    chat_id: ChatId,
-    update: UpdateRollCall,
-) -> QueryResult<Option<RollCall>> {
-    use schema::w_roll_calls::table;
-
-    let filter_open_call = table.filter(
-        sql("id IN (SELECT id FROM w_roll_calls WHERE status = ")
-            .bind::<VarChar, _>(CallStatus::Open)
-            .sql("AND chat_id = ")
-            .bind::<BigInt, _>(chat_id)
-            .sql("ORDER BY created_at DESC LIMIT 1)"));
-
-    let updated = diesel::update(filter_open_call)
-        .set(update)
-        .get_results(conn)?;
-
-    let updated: Option<RollCall> = updated.take_first();
-    debug!("Updated call: {:?}", updated; "call_id" => updated.as_ref().map(|u| u.id));
-    Ok(updated)
-}
+                debug!("Inserted new call: {:?}", result);
+                Ok(result)
+            };
 
-fn get_current_call(conn: &PgConnection, chat_id: ChatId) -> QueryResult<Option<RollCall>> {
-    use schema::w_roll_calls::{dsl, table};
-    let open_calls = table
-        .filter(dsl::chat_id.eq(chat_id))
-        .filter(dsl::status.eq(CallStatus::Open))
-        .order(dsl::updated_at.desc())
-        .limit(1)
-        .load::<RollCall>(conn)?;
+            conn.write_transaction(|| {
+                if !alongside {
+                    close_open_calls()?;
+                }
+                insert_new_call()
+            })
+        }
 
-    Ok(open_calls.take_first())
-}
+        pub fn restart_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            title: &str,
+            keep: bool,
+        ) -> QueryResult<Option<RestartedCall>> {
+            use schema::w_roll_calls::{dsl, table};
 
-fn get_responses(conn: &PgConnection, call_id: CallId) -> QueryResult<Vec<RollCallResponse>> {
-    use schema::w_roll_call_responses::{self, dsl};
-    w_roll_call_responses::table
-        .filter(dsl::roll_call_id.eq(call_id))
-        .order(dsl::updated_at.desc())
-        .load::<RollCallResponse>(conn)
-}
+            conn.write_transaction(|| {
+                let previous_call = table
+                    .filter(dsl::chat_id.eq(chat_id))
+                    .order((dsl::created_at.desc(), dsl::id.desc()))
+                    .first::<RollCall>(conn)
+                    .optional()?;
 
-pub fn get_call_with_responses(
-    conn: &PgConnection,
-    chat_id: ChatId,
-) -> QueryResult<Option<CallWithResponses>> {
-    let open_call = match get_current_call(conn, chat_id)? {
-        Some(call) => call,
-        None => return Ok(None),
-    };
+                let previous_call = match previous_call {
+                    Some(call) => call,
+                    None => return Ok(None),
+                };
 
-    let responses = get_responses(conn, open_call.id)?;
-    Ok(Some((open_call, responses)))
-}
+                // only the previous call is replaced, the others may run alongside it
+                let update = UpdateRollCall::new().with_status(CallStatus::Closed);
+                update_call_by_id(conn, chat_id, previous_call.id, update)?;
+                let previous_call = table.find(previous_call.id).first::<RollCall>(conn)?;
+                let previous_responses = get_responses(conn, previous_call.id)?;
 
-pub fn set_response(
-    conn: &PgConnection,
-    chat_id: ChatId,
-    user_id: UserId,
-    user_name: &str,
-    attendance: &Attendance,
-) -> QueryResult<Option<CallWithResponses>> {
-    set_response_base(conn, chat_id, |call_id| {
-        NewRollCallResponse::new_self(call_id, user_id, user_name, attendance)
-    })
-}
+                let title = match title {
+                    "" => previous_call.title.as_str(),
+                    title => title,
+                };
+                let call = open_new_call(conn, chat_id, title, true)?;
 
-pub fn set_response_for(
-    conn: &PgConnection,
-    chat_id: ChatId,
-    user_name: &str,
-    attendance: &Attendance,
-) -> QueryResult<Option<CallWithResponses>> {
-    set_response_base(conn, chat_id, |call_id| {
-        NewRollCallResponse::new_for(call_id, user_name, attendance)
-    })
-}
+                if keep {
+                    use schema::w_roll_call_responses::table;
+                    let records: Vec<NewRollCallResponse> = previous_responses
+                        .iter()
+                        .map(|response| NewRollCallResponse::carried_over(call.id, response))
+                        .collect();
 
-fn set_response_base<'a, F>(
-    conn: &PgConnection,
-    chat_id: ChatId,
-    value_fn: F,
-) -> QueryResult<Option<CallWithResponses>>
-    where
-        F: Fn(CallId) -> NewRollCallResponse<'a>,
-{
-    let open_call = match get_current_call(conn, chat_id)? {
-        Some(call) => call,
-        None => return Ok(None),
-    };
+                    let inserted = diesel::insert_into(table).values(&records).execute(conn)?;
+                    debug!("Carried over {} responses", inserted; "call_id" => call.id);
+                }
+
+                let responses = get_responses(conn, call.id)?;
+                Ok(Some(RestartedCall {
+                    call_with_responses: (call, responses),
+                    previous_call: (previous_call, previous_responses),
+                }))
+            })
+        }
+
+        pub fn reopen_call(conn: &$conn, chat_id: ChatId) -> QueryResult<Option<CallWithResponses>> {
+            use schema::w_roll_calls::{dsl, table};
+
+            conn.write_transaction(|| {
+                let latest_call = table
+                    .filter(dsl::chat_id.eq(chat_id))
+                    .order((dsl::created_at.desc(), dsl::id.desc()))
+                    .first::<RollCall>(conn)
+                    .optional()?;
+
+                let call_id = match latest_call {
+                    Some(ref call) if call.status == CallStatus::Closed => call.id,
+                    _ => return Ok(None),
+                };
+
+                let update = UpdateRollCall::new().with_status(CallStatus::Open);
+                update_call_by_id(conn, chat_id, call_id, update)?;
+
+                let call = table.find(call_id).first::<RollCall>(conn)?;
+                let responses = get_responses(conn, call_id)?;
+                Ok(Some((call, responses)))
+            })
+        }
+
+        /// Deletes the calls that the retention policies no longer keep. Calls past their age go
+        /// in one query for the chats without a policy of their own, while only a limit on the
+        /// number of calls needs to go through the calls of each chat.
+        pub fn delete_old_calls(
+            conn: &$conn,
+            retention: &Retention,
+            now: NaiveDateTime,
+        ) -> QueryResult<PrunedCalls> {
+            use schema::w_roll_calls::{dsl, table};
+
+            let own_chat_ids: Vec<ChatId> = retention.chats.keys().cloned().collect();
+            let mut pruned = PrunedCalls::default();
+
+            if let Some(max_age) = retention.default.max_age {
+                let (calls, responses) =
+                    delete_closed_calls_before(conn, now - max_age, None, &own_chat_ids)?;
+                pruned.calls += calls;
+                pruned.responses += responses;
+            }
+            for (&chat_id, policy) in retention.chats.iter() {
+                if let Some(max_age) = policy.max_age {
+                    let (calls, responses) =
+                        delete_closed_calls_before(conn, now - max_age, Some(chat_id), &[])?;
+                    pruned.calls += calls;
+                    pruned.responses += responses;
+                }
+            }
+
+            let chat_ids = if retention.default.max_calls.is_some() {
+                table.select(dsl::chat_id).distinct().load(conn)?
+            } else {
+                own_chat_ids
+            };
+            for chat_id in chat_ids {
+                let max_calls = match retention.for_chat(chat_id).max_calls {
+                    Some(max_calls) => max_calls,
+                    None => continue,
+                };
+
+                // open calls count towards the limit, but are kept past it
+                let old_calls: Vec<(CallId, CallStatus)> = table
+                    .select((dsl::id, dsl::status))
+                    .filter(dsl::chat_id.eq(chat_id))
+                    .order((dsl::created_at.desc(), dsl::id.desc()))
+                    // SQLite only takes an offset after a limit
+                    .limit(i64::MAX)
+                    .offset(max_calls as i64)
+                    .load(conn)?;
+                let old_call_ids: Vec<CallId> = old_calls
+                    .into_iter()
+                    .filter(|&(_, status)| status == CallStatus::Closed)
+                    .map(|(call_id, _)| call_id)
+                    .collect();
+
+                if old_call_ids.is_empty() {
+                    continue;
+                }
+
+                let (calls, responses) = conn.write_transaction(|| -> QueryResult<(usize, usize)> {
+                    // SQLite does not enforce the cascading foreign key unless asked to
+                    let responses = {
+                        use schema::w_roll_call_responses::{dsl, table};
+                        diesel::delete(table.filter(dsl::roll_call_id.eq_any(&old_call_ids)))
+                            .execute(conn)?
+                    };
+
+                    let calls =
+                        diesel::delete(table.filter(dsl::id.eq_any(&old_call_ids))).execute(conn)?;
+                    Ok((calls, responses))
+                })?;
+
+                debug!("Deleted {} old calls", calls; "chat_id" => chat_id);
+                pruned.calls += calls;
+                pruned.responses += responses;
+            }
+
+            Ok(pruned)
+        }
+
+        /// Deletes the closed calls created before `cutoff` along with their responses, either of
+        /// a single chat or of every chat but the excluded ones.
+        fn delete_closed_calls_before(
+            conn: &$conn,
+            cutoff: NaiveDateTime,
+            chat_id: Option<ChatId>,
+            excluded_chat_ids: &[ChatId],
+        ) -> QueryResult<(usize, usize)> {
+            use schema::w_roll_calls::{dsl, table};
+
+            let old_calls = || {
+                let query = table
+                    .select(dsl::id)
+                    .filter(dsl::status.eq(CallStatus::Closed))
+                    .filter(dsl::created_at.lt(cutoff))
+                    .filter(dsl::chat_id.ne_all(excluded_chat_ids))
+                    .into_boxed();
+                match chat_id {
+                    Some(chat_id) => query.filter(dsl::chat_id.eq(chat_id)),
+                    None => query,
+                }
+            };
+
+            let (calls, responses) = conn.write_transaction(|| -> QueryResult<(usize, usize)> {
+                let responses = {
+                    use schema::w_roll_call_responses::{dsl, table};
+                    diesel::delete(table.filter(dsl::roll_call_id.eq_any(old_calls())))
+                        .execute(conn)?
+                };
+
+                let calls = diesel::delete(table.filter(dsl::id.eq_any(old_calls()))).execute(conn)?;
+                Ok((calls, responses))
+            })?;
+
+            debug!("Deleted {} calls created before {}", calls, cutoff);
+            Ok((calls, responses))
+        }
+
+        pub fn end_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+        ) -> QueryResult<Option<CallWithResponses>> {
+            let update = UpdateRollCall::new().with_status(CallStatus::Closed);
+            update_call_with_responses(conn, chat_id, selector, update)
+        }
+
+        pub fn update_title(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            new_title: &str,
+        ) -> QueryResult<Option<CallWithResponses>> {
+            let update = UpdateRollCall::new().with_title(new_title);
+            update_call_with_responses(conn, chat_id, selector, update)
+        }
+
+        pub fn update_quiet(
+            conn: &$conn,
+            chat_id: ChatId,
+            quiet: bool,
+        ) -> QueryResult<Option<CallWithResponses>> {
+            let update = UpdateRollCall::new().with_quiet(quiet);
+            update_call_with_responses(conn, chat_id, &CallSelector::Latest, update)
+        }
+
+        pub fn update_board(
+            conn: &$conn,
+            chat_id: ChatId,
+            call_id: CallId,
+            message_id: Option<MessageId>,
+        ) -> QueryResult<bool> {
+            let update = UpdateRollCall::new().with_board_message_id(message_id);
+            update_call_by_id(conn, chat_id, call_id, update)
+        }
+
+        pub fn update_pinned_message(
+            conn: &$conn,
+            chat_id: ChatId,
+            call_id: CallId,
+            message_id: Option<MessageId>,
+        ) -> QueryResult<bool> {
+            let update = UpdateRollCall::new().with_pinned_message_id(message_id);
+            update_call_by_id(conn, chat_id, call_id, update)
+        }
+
+        /// Updates a call whether it is open or not, as long as it belongs to the chat.
+        fn update_call_by_id(
+            conn: &$conn,
+            chat_id: ChatId,
+            call_id: CallId,
+            update: UpdateRollCall,
+        ) -> QueryResult<bool> {
+            use schema::w_roll_calls::{dsl, table};
+
+            let call = table.filter(dsl::chat_id.eq(chat_id)).filter(dsl::id.eq(call_id));
+            let updated = diesel::update(call).set(update).execute(conn)?;
+
+            debug!("Updated {} calls", updated; "call_id" => call_id);
+            Ok(updated > 0)
+        }
+
+        pub fn get_calls(
+            conn: &$conn,
+            chat_id: ChatId,
+            status: CallStatus,
+        ) -> QueryResult<Vec<RollCall>> {
+            use schema::w_roll_calls::{dsl, table};
+            table
+                .filter(dsl::chat_id.eq(chat_id))
+                .filter(dsl::status.eq(status))
+                .order((dsl::created_at.desc(), dsl::id.desc()))
+                .load(conn)
+        }
+
+        pub fn get_calls_with_responses(
+            conn: &$conn,
+            chat_id: ChatId,
+            status: CallStatus,
+        ) -> QueryResult<Vec<CallWithResponses>> {
+            get_calls(conn, chat_id, status)?
+                .into_iter()
+                .map(|call| {
+                    let responses = get_responses(conn, call.id)?;
+                    Ok((call, responses))
+                })
+                .collect()
+        }
+
+        pub fn get_closed_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            call_id: CallId,
+        ) -> QueryResult<Option<CallWithResponses>> {
+            use schema::w_roll_calls::{dsl, table};
+            let closed_call = table
+                .find(call_id)
+                .filter(dsl::chat_id.eq(chat_id))
+                .filter(dsl::status.eq(CallStatus::Closed))
+                .first::<RollCall>(conn)
+                .optional()?;
+
+            match closed_call {
+                Some(call) => {
+                    let responses = get_responses(conn, call.id)?;
+                    Ok(Some((call, responses)))
+                }
+                None => Ok(None),
+            }
+        }
+
+        pub fn get_settings(conn: &$conn, chat_id: ChatId) -> QueryResult<ChatSettings> {
+            use schema::w_chat_settings::table;
+            let settings = table.find(chat_id).first(conn).optional()?;
+            Ok(settings.unwrap_or_else(|| ChatSettings::default_for(chat_id)))
+        }
+
+        pub fn update_settings(
+            conn: &$conn,
+            chat_id: ChatId,
+            update: &UpdateChatSettings,
+        ) -> QueryResult<ChatSettings> {
+            use schema::w_chat_settings::table;
+
+            super::retry_on_unique_violation(|| conn.write_transaction(|| {
+                // chats only get a row once they change something
+                let existing = table.find(chat_id).first::<ChatSettings>(conn).optional()?;
+                if existing.is_none() {
+                    let new_settings = NewChatSettings::new(chat_id);
+                    diesel::insert_into(table).values(new_settings).execute(conn)?;
+                }
+
+                diesel::update(table.find(chat_id)).set(update).execute(conn)?;
+
+                let result = table.find(chat_id).first(conn)?;
+                debug!("Updated settings: {:?}", result);
+                Ok(result)
+            }))
+        }
+
+        pub fn get_aliases(conn: &$conn, chat_id: ChatId) -> QueryResult<Vec<ChatAlias>> {
+            use schema::w_chat_aliases::{dsl, table};
+            table
+                .filter(dsl::chat_id.eq(chat_id))
+                .order(dsl::alias.asc())
+                .load::<ChatAlias>(conn)
+        }
 
-    let record = value_fn(open_call.id);
-    let update = UpdateRollCallResponse::new(record.user_name, record.status, record.reason);
+        pub fn add_alias(
+            conn: &$conn,
+            chat_id: ChatId,
+            alias: &str,
+            command: &str,
+        ) -> QueryResult<ChatAlias> {
+            use schema::w_chat_aliases::table;
 
-    use schema::w_roll_call_responses::{dsl, table};
-    let inserted = diesel::insert_into(table)
-        .values(&record)
-        .on_conflict((dsl::roll_call_id, dsl::unique_token))
-        .do_update()
-        .set(&update)
-        .execute(conn)?;
+            super::retry_on_unique_violation(|| conn.write_transaction(|| {
+                diesel::delete(table.find((chat_id, alias))).execute(conn)?;
+                let new_alias = NewChatAlias::new(chat_id, alias, command);
+                diesel::insert_into(table).values(new_alias).execute(conn)?;
 
-    debug!("Added {} response", inserted; "call_id" => open_call.id);
-    let responses = get_responses(conn, open_call.id)?;
+                let result = table.find((chat_id, alias)).first(conn)?;
+                debug!("Inserted new alias: {:?}", result);
+                Ok(result)
+            }))
+        }
+
+        pub fn delete_alias(conn: &$conn, chat_id: ChatId, alias: &str) -> QueryResult<bool> {
+            use schema::w_chat_aliases::table;
+
+            let deleted = diesel::delete(table.find((chat_id, alias))).execute(conn)?;
+            debug!("Deleted {} aliases", deleted; "alias" => alias);
+            Ok(deleted > 0)
+        }
+
+        fn update_call_with_responses(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            update: UpdateRollCall,
+        ) -> QueryResult<Option<CallWithResponses>> {
+            match update_call(conn, chat_id, selector, update)? {
+                None => Ok(None),
+                Some(call) => {
+                    get_responses(conn, call.id).map(|responses| Some((call, responses)))
+                }
+            }
+        }
+
+        fn update_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            update: UpdateRollCall,
+        ) -> QueryResult<Option<RollCall>> {
+            use schema::w_roll_calls::table;
+
+            conn.write_transaction(|| {
+                let call_id = match get_open_call(conn, chat_id, selector)? {
+                    Some(call) => call.id,
+                    None => return Ok(None),
+                };
+
+                diesel::update(table.find(call_id))
+                    .set(update)
+                    .execute(conn)?;
+
+                let updated = table.find(call_id).first::<RollCall>(conn).optional()?;
+                debug!("Updated call: {:?}", updated; "call_id" => call_id);
+                Ok(updated)
+            })
+        }
+
+        /// Finds the latest open call of the chat that matches the selector.
+        fn get_open_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+        ) -> QueryResult<Option<RollCall>> {
+            use schema::w_roll_calls::{dsl, table};
+            let open_calls = table
+                .filter(dsl::chat_id.eq(chat_id))
+                .filter(dsl::status.eq(CallStatus::Open))
+                .order((dsl::created_at.desc(), dsl::id.desc()))
+                .load::<RollCall>(conn)?;
+
+            Ok(open_calls.into_iter().find(|call| selector.matches(call)))
+        }
+
+        fn get_responses(conn: &$conn, call_id: CallId) -> QueryResult<Vec<RollCallResponse>> {
+            use schema::w_roll_call_responses::{self, dsl};
+            w_roll_call_responses::table
+                .filter(dsl::roll_call_id.eq(call_id))
+                .order(dsl::updated_at.desc())
+                .load::<RollCallResponse>(conn)
+        }
+
+        pub fn get_call_with_responses(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+        ) -> QueryResult<Option<CallWithResponses>> {
+            let open_call = match get_open_call(conn, chat_id, selector)? {
+                Some(call) => call,
+                None => return Ok(None),
+            };
+
+            let responses = get_responses(conn, open_call.id)?;
+            Ok(Some((open_call, responses)))
+        }
+
+        pub fn update_limit(
+            conn: &$conn,
+            chat_id: ChatId,
+            limit: Option<i32>,
+        ) -> QueryResult<Option<(CallWithResponses, Vec<RollCallResponse>)>> {
+            conn.write_transaction(|| {
+                let update = UpdateRollCall::new().with_attendance_limit(limit);
+                let call = match update_call(conn, chat_id, &CallSelector::Latest, update)? {
+                    Some(call) => call,
+                    None => return Ok(None),
+                };
+
+                let promoted = promote_waitlist(conn, &call)?;
+                let responses = get_responses(conn, call.id)?;
+                Ok(Some(((call, responses), promoted)))
+            })
+        }
+
+        pub fn set_response(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            user: &ChatUser,
+            attendance: &Attendance,
+        ) -> QueryResult<Option<ResponseUpdate>> {
+            set_response_base(conn, chat_id, selector, |call_id| {
+                NewRollCallResponse::new_self(call_id, user.id, &user.name, attendance)
+            })
+        }
+
+        pub fn set_response_for(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            user_name: &str,
+            attendance: &Attendance,
+        ) -> QueryResult<Option<ResponseUpdate>> {
+            set_response_base(conn, chat_id, selector, |call_id| {
+                NewRollCallResponse::new_for(call_id, user_name, attendance)
+            })
+        }
+
+        pub fn set_responses_for(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            responses: &[(String, Attendance)],
+        ) -> QueryResult<Option<Vec<ResponseUpdate>>> {
+            conn.write_transaction(|| {
+                let mut updates = vec![];
+                for (user_name, attendance) in responses.iter() {
+                    match set_response_for(conn, chat_id, selector, user_name, attendance)? {
+                        Some(update) => updates.push(update),
+                        None => return Ok(None),
+                    }
+                }
+
+                Ok(Some(updates))
+            })
+        }
+
+        pub fn delete_response(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            unique_token: &str,
+        ) -> QueryResult<Option<ResponseRemoval>> {
+            use schema::w_roll_call_responses::{dsl, table};
+
+            conn.write_transaction(|| {
+                let open_call = match get_open_call(conn, chat_id, selector)? {
+                    Some(call) => call,
+                    None => return Ok(None),
+                };
+
+                let response = table
+                    .filter(dsl::roll_call_id.eq(open_call.id))
+                    .filter(dsl::unique_token.eq(unique_token));
+                let deleted = diesel::delete(response).execute(conn)?;
+
+                debug!("Deleted {} responses", deleted; "call_id" => open_call.id);
+                let promoted = promote_waitlist(conn, &open_call)?;
+                let responses = get_responses(conn, open_call.id)?;
+
+                Ok(Some(ResponseRemoval {
+                    call_with_responses: (open_call, responses),
+                    removed: deleted > 0,
+                    promoted,
+                }))
+            })
+        }
+
+        fn set_response_base<'a, F>(
+            conn: &$conn,
+            chat_id: ChatId,
+            selector: &CallSelector,
+            value_fn: F,
+        ) -> QueryResult<Option<ResponseUpdate>>
+        where
+            F: Fn(CallId) -> NewRollCallResponse<'a>,
+        {
+            use schema::w_roll_call_responses::{dsl, table};
+
+            // two responses of the same person at the same time may both insert it
+            super::retry_on_unique_violation(|| conn.write_transaction(|| {
+                let open_call = match get_open_call(conn, chat_id, selector)? {
+                    Some(call) => call,
+                    None => return Ok(None),
+                };
+
+                let mut record = value_fn(open_call.id);
+                let existing = table
+                    .filter(dsl::roll_call_id.eq(record.roll_call_id))
+                    .filter(dsl::unique_token.eq(&record.unique_token))
+                    .select((dsl::id, dsl::status, dsl::guests))
+                    .first::<(ResponseId, AttendanceStatus, i32)>(conn)
+                    .optional()?;
+
+                let mut refused_guests = 0;
+                if record.status == AttendanceStatus::In {
+                    match existing {
+                        // those who are in keep their place, and only need one for the guests
+                        // they add
+                        Some((_, AttendanceStatus::In, guests)) => {
+                            let in_count = count_in(conn, open_call.id)? - 1 - i64::from(guests);
+                            if record.guests > guests
+                                && !open_call.fits(in_count, record.party_size())
+                            {
+                                refused_guests = record.guests - guests;
+                                record.guests = guests;
+                            }
+                        }
+                        _ => {
+                            if !open_call.fits(count_in(conn, open_call.id)?, record.party_size())
+                            {
+                                record.status = AttendanceStatus::Waitlist;
+                            }
+                        }
+                    }
+                }
+
+                let upserted = match existing {
+                    Some((response_id, status, _)) => {
+                        let mut update = UpdateRollCallResponse::new(
+                            record.user_name,
+                            record.status,
+                            record.reason,
+                            record.guests,
+                        );
+
+                        // keep the place in the queue of those who are already waiting
+                        if status == AttendanceStatus::Waitlist && record.status == status {
+                            update.updated_at = None;
+                        }
+
+                        diesel::update(table.find(response_id))
+                            .set(&update)
+                            .execute(conn)?
+                    }
+                    None => diesel::insert_into(table).values(&record).execute(conn)?,
+                };
+
+                debug!("Added {} response", upserted; "call_id" => open_call.id);
+                let promoted = promote_waitlist(conn, &open_call)?;
+                let responses = get_responses(conn, open_call.id)?;
+
+                Ok(Some(ResponseUpdate {
+                    call_with_responses: (open_call, responses),
+                    status: record.status,
+                    promoted,
+                    refused_guests,
+                }))
+            }))
+        }
+
+        /// Counts the places taken by those who are in, guests included.
+        fn count_in(conn: &$conn, call_id: CallId) -> QueryResult<i64> {
+            use schema::w_roll_call_responses::{dsl, table};
+            let guests = table
+                .filter(dsl::roll_call_id.eq(call_id))
+                .filter(dsl::status.eq(AttendanceStatus::In))
+                .select(dsl::guests)
+                .load::<i32>(conn)?;
+
+            Ok(guests.into_iter().map(|guests| 1 + i64::from(guests)).sum())
+        }
+
+        /// Moves the longest waiting responses to `In` while the call has free places, skipping
+        /// parties that are too large for the places left.
+        fn promote_waitlist(
+            conn: &$conn,
+            call: &RollCall,
+        ) -> QueryResult<Vec<RollCallResponse>> {
+            use schema::w_roll_call_responses::{dsl, table};
+
+            let mut free_places = call.free_places(count_in(conn, call.id)?);
+            if free_places == Some(0) {
+                return Ok(vec![]);
+            }
+
+            let waitlist = table
+                .filter(dsl::roll_call_id.eq(call.id))
+                .filter(dsl::status.eq(AttendanceStatus::Waitlist))
+                .order((dsl::updated_at.asc(), dsl::id.asc()))
+                .load::<RollCallResponse>(conn)?;
+
+            let mut promoted = vec![];
+            for response in waitlist {
+                if let Some(places) = free_places {
+                    if response.party_size() > places {
+                        continue;
+                    }
+                    free_places = Some(places - response.party_size());
+                }
+
+                let update = UpdateRollCallResponse::new_status(AttendanceStatus::In);
+                diesel::update(table.find(response.id))
+                    .set(&update)
+                    .execute(conn)?;
+
+                let response = table.find(response.id).first::<RollCallResponse>(conn)?;
+                promoted.push(response);
+            }
+
+            debug!("Promoted {} responses from the waitlist", promoted.len(); "call_id" => call.id);
+            Ok(promoted)
+        }
+
+        pub fn create_scheduled_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            title: &str,
+            start_at: NaiveDateTime,
+            end_at: Option<NaiveDateTime>,
+        ) -> QueryResult<ScheduledCall> {
+            use schema::w_scheduled_calls::{dsl, table};
+
+            conn.write_transaction(|| {
+                let new_call = NewScheduledCall::new(chat_id, title, start_at, end_at);
+                diesel::insert_into(table).values(new_call).execute(conn)?;
+
+                let result = table
+                    .filter(dsl::chat_id.eq(chat_id))
+                    .order(dsl::id.desc())
+                    .first(conn)?;
+
+                debug!("Inserted new scheduled call: {:?}", result);
+                Ok(result)
+            })
+        }
+
+        pub fn start_scheduled_calls(
+            conn: &$conn,
+            now: NaiveDateTime,
+        ) -> QueryResult<Vec<RollCall>> {
+            use schema::w_scheduled_calls::{dsl, table};
+
+            conn.write_transaction(|| {
+                let due_calls = table
+                    .filter(dsl::status.eq(ScheduleStatus::Pending))
+                    .filter(dsl::start_at.le(now))
+                    .order(dsl::start_at.asc())
+                    .load::<ScheduledCall>(conn)?;
+
+                let mut started = Vec::with_capacity(due_calls.len());
+                for scheduled in due_calls {
+                    let call = create_call(conn, scheduled.chat_id, &scheduled.title)?;
+
+                    // without an end time, the call stays open like any other
+                    let status = match scheduled.end_at {
+                        Some(_) => ScheduleStatus::Started,
+                        None => ScheduleStatus::Done,
+                    };
+                    let update = UpdateScheduledCall::new()
+                        .with_status(status)
+                        .with_roll_call_id(call.id);
+                    diesel::update(table.find(scheduled.id))
+                        .set(update)
+                        .execute(conn)?;
+
+                    debug!("Started scheduled call {}", scheduled.id; "call_id" => call.id);
+                    started.push(call);
+                }
+
+                Ok(started)
+            })
+        }
+
+        pub fn end_scheduled_calls(
+            conn: &$conn,
+            now: NaiveDateTime,
+        ) -> QueryResult<Vec<CallWithResponses>> {
+            use schema::w_scheduled_calls::{dsl, table};
+
+            conn.write_transaction(|| {
+                let due_calls = table
+                    .filter(dsl::status.eq(ScheduleStatus::Started))
+                    .filter(dsl::end_at.le(now))
+                    .order(dsl::end_at.asc())
+                    .load::<ScheduledCall>(conn)?;
+
+                let mut ended = Vec::with_capacity(due_calls.len());
+                for scheduled in due_calls {
+                    let update = UpdateScheduledCall::new().with_status(ScheduleStatus::Done);
+                    diesel::update(table.find(scheduled.id))
+                        .set(update)
+                        .execute(conn)?;
+
+                    // the call may have been ended or replaced by hand in the meantime
+                    if let Some(call) = close_call(conn, scheduled.roll_call_id)? {
+                        debug!("Ended scheduled call {}", scheduled.id; "call_id" => call.id);
+                        let responses = get_responses(conn, call.id)?;
+                        ended.push((call, responses));
+                    }
+                }
+
+                Ok(ended)
+            })
+        }
+
+        pub fn create_recurring_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            title: &str,
+            weekday: Weekday,
+            start_time: NaiveTime,
+        ) -> QueryResult<RecurringCall> {
+            use schema::w_recurring_calls::{dsl, table};
+
+            conn.write_transaction(|| {
+                let settings = get_settings(conn, chat_id)?;
+                let new_call = NewRecurringCall::new(&settings, title, weekday, start_time);
+                diesel::insert_into(table).values(new_call).execute(conn)?;
+
+                let result = table
+                    .filter(dsl::chat_id.eq(chat_id))
+                    .order(dsl::id.desc())
+                    .first(conn)?;
+
+                debug!("Inserted new recurring call: {:?}", result);
+                Ok(result)
+            })
+        }
+
+        pub fn get_recurring_calls(
+            conn: &$conn,
+            chat_id: ChatId,
+        ) -> QueryResult<Vec<RecurringCall>> {
+            use schema::w_recurring_calls::{dsl, table};
+            table
+                .filter(dsl::chat_id.eq(chat_id))
+                .order((dsl::weekday.asc(), dsl::start_time.asc(), dsl::id.asc()))
+                .load::<RecurringCall>(conn)
+        }
+
+        pub fn delete_recurring_call(
+            conn: &$conn,
+            chat_id: ChatId,
+            id: RecurrenceId,
+        ) -> QueryResult<bool> {
+            use schema::w_recurring_calls::{dsl, table};
+
+            let call = table.find(id).filter(dsl::chat_id.eq(chat_id));
+            let deleted = diesel::delete(call).execute(conn)?;
+
+            debug!("Deleted {} recurring calls", deleted; "recurrence_id" => id);
+            Ok(deleted > 0)
+        }
+
+        pub fn start_recurring_calls(
+            conn: &$conn,
+            now: NaiveDateTime,
+        ) -> QueryResult<Vec<RollCall>> {
+            use schema::w_recurring_calls::{dsl, table};
+
+            conn.write_transaction(|| {
+                let due_calls = table
+                    .filter(dsl::next_at.le(now))
+                    .order(dsl::next_at.asc())
+                    .load::<RecurringCall>(conn)?;
+
+                let mut started = Vec::with_capacity(due_calls.len());
+                for recurring in due_calls {
+                    let call = create_call(conn, recurring.chat_id, &recurring.title)?;
+
+                    let update = UpdateRecurringCall::new(recurring.next_occurrence(now));
+                    diesel::update(table.find(recurring.id))
+                        .set(update)
+                        .execute(conn)?;
+
+                    debug!("Started recurring call {}", recurring.id; "call_id" => call.id);
+                    started.push(call);
+                }
+
+                Ok(started)
+            })
+        }
+
+        fn close_call(conn: &$conn, call_id: Option<CallId>) -> QueryResult<Option<RollCall>> {
+            use schema::w_roll_calls::{dsl, table};
+
+            let call_id = match call_id {
+                Some(call_id) => call_id,
+                None => return Ok(None),
+            };
+
+            let open_call = table.find(call_id).filter(dsl::status.eq(CallStatus::Open));
+            let update = UpdateRollCall::new().with_status(CallStatus::Closed);
+            match diesel::update(open_call).set(update).execute(conn)? {
+                0 => Ok(None),
+                _ => table.find(call_id).first::<RollCall>(conn).optional(),
+            }
+        }
+    };
+}
+
+pub mod pg {
+    define_helpers!(diesel::pg::PgConnection);
+}
 
-    Ok(Some((open_call, responses)))
+#[cfg(feature = "sqlite")]
+pub mod sqlite {
+    define_helpers!(diesel::sqlite::SqliteConnection);
 }
@@ -0,0 +1,1741 @@
+use std::cmp::Reverse;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{NaiveDateTime, NaiveTime, Utc, Weekday};
+
+use crate::models::*;
+use crate::util::collections::CollectionTools;
+
+use super::repo::{DatabaseResult, Repository};
+
+#[derive(Default)]
+struct Store {
+    calls: Vec<RollCall>,
+    responses: Vec<RollCallResponse>,
+    scheduled_calls: Vec<ScheduledCall>,
+    last_call_id: CallId,
+    last_response_id: ResponseId,
+    recurring_calls: Vec<RecurringCall>,
+    last_schedule_id: ScheduleId,
+    last_recurrence_id: RecurrenceId,
+    settings: Vec<ChatSettings>,
+    aliases: Vec<ChatAlias>,
+}
+
+/// A `Repository` that keeps everything in memory, mirroring the semantics of the SQL helpers.
+/// Data does not survive a restart, which makes it suitable for tests and local runs only.
+#[derive(Default)]
+pub struct InMemoryRepository {
+    store: Mutex<Store>,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Store> {
+        self.store
+            .lock()
+            .expect("The store was poisoned by a panic while it was being updated")
+    }
+}
+
+impl Store {
+    /// Closes the open calls of the chat.
+    fn close_open_calls(&mut self, chat_id: ChatId) -> usize {
+        let now = Utc::now().naive_local();
+        let mut updated = 0;
+        for call in self.calls.iter_mut() {
+            if call.chat_id == chat_id && call.status != CallStatus::Closed {
+                call.status = CallStatus::Closed;
+                call.updated_at = now;
+                updated += 1;
+            }
+        }
+
+        debug!("Closed {} open calls", updated);
+        updated
+    }
+
+    fn reopen_call(&mut self, chat_id: ChatId) -> Option<CallWithResponses> {
+        let latest_call = self
+            .calls
+            .iter_mut()
+            .filter(|call| call.chat_id == chat_id)
+            .max_by_key(|call| (call.created_at, call.id))
+            .filter(|call| call.status == CallStatus::Closed)?;
+
+        apply_update(
+            latest_call,
+            UpdateRollCall::new().with_status(CallStatus::Open),
+        );
+
+        let call = latest_call.clone();
+        let responses = self.get_responses(call.id);
+        Some((call, responses))
+    }
+
+    /// Deletes the calls past their age at once, and goes through the calls of each chat only
+    /// for a limit on their number.
+    fn delete_old_calls(&mut self, retention: &Retention, now: NaiveDateTime) -> PrunedCalls {
+        let mut to_delete: Vec<CallId> = self
+            .calls
+            .iter()
+            .filter(|call| call.status == CallStatus::Closed)
+            .filter(|call| match retention.for_chat(call.chat_id).max_age {
+                Some(max_age) => call.created_at < now - max_age,
+                None => false,
+            })
+            .map(|call| call.id)
+            .collect();
+
+        let calls_by_chat = self.calls.iter().into_groups_by(|call| call.chat_id);
+        for (chat_id, mut calls) in calls_by_chat {
+            let max_calls = match retention.for_chat(chat_id).max_calls {
+                Some(max_calls) => max_calls,
+                None => continue,
+            };
+
+            // open calls count towards the limit, but are kept past it
+            calls.sort_by_key(|call| Reverse((call.created_at, call.id)));
+            to_delete.extend(
+                calls
+                    .into_iter()
+                    .skip(max_calls)
+                    .filter(|call| call.status == CallStatus::Closed)
+                    .map(|call| call.id),
+            );
+        }
+
+        let (calls, responses) = (self.calls.len(), self.responses.len());
+        self.calls.retain(|call| !to_delete.contains(&call.id));
+        self.responses
+            .retain(|response| !to_delete.contains(&response.roll_call_id));
+
+        PrunedCalls {
+            calls: calls - self.calls.len(),
+            responses: responses - self.responses.len(),
+        }
+    }
+
+    fn insert_new_call(&mut self, chat_id: ChatId, title: &str) -> RollCall {
+        let settings = self.get_settings(chat_id);
+        let new_call = NewRollCall::new(&settings, title);
+        self.last_call_id += 1;
+
+        let result = RollCall {
+            id: self.last_call_id,
+            chat_id: new_call.chat_id,
+            status: new_call.status,
+            title: new_call.title.to_string(),
+            quiet: new_call.quiet,
+            created_at: new_call.created_at,
+            updated_at: new_call.updated_at,
+            attendance_limit: None,
+            board_message_id: None,
+            pinned_message_id: None,
+        };
+
+        self.calls.push(result.clone());
+        debug!("Inserted new call: {:?}", result);
+        result
+    }
+
+    fn open_call_mut(&mut self, chat_id: ChatId, selector: &CallSelector) -> Option<&mut RollCall> {
+        self.calls
+            .iter_mut()
+            .filter(|call| call.chat_id == chat_id && call.status == CallStatus::Open)
+            .filter(|call| selector.matches(call))
+            .max_by_key(|call| (call.created_at, call.id))
+    }
+
+    fn update_call(
+        &mut self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        update: UpdateRollCall,
+    ) -> Option<RollCall> {
+        let updated = self.open_call_mut(chat_id, selector).map(|call| {
+            apply_update(call, update);
+            call.clone()
+        });
+
+        debug!("Updated call: {:?}", updated; "call_id" => updated.as_ref().map(|u| u.id));
+        updated
+    }
+
+    fn update_call_with_responses(
+        &mut self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        update: UpdateRollCall,
+    ) -> Option<CallWithResponses> {
+        self.update_call(chat_id, selector, update).map(|call| {
+            let responses = self.get_responses(call.id);
+            (call, responses)
+        })
+    }
+
+    fn update_call_by_id(
+        &mut self,
+        chat_id: ChatId,
+        call_id: CallId,
+        update: UpdateRollCall,
+    ) -> bool {
+        let call = self
+            .calls
+            .iter_mut()
+            .find(|call| call.chat_id == chat_id && call.id == call_id);
+
+        debug!("Updated {} calls", call.is_some() as usize; "call_id" => call_id);
+        match call {
+            Some(call) => {
+                apply_update(call, update);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn get_calls(&self, chat_id: ChatId, status: CallStatus) -> Vec<RollCall> {
+        let mut calls: Vec<RollCall> = self
+            .calls
+            .iter()
+            .filter(|call| call.chat_id == chat_id && call.status == status)
+            .cloned()
+            .collect();
+
+        calls.sort_by_key(|call| Reverse((call.created_at, call.id)));
+        calls
+    }
+
+    fn get_settings(&self, chat_id: ChatId) -> ChatSettings {
+        self.settings
+            .iter()
+            .find(|settings| settings.chat_id == chat_id)
+            .cloned()
+            .unwrap_or_else(|| ChatSettings::default_for(chat_id))
+    }
+
+    fn update_settings(&mut self, chat_id: ChatId, update: &UpdateChatSettings) -> ChatSettings {
+        let index = match self.settings.iter().position(|s| s.chat_id == chat_id) {
+            Some(index) => index,
+            None => {
+                self.settings.push(ChatSettings::default_for(chat_id));
+                self.settings.len() - 1
+            }
+        };
+
+        let settings = &mut self.settings[index];
+        if let Some(pin_calls) = update.pin_calls {
+            settings.pin_calls = pin_calls;
+        }
+        if let Some(answer) = update.answer_unknown_commands {
+            settings.answer_unknown_commands = answer;
+        }
+        if let Some(quiet) = update.quiet {
+            settings.quiet = quiet;
+        }
+        if let Some(ref language) = update.language {
+            settings.language = language.clone();
+        }
+        if let Some(ref timezone) = update.timezone {
+            settings.timezone = timezone.clone();
+        }
+        if let Some(reply_style) = update.reply_style {
+            settings.reply_style = reply_style;
+        }
+        settings.updated_at = update.updated_at;
+
+        debug!("Updated settings: {:?}", settings);
+        settings.clone()
+    }
+
+    fn get_aliases(&self, chat_id: ChatId) -> Vec<ChatAlias> {
+        let mut aliases: Vec<ChatAlias> = self
+            .aliases
+            .iter()
+            .filter(|alias| alias.chat_id == chat_id)
+            .cloned()
+            .collect();
+        aliases.sort_by(|a, b| a.alias.cmp(&b.alias));
+        aliases
+    }
+
+    fn add_alias(&mut self, chat_id: ChatId, alias: &str, command: &str) -> ChatAlias {
+        self.delete_alias(chat_id, alias);
+        let new_alias = NewChatAlias::new(chat_id, alias, command);
+        let result = ChatAlias {
+            chat_id,
+            alias: new_alias.alias.to_owned(),
+            command: new_alias.command.to_owned(),
+            created_at: new_alias.created_at,
+        };
+
+        self.aliases.push(result.clone());
+        debug!("Inserted new alias: {:?}", result);
+        result
+    }
+
+    fn delete_alias(&mut self, chat_id: ChatId, alias: &str) -> bool {
+        let count = self.aliases.len();
+        self.aliases
+            .retain(|existing| !(existing.chat_id == chat_id && existing.alias == alias));
+
+        let deleted = count - self.aliases.len();
+        debug!("Deleted {} aliases", deleted; "alias" => alias);
+        deleted > 0
+    }
+
+    fn get_open_call(&self, chat_id: ChatId, selector: &CallSelector) -> Option<RollCall> {
+        self.calls
+            .iter()
+            .filter(|call| call.chat_id == chat_id && call.status == CallStatus::Open)
+            .filter(|call| selector.matches(call))
+            .max_by_key(|call| (call.created_at, call.id))
+            .cloned()
+    }
+
+    fn get_responses(&self, call_id: CallId) -> Vec<RollCallResponse> {
+        let mut responses: Vec<RollCallResponse> = self
+            .responses
+            .iter()
+            .filter(|response| response.roll_call_id == call_id)
+            .cloned()
+            .collect();
+
+        responses.sort_by_key(|response| Reverse(response.updated_at));
+        responses
+    }
+
+    fn get_call_with_responses(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+    ) -> Option<CallWithResponses> {
+        self.get_open_call(chat_id, selector).map(|call| {
+            let responses = self.get_responses(call.id);
+            (call, responses)
+        })
+    }
+
+    fn get_calls_with_responses(
+        &self,
+        chat_id: ChatId,
+        status: CallStatus,
+    ) -> Vec<CallWithResponses> {
+        self.get_calls(chat_id, status)
+            .into_iter()
+            .map(|call| {
+                let responses = self.get_responses(call.id);
+                (call, responses)
+            })
+            .collect()
+    }
+
+    fn get_closed_call(&self, chat_id: ChatId, call_id: CallId) -> Option<CallWithResponses> {
+        self.calls
+            .iter()
+            .find(|call| {
+                call.id == call_id && call.chat_id == chat_id && call.status == CallStatus::Closed
+            })
+            .cloned()
+            .map(|call| {
+                let responses = self.get_responses(call.id);
+                (call, responses)
+            })
+    }
+
+    fn delete_response(
+        &mut self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        unique_token: &str,
+    ) -> Option<ResponseRemoval> {
+        let open_call = self.get_open_call(chat_id, selector)?;
+
+        let count = self.responses.len();
+        self.responses.retain(|response| {
+            response.roll_call_id != open_call.id || response.unique_token != unique_token
+        });
+        let deleted = count - self.responses.len();
+
+        debug!("Deleted {} responses", deleted; "call_id" => open_call.id);
+        let promoted = self.promote_waitlist(&open_call);
+        let responses = self.get_responses(open_call.id);
+
+        Some(ResponseRemoval {
+            call_with_responses: (open_call, responses),
+            removed: deleted > 0,
+            promoted,
+        })
+    }
+
+    fn set_response_base<'a, F>(
+        &mut self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        value_fn: F,
+    ) -> Option<ResponseUpdate>
+    where
+        F: Fn(CallId) -> NewRollCallResponse<'a>,
+    {
+        let open_call = self.get_open_call(chat_id, selector)?;
+        let mut record = value_fn(open_call.id);
+
+        let existing = self
+            .responses
+            .iter()
+            .find(|response| {
+                response.roll_call_id == record.roll_call_id
+                    && response.unique_token == record.unique_token
+            })
+            .map(|response| (response.status, response.guests));
+
+        let mut refused_guests = 0;
+        if record.status == AttendanceStatus::In {
+            match existing {
+                // those who are in keep their place, and only need one for the guests they add
+                Some((AttendanceStatus::In, guests)) => {
+                    let in_count = self.count_in(open_call.id) - 1 - i64::from(guests);
+                    if record.guests > guests && !open_call.fits(in_count, record.party_size()) {
+                        refused_guests = record.guests - guests;
+                        record.guests = guests;
+                    }
+                }
+                _ => {
+                    if !open_call.fits(self.count_in(open_call.id), record.party_size()) {
+                        record.status = AttendanceStatus::Waitlist;
+                    }
+                }
+            }
+        }
+
+        let existing = self.responses.iter_mut().find(|response| {
+            response.roll_call_id == record.roll_call_id
+                && response.unique_token == record.unique_token
+        });
+
+        match existing {
+            Some(response) => {
+                // keep the place in the queue of those who are already waiting
+                let keep_place = response.status == AttendanceStatus::Waitlist
+                    && record.status == AttendanceStatus::Waitlist;
+
+                response.user_name = Some(record.user_name.to_string());
+                response.status = record.status;
+                response.reason = Some(record.reason.to_string());
+                response.guests = record.guests;
+                if !keep_place {
+                    response.updated_at = record.updated_at;
+                }
+            }
+            None => {
+                self.last_response_id += 1;
+                self.responses.push(RollCallResponse {
+                    id: self.last_response_id,
+                    roll_call_id: record.roll_call_id,
+                    unique_token: record.unique_token,
+                    user_id: record.user_id,
+                    user_name: Some(record.user_name.to_string()),
+                    status: record.status,
+                    reason: Some(record.reason.to_string()),
+                    created_at: record.created_at,
+                    updated_at: record.updated_at,
+                    guests: record.guests,
+                });
+            }
+        }
+
+        debug!("Added 1 response"; "call_id" => open_call.id);
+        let promoted = self.promote_waitlist(&open_call);
+        let responses = self.get_responses(open_call.id);
+
+        Some(ResponseUpdate {
+            call_with_responses: (open_call, responses),
+            status: record.status,
+            promoted,
+            refused_guests,
+        })
+    }
+
+    /// Counts the places taken by those who are in, guests included.
+    fn count_in(&self, call_id: CallId) -> i64 {
+        self.responses
+            .iter()
+            .filter(|response| {
+                response.roll_call_id == call_id && response.status == AttendanceStatus::In
+            })
+            .map(RollCallResponse::party_size)
+            .sum()
+    }
+
+    fn promote_waitlist(&mut self, call: &RollCall) -> Vec<RollCallResponse> {
+        let mut free_places = call.free_places(self.count_in(call.id)).unwrap_or(i64::MAX);
+
+        let mut waitlist: Vec<(NaiveDateTime, ResponseId)> = self
+            .responses
+            .iter()
+            .filter(|response| {
+                response.roll_call_id == call.id && response.status == AttendanceStatus::Waitlist
+            })
+            .map(|response| (response.updated_at, response.id))
+            .collect();
+        waitlist.sort();
+
+        let now = Utc::now().naive_local();
+        let mut promoted = vec![];
+        for (_, response_id) in waitlist {
+            if let Some(response) = self.responses.iter_mut().find(|r| r.id == response_id) {
+                // parties that are too large wait for more places to free up
+                if response.party_size() > free_places {
+                    continue;
+                }
+
+                free_places -= response.party_size();
+                response.status = AttendanceStatus::In;
+                response.updated_at = now;
+                promoted.push(response.clone());
+            }
+        }
+
+        debug!("Promoted {} responses from the waitlist", promoted.len(); "call_id" => call.id);
+        promoted
+    }
+
+    fn update_limit(
+        &mut self,
+        chat_id: ChatId,
+        limit: Option<i32>,
+    ) -> Option<(CallWithResponses, Vec<RollCallResponse>)> {
+        let update = UpdateRollCall::new().with_attendance_limit(limit);
+        let call = self.update_call(chat_id, &CallSelector::Latest, update)?;
+        let promoted = self.promote_waitlist(&call);
+        let responses = self.get_responses(call.id);
+        Some(((call, responses), promoted))
+    }
+
+    fn create_call(&mut self, chat_id: ChatId, title: &str, alongside: bool) -> RollCall {
+        if !alongside {
+            self.close_open_calls(chat_id);
+        }
+        self.insert_new_call(chat_id, title)
+    }
+
+    fn restart_call(&mut self, chat_id: ChatId, title: &str, keep: bool) -> Option<RestartedCall> {
+        let previous_id = self
+            .calls
+            .iter()
+            .filter(|call| call.chat_id == chat_id)
+            .max_by_key(|call| (call.created_at, call.id))?
+            .id;
+
+        // only the previous call is replaced, the others may run alongside it
+        let update = UpdateRollCall::new().with_status(CallStatus::Closed);
+        self.update_call_by_id(chat_id, previous_id, update);
+        let previous_call = self
+            .calls
+            .iter()
+            .find(|call| call.id == previous_id)
+            .cloned()?;
+        let previous_responses = self.get_responses(previous_id);
+
+        let title = match title {
+            "" => previous_call.title.as_str(),
+            title => title,
+        };
+        let call = self.create_call(chat_id, title, true);
+
+        if keep {
+            for response in previous_responses.iter() {
+                let record = NewRollCallResponse::carried_over(call.id, response);
+                self.last_response_id += 1;
+                self.responses.push(RollCallResponse {
+                    id: self.last_response_id,
+                    roll_call_id: record.roll_call_id,
+                    unique_token: record.unique_token,
+                    user_id: record.user_id,
+                    user_name: Some(record.user_name.to_string()),
+                    status: record.status,
+                    reason: Some(record.reason.to_string()),
+                    created_at: record.created_at,
+                    updated_at: record.updated_at,
+                    guests: record.guests,
+                });
+            }
+        }
+
+        let responses = self.get_responses(call.id);
+        Some(RestartedCall {
+            call_with_responses: (call, responses),
+            previous_call: (previous_call, previous_responses),
+        })
+    }
+
+    fn insert_scheduled_call(
+        &mut self,
+        chat_id: ChatId,
+        title: &str,
+        start_at: NaiveDateTime,
+        end_at: Option<NaiveDateTime>,
+    ) -> ScheduledCall {
+        let new_call = NewScheduledCall::new(chat_id, title, start_at, end_at);
+        self.last_schedule_id += 1;
+
+        let result = ScheduledCall {
+            id: self.last_schedule_id,
+            chat_id: new_call.chat_id,
+            status: new_call.status,
+            title: new_call.title.to_string(),
+            start_at: new_call.start_at,
+            end_at: new_call.end_at,
+            roll_call_id: None,
+            created_at: new_call.created_at,
+            updated_at: new_call.updated_at,
+        };
+
+        self.scheduled_calls.push(result.clone());
+        debug!("Inserted new scheduled call: {:?}", result);
+        result
+    }
+
+    fn find_due_calls<F>(&self, is_due: F) -> Vec<ScheduledCall>
+    where
+        F: Fn(&ScheduledCall) -> Option<NaiveDateTime>,
+    {
+        let mut due_calls: Vec<(NaiveDateTime, ScheduledCall)> = self
+            .scheduled_calls
+            .iter()
+            .filter_map(|scheduled| is_due(scheduled).map(|at| (at, scheduled.clone())))
+            .collect();
+
+        due_calls.sort_by_key(|(at, scheduled)| (*at, scheduled.id));
+        due_calls
+            .into_iter()
+            .map(|(_, scheduled)| scheduled)
+            .collect()
+    }
+
+    fn update_scheduled_call(&mut self, id: ScheduleId, update: UpdateScheduledCall) {
+        if let Some(scheduled) = self.scheduled_calls.iter_mut().find(|s| s.id == id) {
+            if let Some(status) = update.status {
+                scheduled.status = status;
+            }
+            if let Some(roll_call_id) = update.roll_call_id {
+                scheduled.roll_call_id = Some(roll_call_id);
+            }
+            if let Some(updated_at) = update.updated_at {
+                scheduled.updated_at = updated_at;
+            }
+        }
+    }
+
+    fn start_scheduled_calls(&mut self, now: NaiveDateTime) -> Vec<RollCall> {
+        let due_calls = self.find_due_calls(|scheduled| match scheduled.status {
+            ScheduleStatus::Pending if scheduled.start_at <= now => Some(scheduled.start_at),
+            _ => None,
+        });
+
+        let mut started = Vec::with_capacity(due_calls.len());
+        for scheduled in due_calls {
+            let call = self.create_call(scheduled.chat_id, &scheduled.title, false);
+
+            let status = match scheduled.end_at {
+                Some(_) => ScheduleStatus::Started,
+                None => ScheduleStatus::Done,
+            };
+            let update = UpdateScheduledCall::new()
+                .with_status(status)
+                .with_roll_call_id(call.id);
+            self.update_scheduled_call(scheduled.id, update);
+
+            debug!("Started scheduled call {}", scheduled.id; "call_id" => call.id);
+            started.push(call);
+        }
+
+        started
+    }
+
+    fn end_scheduled_calls(&mut self, now: NaiveDateTime) -> Vec<CallWithResponses> {
+        let due_calls = self.find_due_calls(|scheduled| match scheduled.status {
+            ScheduleStatus::Started => scheduled.end_at.filter(|&end_at| end_at <= now),
+            _ => None,
+        });
+
+        let mut ended = Vec::with_capacity(due_calls.len());
+        for scheduled in due_calls {
+            let update = UpdateScheduledCall::new().with_status(ScheduleStatus::Done);
+            self.update_scheduled_call(scheduled.id, update);
+
+            let now = Utc::now().naive_local();
+            let open_call = self.calls.iter_mut().find(|call| {
+                Some(call.id) == scheduled.roll_call_id && call.status == CallStatus::Open
+            });
+
+            if let Some(call) = open_call {
+                call.status = CallStatus::Closed;
+                call.updated_at = now;
+
+                let call = call.clone();
+                debug!("Ended scheduled call {}", scheduled.id; "call_id" => call.id);
+                let responses = self.get_responses(call.id);
+                ended.push((call, responses));
+            }
+        }
+
+        ended
+    }
+
+    fn insert_recurring_call(
+        &mut self,
+        chat_id: ChatId,
+        title: &str,
+        weekday: Weekday,
+        start_time: NaiveTime,
+    ) -> RecurringCall {
+        let settings = self.get_settings(chat_id);
+        let new_call = NewRecurringCall::new(&settings, title, weekday, start_time);
+        self.last_recurrence_id += 1;
+
+        let result = RecurringCall {
+            id: self.last_recurrence_id,
+            chat_id: new_call.chat_id,
+            title: new_call.title.to_string(),
+            weekday: new_call.weekday,
+            start_time: new_call.start_time,
+            next_at: new_call.next_at,
+            created_at: new_call.created_at,
+            updated_at: new_call.updated_at,
+            timezone: new_call.timezone.to_owned(),
+        };
+
+        self.recurring_calls.push(result.clone());
+        debug!("Inserted new recurring call: {:?}", result);
+        result
+    }
+
+    fn get_recurring_calls(&self, chat_id: ChatId) -> Vec<RecurringCall> {
+        let mut calls: Vec<RecurringCall> = self
+            .recurring_calls
+            .iter()
+            .filter(|recurring| recurring.chat_id == chat_id)
+            .cloned()
+            .collect();
+
+        calls.sort_by_key(|recurring| (recurring.weekday, recurring.start_time, recurring.id));
+        calls
+    }
+
+    fn delete_recurring_call(&mut self, chat_id: ChatId, id: RecurrenceId) -> bool {
+        let count = self.recurring_calls.len();
+        self.recurring_calls
+            .retain(|recurring| !(recurring.id == id && recurring.chat_id == chat_id));
+
+        let deleted = count - self.recurring_calls.len();
+        debug!("Deleted {} recurring calls", deleted; "recurrence_id" => id);
+        deleted > 0
+    }
+
+    fn start_recurring_calls(&mut self, now: NaiveDateTime) -> Vec<RollCall> {
+        let mut due_calls: Vec<RecurringCall> = self
+            .recurring_calls
+            .iter()
+            .filter(|recurring| recurring.next_at <= now)
+            .cloned()
+            .collect();
+        due_calls.sort_by_key(|recurring| (recurring.next_at, recurring.id));
+
+        let mut started = Vec::with_capacity(due_calls.len());
+        for recurring in due_calls {
+            let call = self.create_call(recurring.chat_id, &recurring.title, false);
+
+            let update = UpdateRecurringCall::new(recurring.next_occurrence(now));
+            if let Some(stored) = self
+                .recurring_calls
+                .iter_mut()
+                .find(|r| r.id == recurring.id)
+            {
+                stored.next_at = update.next_at;
+                stored.updated_at = update.updated_at;
+            }
+
+            debug!("Started recurring call {}", recurring.id; "call_id" => call.id);
+            started.push(call);
+        }
+
+        started
+    }
+}
+
+fn apply_update(call: &mut RollCall, update: UpdateRollCall) {
+    if let Some(status) = update.status {
+        call.status = status;
+    }
+    if let Some(title) = update.title {
+        call.title = title.to_string();
+    }
+    if let Some(quiet) = update.quiet {
+        call.quiet = quiet;
+    }
+    if let Some(attendance_limit) = update.attendance_limit {
+        call.attendance_limit = attendance_limit;
+    }
+    if let Some(board_message_id) = update.board_message_id {
+        call.board_message_id = board_message_id;
+    }
+    if let Some(pinned_message_id) = update.pinned_message_id {
+        call.pinned_message_id = pinned_message_id;
+    }
+    if let Some(updated_at) = update.updated_at {
+        call.updated_at = updated_at;
+    }
+}
+
+impl Repository for InMemoryRepository {
+    fn create_call(&self, chat_id: ChatId, title: &str) -> DatabaseResult<RollCall> {
+        Ok(self.lock().create_call(chat_id, title, false))
+    }
+
+    fn add_call(&self, chat_id: ChatId, title: &str) -> DatabaseResult<RollCall> {
+        Ok(self.lock().create_call(chat_id, title, true))
+    }
+
+    fn restart_call(
+        &self,
+        chat_id: ChatId,
+        title: &str,
+        keep: bool,
+    ) -> DatabaseResult<Option<RestartedCall>> {
+        Ok(self.lock().restart_call(chat_id, title, keep))
+    }
+
+    fn end_call(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+    ) -> DatabaseResult<Option<CallWithResponses>> {
+        let update = UpdateRollCall::new().with_status(CallStatus::Closed);
+        Ok(self
+            .lock()
+            .update_call_with_responses(chat_id, selector, update))
+    }
+
+    fn reopen_call(&self, chat_id: ChatId) -> DatabaseResult<Option<CallWithResponses>> {
+        Ok(self.lock().reopen_call(chat_id))
+    }
+
+    fn delete_old_calls(
+        &self,
+        retention: &Retention,
+        now: NaiveDateTime,
+    ) -> DatabaseResult<PrunedCalls> {
+        Ok(self.lock().delete_old_calls(retention, now))
+    }
+
+    fn update_title(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        new_title: &str,
+    ) -> DatabaseResult<Option<CallWithResponses>> {
+        let update = UpdateRollCall::new().with_title(new_title);
+        Ok(self
+            .lock()
+            .update_call_with_responses(chat_id, selector, update))
+    }
+
+    fn update_board(
+        &self,
+        chat_id: ChatId,
+        call_id: CallId,
+        message_id: Option<MessageId>,
+    ) -> DatabaseResult<bool> {
+        let update = UpdateRollCall::new().with_board_message_id(message_id);
+        Ok(self.lock().update_call_by_id(chat_id, call_id, update))
+    }
+
+    fn update_pinned_message(
+        &self,
+        chat_id: ChatId,
+        call_id: CallId,
+        message_id: Option<MessageId>,
+    ) -> DatabaseResult<bool> {
+        let update = UpdateRollCall::new().with_pinned_message_id(message_id);
+        Ok(self.lock().update_call_by_id(chat_id, call_id, update))
+    }
+
+    fn get_calls(&self, chat_id: ChatId, status: CallStatus) -> DatabaseResult<Vec<RollCall>> {
+        Ok(self.lock().get_calls(chat_id, status))
+    }
+
+    fn get_calls_with_responses(
+        &self,
+        chat_id: ChatId,
+        status: CallStatus,
+    ) -> DatabaseResult<Vec<CallWithResponses>> {
+        Ok(self.lock().get_calls_with_responses(chat_id, status))
+    }
+
+    fn get_closed_call(
+        &self,
+        chat_id: ChatId,
+        call_id: CallId,
+    ) -> DatabaseResult<Option<CallWithResponses>> {
+        Ok(self.lock().get_closed_call(chat_id, call_id))
+    }
+
+    fn get_settings(&self, chat_id: ChatId) -> DatabaseResult<ChatSettings> {
+        Ok(self.lock().get_settings(chat_id))
+    }
+
+    fn update_settings(
+        &self,
+        chat_id: ChatId,
+        update: &UpdateChatSettings,
+    ) -> DatabaseResult<ChatSettings> {
+        Ok(self.lock().update_settings(chat_id, update))
+    }
+
+    fn get_aliases(&self, chat_id: ChatId) -> DatabaseResult<Vec<ChatAlias>> {
+        Ok(self.lock().get_aliases(chat_id))
+    }
+
+    fn add_alias(&self, chat_id: ChatId, alias: &str, command: &str) -> DatabaseResult<ChatAlias> {
+        Ok(self.lock().add_alias(chat_id, alias, command))
+    }
+
+    fn delete_alias(&self, chat_id: ChatId, alias: &str) -> DatabaseResult<bool> {
+        Ok(self.lock().delete_alias(chat_id, alias))
+    }
+
+    fn update_quiet(
+        &self,
+        chat_id: ChatId,
+        quiet: bool,
+    ) -> DatabaseResult<Option<CallWithResponses>> {
+        let update = UpdateRollCall::new().with_quiet(quiet);
+        Ok(self
+            .lock()
+            .update_call_with_responses(chat_id, &CallSelector::Latest, update))
+    }
+
+    fn update_limit(
+        &self,
+        chat_id: ChatId,
+        limit: Option<i32>,
+    ) -> DatabaseResult<Option<(CallWithResponses, Vec<RollCallResponse>)>> {
+        Ok(self.lock().update_limit(chat_id, limit))
+    }
+
+    fn set_response(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        user: &ChatUser,
+        attendance: &Attendance,
+    ) -> DatabaseResult<Option<ResponseUpdate>> {
+        Ok(self.lock().set_response_base(chat_id, selector, |call_id| {
+            NewRollCallResponse::new_self(call_id, user.id, &user.name, attendance)
+        }))
+    }
+
+    fn set_response_for(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        user_name: &str,
+        attendance: &Attendance,
+    ) -> DatabaseResult<Option<ResponseUpdate>> {
+        Ok(self.lock().set_response_base(chat_id, selector, |call_id| {
+            NewRollCallResponse::new_for(call_id, user_name, attendance)
+        }))
+    }
+
+    fn set_responses_for(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        responses: &[(String, Attendance)],
+    ) -> DatabaseResult<Option<Vec<ResponseUpdate>>> {
+        let mut store = self.lock();
+        Ok(responses
+            .iter()
+            .map(|(user_name, attendance)| {
+                store.set_response_base(chat_id, selector, |call_id| {
+                    NewRollCallResponse::new_for(call_id, user_name, attendance)
+                })
+            })
+            .collect())
+    }
+
+    fn delete_response(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        unique_token: &str,
+    ) -> DatabaseResult<Option<ResponseRemoval>> {
+        Ok(self.lock().delete_response(chat_id, selector, unique_token))
+    }
+
+    fn get_call_with_responses(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+    ) -> DatabaseResult<Option<CallWithResponses>> {
+        Ok(self.lock().get_call_with_responses(chat_id, selector))
+    }
+
+    fn create_scheduled_call(
+        &self,
+        chat_id: ChatId,
+        title: &str,
+        start_at: NaiveDateTime,
+        end_at: Option<NaiveDateTime>,
+    ) -> DatabaseResult<ScheduledCall> {
+        Ok(self
+            .lock()
+            .insert_scheduled_call(chat_id, title, start_at, end_at))
+    }
+
+    fn start_scheduled_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<RollCall>> {
+        Ok(self.lock().start_scheduled_calls(now))
+    }
+
+    fn end_scheduled_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<CallWithResponses>> {
+        Ok(self.lock().end_scheduled_calls(now))
+    }
+
+    fn create_recurring_call(
+        &self,
+        chat_id: ChatId,
+        title: &str,
+        weekday: Weekday,
+        start_time: NaiveTime,
+    ) -> DatabaseResult<RecurringCall> {
+        Ok(self
+            .lock()
+            .insert_recurring_call(chat_id, title, weekday, start_time))
+    }
+
+    fn get_recurring_calls(&self, chat_id: ChatId) -> DatabaseResult<Vec<RecurringCall>> {
+        Ok(self.lock().get_recurring_calls(chat_id))
+    }
+
+    fn delete_recurring_call(&self, chat_id: ChatId, id: RecurrenceId) -> DatabaseResult<bool> {
+        Ok(self.lock().delete_recurring_call(chat_id, id))
+    }
+
+    fn start_recurring_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<RollCall>> {
+        Ok(self.lock().start_recurring_calls(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Datelike;
+
+    use crate::models::AttendanceStatus::*;
+
+    use super::*;
+
+    fn attendance(status: AttendanceStatus, reason: &str) -> Attendance {
+        Attendance::new(status, reason.to_string())
+    }
+
+    const LATEST: &CallSelector = &CallSelector::Latest;
+
+    fn minutes_from_now(minutes: i64) -> NaiveDateTime {
+        Utc::now().naive_local() + chrono::Duration::minutes(minutes)
+    }
+
+    #[test]
+    fn test_create_call_closes_open_calls() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let first = repo.create_call(1, "football")?;
+        let tennis = repo.create_call(1, "tennis")?;
+        let other_chat = repo.create_call(2, "golf")?;
+
+        let store = repo.lock();
+        let status_of = |id| store.calls.iter().find(|c| c.id == id).unwrap().status;
+        assert_eq!(CallStatus::Closed, status_of(first.id));
+        assert_eq!(CallStatus::Open, status_of(tennis.id));
+        assert_eq!(CallStatus::Open, status_of(other_chat.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_call_keeps_calls_with_the_same_title() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let first = repo.create_call(1, "football")?;
+        let tennis = repo.add_call(1, "tennis")?;
+        let second = repo.add_call(1, "football")?;
+
+        let store = repo.lock();
+        let status_of = |id| store.calls.iter().find(|c| c.id == id).unwrap().status;
+        assert_eq!(CallStatus::Open, status_of(first.id));
+        assert_eq!(CallStatus::Open, status_of(tennis.id));
+        assert_eq!(CallStatus::Open, status_of(second.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_calls_are_selected() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let football = repo.create_call(1, "Football on Saturday")?;
+        let dinner = repo.add_call(1, "Dinner on Sunday")?;
+
+        let by_id = CallSelector::Id(football.id);
+        let by_name = CallSelector::Name("dinner".to_string());
+        repo.set_response(1, &by_id, &ChatUser::new(2, "Peter"), &attendance(In, ""))?;
+        repo.set_response(
+            1,
+            &by_name,
+            &ChatUser::new(2, "Peter"),
+            &attendance(Out, ""),
+        )?;
+        repo.set_response(1, LATEST, &ChatUser::new(3, "Henry"), &attendance(In, ""))?;
+
+        let (call, responses) = repo.get_call_with_responses(1, &by_id)?.unwrap();
+        assert_eq!(football.id, call.id);
+        assert_eq!(1, responses.len());
+        assert_eq!(In, responses[0].status);
+
+        let (call, responses) = repo.get_call_with_responses(1, LATEST)?.unwrap();
+        assert_eq!(dinner.id, call.id);
+        assert_eq!(2, responses.len());
+
+        assert!(repo
+            .end_call(1, &CallSelector::Id(dinner.id + 1))?
+            .is_none());
+        assert_eq!(dinner.id, repo.end_call(1, &by_name)?.unwrap().0.id);
+        assert_eq!(football.id, repo.end_call(1, LATEST)?.unwrap().0.id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_old_calls_and_responses() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let first = repo.create_call(1, "first")?;
+        repo.set_response_for(1, LATEST, "Peter", &attendance(In, ""))?;
+        repo.end_call(1, LATEST)?;
+
+        for i in 0..10 {
+            let call = repo.create_call(1, &format!("call {}", i))?;
+            repo.end_call(1, &CallSelector::Id(call.id))?;
+        }
+        repo.create_call(2, "other chat")?;
+        repo.create_call(1, "still open")?;
+
+        // calls are no longer deleted when new ones are created
+        assert_eq!(13, repo.lock().calls.len());
+
+        let now = Utc::now().naive_local();
+        let pruned = repo.delete_old_calls(&Retention::default(), now)?;
+        assert_eq!(
+            PrunedCalls {
+                calls: 2,
+                responses: 1
+            },
+            pruned
+        );
+
+        let store = repo.lock();
+        assert_eq!(11, store.calls.len());
+        assert!(store.calls.iter().all(|call| call.id != first.id));
+        assert!(store.responses.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_old_calls_by_age_keeps_open_calls() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let open = repo.create_call(1, "open")?;
+        let closed = repo.add_call(1, "closed")?;
+        repo.end_call(1, &CallSelector::Id(closed.id))?;
+
+        let retention = Retention {
+            default: RetentionPolicy {
+                max_calls: None,
+                max_age: Some(chrono::Duration::days(1)),
+            },
+            ..Default::default()
+        };
+
+        let now = minutes_from_now(24 * 60 - 1);
+        assert_eq!(0, repo.delete_old_calls(&retention, now)?.calls);
+
+        let now = minutes_from_now(24 * 60 + 1);
+        assert_eq!(1, repo.delete_old_calls(&retention, now)?.calls);
+        assert_eq!(open.id, repo.lock().calls[0].id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_old_calls_counts_open_calls_towards_the_limit() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let open = repo.create_call(1, "open")?;
+        for title in &["first", "second", "third"] {
+            let call = repo.add_call(1, title)?;
+            repo.end_call(1, &CallSelector::Id(call.id))?;
+        }
+        for title in &["first", "second", "third"] {
+            let call = repo.create_call(2, title)?;
+            repo.end_call(2, &CallSelector::Id(call.id))?;
+        }
+
+        let policy = RetentionPolicy {
+            max_calls: Some(2),
+            max_age: None,
+        };
+        let retention = Retention {
+            default: policy,
+            chats: hashmap! { 2 => RetentionPolicy { max_calls: None, ..policy } },
+        };
+
+        let now = Utc::now().naive_local();
+        assert_eq!(1, repo.delete_old_calls(&retention, now)?.calls);
+        let titles: Vec<String> = repo
+            .get_calls(1, CallStatus::Closed)?
+            .into_iter()
+            .map(|call| call.title)
+            .collect();
+        assert_eq!(vec!["third", "second"], titles);
+        assert_eq!(open.id, repo.get_calls(1, CallStatus::Open)?[0].id);
+        assert_eq!(3, repo.get_calls(2, CallStatus::Closed)?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restart_call_keeps_calls_alongside() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let football = repo.create_call(1, "football")?;
+        let tennis = repo.add_call(1, "tennis")?;
+
+        let restarted = repo.restart_call(1, "", false)?.unwrap();
+        assert_eq!(tennis.id, restarted.previous_call.0.id);
+        assert_eq!("tennis", restarted.call_with_responses.0.title);
+
+        let store = repo.lock();
+        let status_of = |id| store.calls.iter().find(|c| c.id == id).unwrap().status;
+        assert_eq!(CallStatus::Open, status_of(football.id));
+        assert_eq!(CallStatus::Closed, status_of(tennis.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restart_call_carries_over_responses() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        assert!(repo.restart_call(1, "", false)?.is_none());
+
+        let first = repo.create_call(1, "football")?;
+        let user = ChatUser::new(1, "User 1");
+        repo.set_response(1, LATEST, &user, &attendance(In, "on time"))?;
+        repo.set_response_for(1, LATEST, "Peter", &attendance(Out, ""))?;
+
+        let restarted = repo.restart_call(1, "", false)?.unwrap();
+        let (call, responses) = restarted.call_with_responses;
+        assert_eq!("football", call.title);
+        assert!(responses.is_empty());
+        // the restarted call is closed and returned with its responses
+        let (previous_call, previous_responses) = restarted.previous_call;
+        assert_eq!(first.id, previous_call.id);
+        assert_eq!(CallStatus::Closed, previous_call.status);
+        assert_eq!(2, previous_responses.len());
+        assert_eq!(CallStatus::Closed, repo.lock().calls[0].status);
+
+        repo.set_response(1, LATEST, &user, &attendance(In, ""))?;
+        repo.set_response_for(1, LATEST, "Peter", &attendance(Out, ""))?;
+        let (call, responses) = repo
+            .restart_call(1, "tennis", true)?
+            .unwrap()
+            .call_with_responses;
+        assert_eq!("tennis", call.title);
+        assert_eq!(2, responses.len());
+        assert!(responses.iter().all(|response| response.status == Maybe));
+
+        // carried over responses are updated rather than duplicated
+        repo.set_response(1, LATEST, &user, &attendance(In, ""))?;
+        let (_, responses) = repo.get_call_with_responses(1, LATEST)?.unwrap();
+        assert_eq!(2, responses.len());
+        assert_ne!(first.id, call.id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_response_promotes_waitlist() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_call(1, "football")?;
+        repo.update_limit(1, Some(1))?;
+        repo.set_response(1, LATEST, &ChatUser::new(1, "User 1"), &attendance(In, ""))?;
+        repo.set_response_for(1, LATEST, "Peter", &attendance(In, ""))?;
+
+        let token = NewRollCallResponse::self_token(2);
+        let removal = repo.delete_response(1, LATEST, &token)?.unwrap();
+        assert!(!removal.removed);
+        assert_eq!(2, removal.call_with_responses.1.len());
+
+        let token = NewRollCallResponse::self_token(1);
+        let removal = repo.delete_response(1, LATEST, &token)?.unwrap();
+        assert!(removal.removed);
+        assert_eq!(1, removal.call_with_responses.1.len());
+        assert_eq!(Some("Peter".to_string()), removal.promoted[0].user_name);
+
+        let token = NewRollCallResponse::for_token("PETER");
+        assert!(repo.delete_response(1, LATEST, &token)?.unwrap().removed);
+        assert!(repo.delete_response(2, LATEST, &token)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reopen_only_latest_call() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let first = repo.create_call(1, "football")?;
+        repo.set_response(1, LATEST, &ChatUser::new(1, "User 1"), &attendance(In, ""))?;
+        assert!(repo.reopen_call(1)?.is_none());
+
+        repo.end_call(1, LATEST)?;
+        let (call, responses) = repo.reopen_call(1)?.unwrap();
+        assert_eq!(first.id, call.id);
+        assert_eq!(CallStatus::Open, call.status);
+        assert_eq!(1, responses.len());
+
+        // replaced calls stay closed
+        repo.create_call(1, "football")?;
+        repo.end_call(1, LATEST)?;
+        let (call, _) = repo.reopen_call(1)?.unwrap();
+        assert_ne!(first.id, call.id);
+        assert!(repo.reopen_call(1)?.is_none());
+        assert!(repo.reopen_call(2)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_calls_without_call_in_progress() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        assert!(repo.end_call(1, LATEST)?.is_none());
+        assert!(repo.update_title(1, LATEST, "title")?.is_none());
+        assert!(repo.update_quiet(1, true)?.is_none());
+        assert!(repo
+            .set_response(1, LATEST, &ChatUser::new(2, "Peter"), &attendance(In, ""))?
+            .is_none());
+        assert!(repo.get_call_with_responses(1, LATEST)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_current_call() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_call(1, "title")?;
+
+        assert_eq!(
+            "new title",
+            repo.update_title(1, LATEST, "new title")?.unwrap().0.title
+        );
+        assert!(repo.update_quiet(1, true)?.unwrap().0.quiet);
+        assert_eq!(
+            CallStatus::Closed,
+            repo.end_call(1, LATEST)?.unwrap().0.status
+        );
+        assert!(repo.get_call_with_responses(1, LATEST)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_board_of_call_in_chat() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let call = repo.create_call(1, "title")?;
+
+        assert!(!repo.update_board(2, call.id, Some(100))?);
+        assert!(repo.update_board(1, call.id, Some(100))?);
+        let (call, _) = repo.end_call(1, LATEST)?.unwrap();
+        assert_eq!(Some(100), call.board_message_id);
+
+        // the board of a closed call can still be forgotten
+        assert!(repo.update_board(1, call.id, None)?);
+        assert_eq!(None, repo.lock().calls[0].board_message_id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_message_and_open_calls() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let football = repo.create_call(1, "football")?;
+        let tennis = repo.add_call(1, "tennis")?;
+        repo.create_call(2, "golf")?;
+
+        assert!(repo.update_pinned_message(1, football.id, Some(100))?);
+        repo.end_call(1, LATEST)?;
+
+        let open_calls = repo.get_calls(1, CallStatus::Open)?;
+        assert_eq!(
+            vec![football.id],
+            open_calls.iter().map(|c| c.id).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(100), open_calls[0].pinned_message_id);
+        let closed_calls = repo.get_calls(1, CallStatus::Closed)?;
+        assert_eq!(
+            vec![tennis.id],
+            closed_calls.iter().map(|c| c.id).collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_closed_calls_are_found_in_their_chat() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let call = repo.create_call(1, "football")?;
+        repo.set_response(1, LATEST, &ChatUser::new(1, "User 1"), &attendance(In, ""))?;
+        assert!(repo.get_closed_call(1, call.id)?.is_none());
+
+        repo.end_call(1, LATEST)?;
+        repo.create_call(1, "tennis")?;
+
+        let history = repo.get_calls_with_responses(1, CallStatus::Closed)?;
+        assert_eq!(1, history.len());
+        assert_eq!(call.id, history[0].0.id);
+        assert_eq!(1, history[0].1.len());
+
+        assert!(repo.get_closed_call(1, call.id)?.is_some());
+        assert!(repo.get_closed_call(2, call.id)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_settings_default_until_updated() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        assert!(!repo.get_settings(1)?.pin_calls);
+
+        let update = UpdateChatSettings::new().with_pin_calls(true);
+        assert!(repo.update_settings(1, &update)?.pin_calls);
+        assert!(repo.get_settings(1)?.pin_calls);
+        assert!(!repo.get_settings(2)?.pin_calls);
+
+        // updates without a value keep the current one
+        assert!(
+            repo.update_settings(1, &UpdateChatSettings::new())?
+                .pin_calls
+        );
+        assert_eq!(1, repo.lock().settings.len());
+
+        let update = UpdateChatSettings::new().with_answer_unknown_commands(true);
+        let settings = repo.update_settings(1, &update)?;
+        assert!(settings.answer_unknown_commands && settings.pin_calls);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_calls_take_defaults_from_settings() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        assert!(!repo.create_call(1, "football")?.quiet);
+
+        repo.update_settings(1, &UpdateChatSettings::new().with_quiet(true))?;
+        assert!(repo.create_call(1, "football")?.quiet);
+        assert!(!repo.create_call(2, "football")?.quiet);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_response_upserts_by_unique_token() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_call(1, "title")?;
+
+        repo.set_response(
+            1,
+            LATEST,
+            &ChatUser::new(2, "Peter"),
+            &attendance(In, "will come"),
+        )?;
+        repo.set_response_for(1, LATEST, "Henry", &attendance(Maybe, ""))?;
+        repo.set_response_for(1, LATEST, "henry", &attendance(Out, "sick"))?;
+        let (_, responses) = repo
+            .set_response(
+                1,
+                LATEST,
+                &ChatUser::new(2, "Peter"),
+                &attendance(Out, "changed my mind"),
+            )?
+            .unwrap()
+            .call_with_responses;
+
+        assert_eq!(2, responses.len());
+        assert_eq!(Some("Peter".to_string()), responses[0].user_name);
+        assert_eq!(Out, responses[0].status);
+        assert_eq!(Some("changed my mind".to_string()), responses[0].reason);
+        assert_eq!(Some("henry".to_string()), responses[1].user_name);
+        assert_eq!(Out, responses[1].status);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduled_call_is_started_and_ended() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let scheduled = repo.create_scheduled_call(
+            1,
+            "football",
+            minutes_from_now(10),
+            Some(minutes_from_now(60)),
+        )?;
+        assert_eq!(ScheduleStatus::Pending, scheduled.status);
+
+        assert!(repo.start_scheduled_calls(minutes_from_now(5))?.is_empty());
+        let started = repo.start_scheduled_calls(minutes_from_now(15))?;
+        assert_eq!(1, started.len());
+        assert_eq!("football", started[0].title);
+        assert!(repo.start_scheduled_calls(minutes_from_now(20))?.is_empty());
+
+        repo.set_response(1, LATEST, &ChatUser::new(2, "Peter"), &attendance(In, ""))?;
+        assert!(repo.end_scheduled_calls(minutes_from_now(30))?.is_empty());
+
+        let ended = repo.end_scheduled_calls(minutes_from_now(90))?;
+        assert_eq!(1, ended.len());
+        assert_eq!(CallStatus::Closed, ended[0].0.status);
+        assert_eq!(1, ended[0].1.len());
+        assert!(repo.get_call_with_responses(1, LATEST)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduled_call_replaced_by_hand_is_not_ended() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_scheduled_call(
+            1,
+            "football",
+            minutes_from_now(-10),
+            Some(minutes_from_now(10)),
+        )?;
+        repo.start_scheduled_calls(minutes_from_now(0))?;
+        let replacement = repo.create_call(1, "football")?;
+
+        assert!(repo.end_scheduled_calls(minutes_from_now(20))?.is_empty());
+        let (call, _) = repo.get_call_with_responses(1, LATEST)?.unwrap();
+        assert_eq!(replacement.id, call.id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recurring_call_is_started_every_week() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let start_time = NaiveTime::from_hms(18, 0, 0);
+        let recurring = repo.create_recurring_call(1, "football", Weekday::Tue, start_time)?;
+        let first = recurring.next_at;
+        assert_eq!(Weekday::Tue, first.weekday());
+        assert!(first > Utc::now().naive_local());
+
+        assert!(repo
+            .start_recurring_calls(first - chrono::Duration::minutes(1))?
+            .is_empty());
+        let started = repo.start_recurring_calls(first)?;
+        assert_eq!(1, started.len());
+        assert_eq!("football", started[0].title);
+        assert!(repo.start_recurring_calls(first)?.is_empty());
+
+        let next = repo.get_recurring_calls(1)?[0].next_at;
+        assert_eq!(first + chrono::Duration::weeks(1), next);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_recurring_call_only_in_its_chat() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let start_time = NaiveTime::from_hms(18, 0, 0);
+        let recurring = repo.create_recurring_call(1, "football", Weekday::Tue, start_time)?;
+
+        assert!(!repo.delete_recurring_call(2, recurring.id)?);
+        assert!(repo.delete_recurring_call(1, recurring.id)?);
+        assert!(repo.get_recurring_calls(1)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_alias_replaces_it_in_its_chat() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.add_alias(1, "/yes", "/in")?;
+        repo.add_alias(1, "/no", "/out")?;
+        repo.add_alias(2, "/yes", "/maybe")?;
+        repo.add_alias(1, "/yes", "/maybe")?;
+
+        let aliases: Vec<(String, String)> = repo
+            .get_aliases(1)?
+            .into_iter()
+            .map(|alias| (alias.alias, alias.command))
+            .collect();
+        let expected = vec![
+            ("/no".to_string(), "/out".to_string()),
+            ("/yes".to_string(), "/maybe".to_string()),
+        ];
+        assert_eq!(expected, aliases);
+
+        assert!(repo.delete_alias(1, "/yes")?);
+        assert!(!repo.delete_alias(1, "/yes")?);
+        assert_eq!(1, repo.get_aliases(2)?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_response_puts_in_on_waitlist_when_full() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_call(1, "title")?;
+        repo.update_limit(1, Some(1))?;
+
+        let peter = repo
+            .set_response(1, LATEST, &ChatUser::new(2, "Peter"), &attendance(In, ""))?
+            .unwrap();
+        assert_eq!(In, peter.status);
+
+        let henry = repo
+            .set_response(1, LATEST, &ChatUser::new(3, "Henry"), &attendance(In, ""))?
+            .unwrap();
+        assert_eq!(Waitlist, henry.status);
+        assert!(henry.promoted.is_empty());
+
+        // changing the reason does not move anyone
+        let peter = repo
+            .set_response(
+                1,
+                LATEST,
+                &ChatUser::new(2, "Peter"),
+                &attendance(In, "early"),
+            )?
+            .unwrap();
+        assert_eq!(In, peter.status);
+        let henry = repo
+            .set_response(
+                1,
+                LATEST,
+                &ChatUser::new(3, "Henry"),
+                &attendance(In, "please"),
+            )?
+            .unwrap();
+        assert_eq!(Waitlist, henry.status);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_responses_for_several_names() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        let responses = vec![
+            ("Dave".to_string(), attendance(In, "")),
+            ("Sarah".to_string(), attendance(In, "late")),
+        ];
+        assert!(repo.set_responses_for(1, LATEST, &responses)?.is_none());
+
+        repo.create_call(1, "title")?;
+        repo.update_limit(1, Some(1))?;
+        let updates = repo.set_responses_for(1, LATEST, &responses)?.unwrap();
+        assert_eq!(
+            vec![In, Waitlist],
+            updates.iter().map(|u| u.status).collect::<Vec<_>>()
+        );
+        assert_eq!(2, updates[1].call_with_responses.1.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_waitlist_is_promoted_in_order() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_call(1, "title")?;
+        repo.update_limit(1, Some(1))?;
+
+        repo.set_response(1, LATEST, &ChatUser::new(2, "Peter"), &attendance(In, ""))?;
+        repo.set_response(1, LATEST, &ChatUser::new(3, "Henry"), &attendance(In, ""))?;
+        repo.set_response_for(1, LATEST, "Albert", &attendance(In, ""))?;
+
+        let update = repo
+            .set_response(1, LATEST, &ChatUser::new(2, "Peter"), &attendance(Out, ""))?
+            .unwrap();
+        assert_eq!(1, update.promoted.len());
+        assert_eq!(Some("Henry".to_string()), update.promoted[0].user_name);
+        assert_eq!(In, update.promoted[0].status);
+
+        let (_, promoted) = repo.update_limit(1, None)?.unwrap();
+        assert_eq!(1, promoted.len());
+        assert_eq!(Some("Albert".to_string()), promoted[0].user_name);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_guests_take_places_within_the_limit() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_call(1, "title")?;
+        repo.update_limit(1, Some(4))?;
+
+        let with_guests = |guests| attendance(In, "").with_guests(guests);
+        let update = repo
+            .set_response(1, LATEST, &ChatUser::new(2, "Peter"), &with_guests(2))?
+            .unwrap();
+        assert_eq!(In, update.status);
+
+        // a party of two does not fit in the last place, but one person does
+        let update = repo
+            .set_response(1, LATEST, &ChatUser::new(3, "Henry"), &with_guests(1))?
+            .unwrap();
+        assert_eq!(Waitlist, update.status);
+        let update = repo
+            .set_response_for(1, LATEST, "Albert", &with_guests(0))?
+            .unwrap();
+        assert_eq!(In, update.status);
+
+        // bringing more guests than there are places keeps the party as it was
+        let update = repo
+            .set_response(1, LATEST, &ChatUser::new(2, "Peter"), &with_guests(3))?
+            .unwrap();
+        assert_eq!(In, update.status);
+        assert_eq!(1, update.refused_guests);
+        assert!(update.promoted.is_empty());
+
+        let (_, responses) = update.call_with_responses;
+        let peter = responses.iter().find(|r| r.user_id == Some(2)).unwrap();
+        assert_eq!((In, 2), (peter.status, peter.guests));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_guests_that_do_not_fit_keep_the_responder_in() -> DatabaseResult<()> {
+        let repo = InMemoryRepository::new();
+        repo.create_call(1, "title")?;
+        repo.update_limit(1, Some(2))?;
+
+        let user = ChatUser::new(2, "Peter");
+        repo.set_response(1, LATEST, &user, &attendance(In, ""))?;
+        repo.set_response_for(1, LATEST, "Albert", &attendance(In, ""))?;
+
+        let update = repo
+            .set_response(1, LATEST, &user, &attendance(In, "").with_guests(1))?
+            .unwrap();
+        assert_eq!(In, update.status);
+        assert_eq!(1, update.refused_guests);
+
+        let (_, responses) = update.call_with_responses;
+        assert!(responses.iter().all(|r| r.status == In && r.guests == 0));
+
+        Ok(())
+    }
+}
@@ -1,8 +1,22 @@
+use diesel::pg::PgConnection;
 use diesel::prelude::*;
+#[cfg(feature = "sqlite")]
+use diesel::sqlite::SqliteConnection;
 
 use super::helpers;
 
-embed_migrations!("migrations");
+mod postgres {
+    embed_migrations!("migrations");
+
+    pub use self::embedded_migrations::run_with_output;
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    embed_migrations!("migrations_sqlite");
+
+    pub use self::embedded_migrations::run_with_output;
+}
 
 #[derive(Fail, Debug)]
 pub enum MigrationError {
@@ -15,11 +29,22 @@ pub enum MigrationError {
 
 pub type MigrationResult = Result<(), MigrationError>;
 
-pub fn migrate(database_url: &str) -> MigrationResult {
-    let connection =
-        helpers::create_connection(database_url).map_err(MigrationError::ConnectError)?;
+pub fn migrate_postgres(database_url: &str) -> MigrationResult {
+    let connection = helpers::create_connection::<PgConnection>(database_url)
+        .map_err(MigrationError::ConnectError)?;
+
+    postgres::run_with_output(&connection, &mut std::io::stdout())
+        .map_err(MigrationError::MigrationError)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+pub fn migrate_sqlite(database_url: &str) -> MigrationResult {
+    let connection = helpers::create_connection::<SqliteConnection>(database_url)
+        .map_err(MigrationError::ConnectError)?;
 
-    embedded_migrations::run_with_output(&connection, &mut std::io::stdout())
+    sqlite::run_with_output(&connection, &mut std::io::stdout())
         .map_err(MigrationError::MigrationError)?;
 
     Ok(())
@@ -1,6 +1,14 @@
-pub use migration::migrate;
-pub use repo::{DatabaseResult, PostgresRepository, Repository};
+pub use memory::InMemoryRepository;
+pub use migration::migrate_postgres;
+#[cfg(feature = "sqlite")]
+pub use migration::migrate_sqlite;
+#[cfg(test)]
+pub use repo::RepositoryMock;
+#[cfg(feature = "sqlite")]
+pub use repo::SqliteRepository;
+pub use repo::{DatabaseError, DatabaseResult, DieselRepository, PostgresRepository, Repository};
 
 mod helpers;
+mod memory;
 mod migration;
 mod repo;
@@ -1,6 +1,10 @@
 use std::time::Duration;
 
-use diesel::result::QueryResult;
+use chrono::{NaiveDateTime, NaiveTime, Weekday};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+#[cfg(feature = "sqlite")]
+use diesel::sqlite::SqliteConnection;
 #[cfg(test)]
 use mockers_derive::mocked;
 use r2d2;
@@ -22,11 +26,100 @@ pub type DatabaseResult<T> = Result<T, DatabaseError>;
 
 #[cfg_attr(test, mocked)]
 pub trait Repository {
+    /// Opens a new call, closing the open calls of the chat.
     fn create_call(&self, chat_id: ChatId, title: &str) -> DatabaseResult<RollCall>;
 
-    fn end_call(&self, chat_id: ChatId) -> DatabaseResult<Option<RollCall>>;
+    /// Opens a new call alongside the open calls of the chat, which all stay open, even those with
+    /// the same title. The open calls are picked with a `CallSelector` by the methods below.
+    fn add_call(&self, chat_id: ChatId, title: &str) -> DatabaseResult<RollCall>;
 
-    fn update_title(&self, chat_id: ChatId, new_title: &str) -> DatabaseResult<Option<RollCall>>;
+    /// Closes the latest call of the chat and opens a new call like `add_call`, leaving any other
+    /// open calls alone, with the title of the latest call if `title` is empty. With `keep`, everyone who responded to the latest
+    /// call is carried over as a `Maybe`. Returns `None` if the chat has no call yet.
+    fn restart_call(
+        &self,
+        chat_id: ChatId,
+        title: &str,
+        keep: bool,
+    ) -> DatabaseResult<Option<RestartedCall>>;
+
+    fn end_call(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+    ) -> DatabaseResult<Option<CallWithResponses>>;
+
+    /// Opens the latest call of the chat again if it has ended, so that calls replaced by newer
+    /// ones stay closed.
+    fn reopen_call(&self, chat_id: ChatId) -> DatabaseResult<Option<CallWithResponses>>;
+
+    /// Deletes the calls that the retention policy of their chat no longer keeps, along with
+    /// their responses.
+    fn delete_old_calls(
+        &self,
+        retention: &Retention,
+        now: NaiveDateTime,
+    ) -> DatabaseResult<PrunedCalls>;
+
+    fn update_title(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        new_title: &str,
+    ) -> DatabaseResult<Option<CallWithResponses>>;
+
+    /// Remembers the message that shows the board of a call, open or not, or forgets it if
+    /// `message_id` is `None`. Returns whether the call exists in the chat.
+    fn update_board(
+        &self,
+        chat_id: ChatId,
+        call_id: CallId,
+        message_id: Option<MessageId>,
+    ) -> DatabaseResult<bool>;
+
+    /// Remembers the message pinned for a call, open or not, or forgets it if `message_id` is
+    /// `None`. Returns whether the call exists in the chat.
+    fn update_pinned_message(
+        &self,
+        chat_id: ChatId,
+        call_id: CallId,
+        message_id: Option<MessageId>,
+    ) -> DatabaseResult<bool>;
+
+    /// Returns the calls of the chat with the given status, the latest first.
+    fn get_calls(&self, chat_id: ChatId, status: CallStatus) -> DatabaseResult<Vec<RollCall>>;
+
+    /// Returns the calls of the chat with the given status and their responses, the latest first.
+    fn get_calls_with_responses(
+        &self,
+        chat_id: ChatId,
+        status: CallStatus,
+    ) -> DatabaseResult<Vec<CallWithResponses>>;
+
+    /// Returns a call of the chat that has ended, by its number.
+    fn get_closed_call(
+        &self,
+        chat_id: ChatId,
+        call_id: CallId,
+    ) -> DatabaseResult<Option<CallWithResponses>>;
+
+    /// Returns the settings of the chat, or the defaults if it never changed them.
+    fn get_settings(&self, chat_id: ChatId) -> DatabaseResult<ChatSettings>;
+
+    fn update_settings(
+        &self,
+        chat_id: ChatId,
+        update: &UpdateChatSettings,
+    ) -> DatabaseResult<ChatSettings>;
+
+    /// Returns the aliases the chat gave to commands, ordered by alias.
+    fn get_aliases(&self, chat_id: ChatId) -> DatabaseResult<Vec<ChatAlias>>;
+
+    /// Gives a command an alias in the chat, replacing the command the alias stood for, if any.
+    fn add_alias(&self, chat_id: ChatId, alias: &str, command: &str) -> DatabaseResult<ChatAlias>;
+
+    /// Returns whether the alias existed in the chat.
+    fn delete_alias(&self, chat_id: ChatId, alias: &str) -> DatabaseResult<bool>;
 
     fn update_quiet(
         &self,
@@ -34,40 +127,111 @@ pub trait Repository {
         quiet: bool,
     ) -> DatabaseResult<Option<CallWithResponses>>;
 
+    /// Sets the limit of the latest call, or removes it if `limit` is `None`. Also returns the
+    /// responses that were promoted from the waitlist because the limit went up.
+    fn update_limit(
+        &self,
+        chat_id: ChatId,
+        limit: Option<i32>,
+    ) -> DatabaseResult<Option<(CallWithResponses, Vec<RollCallResponse>)>>;
+
+    /// Records the response of a user. An `In` is put on the waitlist if the call is full, and
+    /// switching away from `In` promotes the first response on the waitlist.
     fn set_response(
         &self,
         chat_id: ChatId,
-        user_id: UserId,
-        user_name: &str,
+        selector: &CallSelector,
+        user: &ChatUser,
         attendance: &Attendance,
-    ) -> DatabaseResult<Option<CallWithResponses>>;
+    ) -> DatabaseResult<Option<ResponseUpdate>>;
 
     fn set_response_for(
         &self,
         chat_id: ChatId,
+        selector: &CallSelector,
         user_name: &str,
         attendance: &Attendance,
+    ) -> DatabaseResult<Option<ResponseUpdate>>;
+
+    /// Records the responses for several names in one go, either all of them or none. The
+    /// updates are in the order of the names, the last one holding the final responses.
+    fn set_responses_for(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        responses: &[(String, Attendance)],
+    ) -> DatabaseResult<Option<Vec<ResponseUpdate>>>;
+
+    /// Deletes the response with the given token, as made by `NewRollCallResponse::self_token`
+    /// or `for_token`, promoting the waitlist if it freed up places.
+    fn delete_response(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
+        unique_token: &str,
+    ) -> DatabaseResult<Option<ResponseRemoval>>;
+
+    fn get_call_with_responses(
+        &self,
+        chat_id: ChatId,
+        selector: &CallSelector,
     ) -> DatabaseResult<Option<CallWithResponses>>;
 
-    fn get_call_with_responses(&self, chat_id: ChatId)
-        -> DatabaseResult<Option<CallWithResponses>>;
+    fn create_scheduled_call(
+        &self,
+        chat_id: ChatId,
+        title: &str,
+        start_at: NaiveDateTime,
+        end_at: Option<NaiveDateTime>,
+    ) -> DatabaseResult<ScheduledCall>;
+
+    /// Opens the scheduled calls whose start time has passed, the same way `create_call` does.
+    fn start_scheduled_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<RollCall>>;
+
+    /// Closes the started calls whose end time has passed. Calls that were already closed by hand
+    /// are not returned.
+    fn end_scheduled_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<CallWithResponses>>;
+
+    fn create_recurring_call(
+        &self,
+        chat_id: ChatId,
+        title: &str,
+        weekday: Weekday,
+        start_time: NaiveTime,
+    ) -> DatabaseResult<RecurringCall>;
+
+    fn get_recurring_calls(&self, chat_id: ChatId) -> DatabaseResult<Vec<RecurringCall>>;
+
+    /// Returns whether the recurring call existed in the chat.
+    fn delete_recurring_call(&self, chat_id: ChatId, id: RecurrenceId) -> DatabaseResult<bool>;
+
+    /// Opens a call for every recurring call that is due, the same way `create_call` does, and
+    /// moves each of them to its next occurrence after `now`.
+    fn start_recurring_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<RollCall>>;
 }
 
-pub struct PostgresRepository {
-    pool: Pool,
+/// A `Repository` backed by a diesel connection pool. See `impl_diesel_repository!` for the
+/// backends it is implemented for.
+pub struct DieselRepository<C: Connection + 'static> {
+    pool: Pool<C>,
 }
 
-impl PostgresRepository {
+pub type PostgresRepository = DieselRepository<PgConnection>;
+
+#[cfg(feature = "sqlite")]
+pub type SqliteRepository = DieselRepository<SqliteConnection>;
+
+impl<C: h::BackendConnection> DieselRepository<C> {
     pub fn new(database_url: &str, timeout: Duration) -> DatabaseResult<Self> {
         let pool = h::create_pool(database_url, timeout).map_err(DatabaseError::ConnectError)?;
 
-        let repository = PostgresRepository { pool };
+        let repository = DieselRepository { pool };
         Ok(repository)
     }
 
     fn exec_with_pool<T>(
         &self,
-        exec: impl Fn(&h::PooledConnection) -> QueryResult<T>,
+        exec: impl Fn(&h::PooledConnection<C>) -> QueryResult<T>,
     ) -> DatabaseResult<T> {
         let pool = self.pool.clone();
         let connection = pool.get().map_err(DatabaseError::ConnectError)?;
@@ -75,50 +239,426 @@ impl PostgresRepository {
     }
 }
 
-impl Repository for PostgresRepository {
-    fn create_call(&self, chat_id: ChatId, title: &str) -> DatabaseResult<RollCall> {
-        self.exec_with_pool(|conn| h::create_call(conn, chat_id, title))
+macro_rules! impl_diesel_repository {
+    ($conn:ty, $helpers:ident) => {
+        impl Repository for DieselRepository<$conn> {
+            fn create_call(&self, chat_id: ChatId, title: &str) -> DatabaseResult<RollCall> {
+                self.exec_with_pool(|conn| h::$helpers::create_call(conn, chat_id, title))
+            }
+
+            fn add_call(&self, chat_id: ChatId, title: &str) -> DatabaseResult<RollCall> {
+                self.exec_with_pool(|conn| h::$helpers::add_call(conn, chat_id, title))
+            }
+
+            fn restart_call(
+                &self,
+                chat_id: ChatId,
+                title: &str,
+                keep: bool,
+            ) -> DatabaseResult<Option<RestartedCall>> {
+                self.exec_with_pool(|conn| h::$helpers::restart_call(conn, chat_id, title, keep))
+            }
+
+            fn end_call(
+                &self,
+                chat_id: ChatId,
+                selector: &CallSelector,
+            ) -> DatabaseResult<Option<CallWithResponses>> {
+                self.exec_with_pool(|conn| h::$helpers::end_call(conn, chat_id, selector))
+            }
+
+            fn reopen_call(&self, chat_id: ChatId) -> DatabaseResult<Option<CallWithResponses>> {
+                self.exec_with_pool(|conn| h::$helpers::reopen_call(conn, chat_id))
+            }
+
+            fn delete_old_calls(
+                &self,
+                retention: &Retention,
+                now: NaiveDateTime,
+            ) -> DatabaseResult<PrunedCalls> {
+                self.exec_with_pool(|conn| h::$helpers::delete_old_calls(conn, retention, now))
+            }
+
+            fn update_title(
+                &self,
+                chat_id: ChatId,
+                selector: &CallSelector,
+                new_title: &str,
+            ) -> DatabaseResult<Option<CallWithResponses>> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::update_title(conn, chat_id, selector, new_title)
+                })
+            }
+
+            fn update_board(
+                &self,
+                chat_id: ChatId,
+                call_id: CallId,
+                message_id: Option<MessageId>,
+            ) -> DatabaseResult<bool> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::update_board(conn, chat_id, call_id, message_id)
+                })
+            }
+
+            fn update_pinned_message(
+                &self,
+                chat_id: ChatId,
+                call_id: CallId,
+                message_id: Option<MessageId>,
+            ) -> DatabaseResult<bool> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::update_pinned_message(conn, chat_id, call_id, message_id)
+                })
+            }
+
+            fn get_calls(
+                &self,
+                chat_id: ChatId,
+                status: CallStatus,
+            ) -> DatabaseResult<Vec<RollCall>> {
+                self.exec_with_pool(|conn| h::$helpers::get_calls(conn, chat_id, status))
+            }
+
+            fn get_calls_with_responses(
+                &self,
+                chat_id: ChatId,
+                status: CallStatus,
+            ) -> DatabaseResult<Vec<CallWithResponses>> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::get_calls_with_responses(conn, chat_id, status)
+                })
+            }
+
+            fn get_closed_call(
+                &self,
+                chat_id: ChatId,
+                call_id: CallId,
+            ) -> DatabaseResult<Option<CallWithResponses>> {
+                self.exec_with_pool(|conn| h::$helpers::get_closed_call(conn, chat_id, call_id))
+            }
+
+            fn get_settings(&self, chat_id: ChatId) -> DatabaseResult<ChatSettings> {
+                self.exec_with_pool(|conn| h::$helpers::get_settings(conn, chat_id))
+            }
+
+            fn update_settings(
+                &self,
+                chat_id: ChatId,
+                update: &UpdateChatSettings,
+            ) -> DatabaseResult<ChatSettings> {
+                self.exec_with_pool(|conn| h::$helpers::update_settings(conn, chat_id, update))
+            }
+
+            fn get_aliases(&self, chat_id: ChatId) -> DatabaseResult<Vec<ChatAlias>> {
+                self.exec_with_pool(|conn| h::$helpers::get_aliases(conn, chat_id))
+            }
+
+            fn add_alias(
+                &self,
+                chat_id: ChatId,
+                alias: &str,
+                command: &str,
+            ) -> DatabaseResult<ChatAlias> {
+                self.exec_with_pool(|conn| h::$helpers::add_alias(conn, chat_id, alias, command))
+            }
+
+            fn delete_alias(&self, chat_id: ChatId, alias: &str) -> DatabaseResult<bool> {
+                self.exec_with_pool(|conn| h::$helpers::delete_alias(conn, chat_id, alias))
+            }
+
+            fn update_quiet(
+                &self,
+                chat_id: ChatId,
+                quiet: bool,
+            ) -> DatabaseResult<Option<CallWithResponses>> {
+                self.exec_with_pool(|conn| h::$helpers::update_quiet(conn, chat_id, quiet))
+            }
+
+            fn update_limit(
+                &self,
+                chat_id: ChatId,
+                limit: Option<i32>,
+            ) -> DatabaseResult<Option<(CallWithResponses, Vec<RollCallResponse>)>> {
+                self.exec_with_pool(|conn| h::$helpers::update_limit(conn, chat_id, limit))
+            }
+
+            fn set_response(
+                &self,
+                chat_id: ChatId,
+                selector: &CallSelector,
+                user: &ChatUser,
+                attendance: &Attendance,
+            ) -> DatabaseResult<Option<ResponseUpdate>> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::set_response(conn, chat_id, selector, user, attendance)
+                })
+            }
+
+            fn set_response_for(
+                &self,
+                chat_id: ChatId,
+                selector: &CallSelector,
+                user_name: &str,
+                attendance: &Attendance,
+            ) -> DatabaseResult<Option<ResponseUpdate>> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::set_response_for(conn, chat_id, selector, user_name, attendance)
+                })
+            }
+
+            fn set_responses_for(
+                &self,
+                chat_id: ChatId,
+                selector: &CallSelector,
+                responses: &[(String, Attendance)],
+            ) -> DatabaseResult<Option<Vec<ResponseUpdate>>> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::set_responses_for(conn, chat_id, selector, responses)
+                })
+            }
+
+            fn delete_response(
+                &self,
+                chat_id: ChatId,
+                selector: &CallSelector,
+                unique_token: &str,
+            ) -> DatabaseResult<Option<ResponseRemoval>> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::delete_response(conn, chat_id, selector, unique_token)
+                })
+            }
+
+            fn get_call_with_responses(
+                &self,
+                chat_id: ChatId,
+                selector: &CallSelector,
+            ) -> DatabaseResult<Option<CallWithResponses>> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::get_call_with_responses(conn, chat_id, selector)
+                })
+            }
+
+            fn create_scheduled_call(
+                &self,
+                chat_id: ChatId,
+                title: &str,
+                start_at: NaiveDateTime,
+                end_at: Option<NaiveDateTime>,
+            ) -> DatabaseResult<ScheduledCall> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::create_scheduled_call(conn, chat_id, title, start_at, end_at)
+                })
+            }
+
+            fn start_scheduled_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<RollCall>> {
+                self.exec_with_pool(|conn| h::$helpers::start_scheduled_calls(conn, now))
+            }
+
+            fn end_scheduled_calls(
+                &self,
+                now: NaiveDateTime,
+            ) -> DatabaseResult<Vec<CallWithResponses>> {
+                self.exec_with_pool(|conn| h::$helpers::end_scheduled_calls(conn, now))
+            }
+
+            fn create_recurring_call(
+                &self,
+                chat_id: ChatId,
+                title: &str,
+                weekday: Weekday,
+                start_time: NaiveTime,
+            ) -> DatabaseResult<RecurringCall> {
+                self.exec_with_pool(|conn| {
+                    h::$helpers::create_recurring_call(conn, chat_id, title, weekday, start_time)
+                })
+            }
+
+            fn get_recurring_calls(&self, chat_id: ChatId) -> DatabaseResult<Vec<RecurringCall>> {
+                self.exec_with_pool(|conn| h::$helpers::get_recurring_calls(conn, chat_id))
+            }
+
+            fn delete_recurring_call(
+                &self,
+                chat_id: ChatId,
+                id: RecurrenceId,
+            ) -> DatabaseResult<bool> {
+                self.exec_with_pool(|conn| h::$helpers::delete_recurring_call(conn, chat_id, id))
+            }
+
+            fn start_recurring_calls(&self, now: NaiveDateTime) -> DatabaseResult<Vec<RollCall>> {
+                self.exec_with_pool(|conn| h::$helpers::start_recurring_calls(conn, now))
+            }
+        }
+    };
+}
+
+impl_diesel_repository!(PgConnection, pg);
+
+#[cfg(feature = "sqlite")]
+impl_diesel_repository!(SqliteConnection, sqlite);
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use chrono_tz::Tz;
+
+    use crate::db::migrate_sqlite;
+    use crate::models::AttendanceStatus::*;
+
+    use super::*;
+
+    const LATEST: &CallSelector = &CallSelector::Latest;
+
+    fn attendance(status: AttendanceStatus, reason: &str) -> Attendance {
+        Attendance::new(status, reason.to_string())
     }
 
-    fn end_call(&self, chat_id: ChatId) -> DatabaseResult<Option<RollCall>> {
-        self.exec_with_pool(|conn| h::end_call(conn, chat_id))
+    /// Creates a migrated database in a file, so that all the connections of the pool share it.
+    fn create_repo(name: &str) -> DatabaseResult<SqliteRepository> {
+        let path =
+            std::env::temp_dir().join(format!("whosinbot-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let database_url = path.to_str().unwrap();
+
+        migrate_sqlite(database_url).expect("migrations failed");
+        SqliteRepository::new(database_url, Duration::from_secs(10))
     }
 
-    fn update_title(&self, chat_id: ChatId, new_title: &str) -> DatabaseResult<Option<RollCall>> {
-        self.exec_with_pool(|conn| h::update_title(conn, chat_id, new_title))
+    #[test]
+    fn test_guests_that_do_not_fit_keep_the_responder_in() -> DatabaseResult<()> {
+        let repo = create_repo("guests")?;
+        repo.create_call(1, "title")?;
+        repo.update_limit(1, Some(2))?;
+
+        let user = ChatUser::new(2, "Peter");
+        repo.set_response(1, LATEST, &user, &attendance(In, ""))?;
+        repo.set_response_for(1, LATEST, "Albert", &attendance(In, ""))?;
+
+        let update = repo
+            .set_response(1, LATEST, &user, &attendance(In, "").with_guests(1))?
+            .unwrap();
+        assert_eq!(In, update.status);
+        assert_eq!(1, update.refused_guests);
+
+        let (_, responses) = update.call_with_responses;
+        assert!(responses.iter().all(|r| r.status == In && r.guests == 0));
+
+        Ok(())
     }
 
-    fn update_quiet(
-        &self,
-        chat_id: ChatId,
-        quiet: bool,
-    ) -> DatabaseResult<Option<CallWithResponses>> {
-        self.exec_with_pool(|conn| h::update_quiet(conn, chat_id, quiet))
+    #[test]
+    fn test_restart_call_closes_the_previous_call() -> DatabaseResult<()> {
+        let repo = create_repo("restart")?;
+        let first = repo.create_call(1, "football")?;
+        repo.set_response_for(1, LATEST, "Peter", &attendance(In, ""))?;
+
+        let restarted = repo.restart_call(1, "", true)?.unwrap();
+        let (previous_call, previous_responses) = restarted.previous_call;
+        assert_eq!(first.id, previous_call.id);
+        assert_eq!(CallStatus::Closed, previous_call.status);
+        assert_eq!(1, previous_responses.len());
+
+        let (call, responses) = restarted.call_with_responses;
+        assert_eq!("football", call.title);
+        assert_eq!(Maybe, responses[0].status);
+
+        Ok(())
     }
 
-    fn set_response(
-        &self,
-        chat_id: ChatId,
-        user_id: UserId,
-        user_name: &str,
-        attendance: &Attendance,
-    ) -> DatabaseResult<Option<CallWithResponses>> {
-        self.exec_with_pool(|conn| h::set_response(conn, chat_id, user_id, user_name, attendance))
+    #[test]
+    fn test_delete_old_calls_by_number_and_age() -> DatabaseResult<()> {
+        let repo = create_repo("retention")?;
+        let open = repo.create_call(1, "open")?;
+        for title in &["first", "second", "third"] {
+            let call = repo.add_call(1, title)?;
+            repo.set_response_for(1, &CallSelector::Id(call.id), "Peter", &attendance(In, ""))?;
+            repo.end_call(1, &CallSelector::Id(call.id))?;
+        }
+        for chat_id in 2..4 {
+            let call = repo.create_call(chat_id, "closed")?;
+            repo.end_call(chat_id, &CallSelector::Id(call.id))?;
+        }
+
+        let policy = RetentionPolicy {
+            max_calls: Some(2),
+            max_age: None,
+        };
+        let retention = Retention {
+            default: policy,
+            chats: hashmap! {
+                2 => RetentionPolicy { max_calls: None, max_age: Some(chrono::Duration::days(1)) },
+            },
+        };
+
+        let now = chrono::Utc::now().naive_local();
+        let pruned = repo.delete_old_calls(&retention, now)?;
+        assert_eq!(
+            PrunedCalls {
+                calls: 1,
+                responses: 1
+            },
+            pruned
+        );
+        let titles: Vec<String> = repo
+            .get_calls(1, CallStatus::Closed)?
+            .into_iter()
+            .map(|call| call.title)
+            .collect();
+        assert_eq!(vec!["third", "second"], titles);
+        assert_eq!(open.id, repo.get_calls(1, CallStatus::Open)?[0].id);
+
+        // only the chat with a maximum age loses its call
+        let now = now + chrono::Duration::days(2);
+        assert_eq!(1, repo.delete_old_calls(&retention, now)?.calls);
+        assert!(repo.get_calls(2, CallStatus::Closed)?.is_empty());
+        assert_eq!(1, repo.get_calls(3, CallStatus::Closed)?.len());
+
+        Ok(())
     }
 
-    fn set_response_for(
-        &self,
-        chat_id: ChatId,
-        user_name: &str,
-        attendance: &Attendance,
-    ) -> DatabaseResult<Option<CallWithResponses>> {
-        self.exec_with_pool(|conn| h::set_response_for(conn, chat_id, user_name, attendance))
+    #[test]
+    fn test_recurring_call_takes_the_timezone_of_the_chat() -> DatabaseResult<()> {
+        let repo = create_repo("recurring")?;
+        let update = UpdateChatSettings::new().with_timezone(Tz::Europe__Berlin);
+        repo.update_settings(1, &update)?;
+
+        let start_time = NaiveTime::from_hms(18, 0, 0);
+        repo.create_recurring_call(1, "football", Weekday::Tue, start_time)?;
+
+        let recurring = &repo.get_recurring_calls(1)?[0];
+        assert_eq!("Europe/Berlin", recurring.timezone);
+        assert_eq!(start_time, recurring.start_time);
+        let local_next_at = utc_to_local(Tz::Europe__Berlin, recurring.next_at);
+        assert_eq!(start_time, local_next_at.time());
+
+        Ok(())
     }
 
-    fn get_call_with_responses(
-        &self,
-        chat_id: ChatId,
-    ) -> DatabaseResult<Option<CallWithResponses>> {
-        self.exec_with_pool(|conn| h::get_call_with_responses(conn, chat_id))
+    #[test]
+    fn test_concurrent_responses_of_the_same_user() -> DatabaseResult<()> {
+        let repo = Arc::new(create_repo("concurrent")?);
+        repo.create_call(1, "title")?;
+
+        let threads = (0..8)
+            .map(|i| {
+                let repo = repo.clone();
+                thread::spawn(move || {
+                    let user = ChatUser::new(2, "Peter");
+                    repo.set_response(1, LATEST, &user, &attendance(In, &i.to_string()))
+                        .map(|update| update.is_some())
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert!(thread.join().unwrap()?);
+        }
+
+        let (_, responses) = repo.get_call_with_responses(1, LATEST)?.unwrap();
+        assert_eq!(1, responses.len());
+
+        Ok(())
     }
 }
@@ -10,6 +10,7 @@ extern crate diesel_migrations;
 #[macro_use]
 extern crate failure;
 extern crate futures;
+extern crate hyper;
 extern crate itertools;
 #[macro_use]
 extern crate lazy_static;
@@ -23,6 +24,7 @@ extern crate mockers;
 extern crate mockers_derive;
 extern crate regex;
 extern crate serde;
+extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
@@ -30,25 +32,72 @@ extern crate slog;
 #[macro_use]
 extern crate slog_scope;
 extern crate telegram_bot;
+extern crate telegram_bot_raw;
 extern crate tokio_core;
 
+pub mod bot;
 pub mod db;
 pub mod models;
+pub mod repl;
 pub mod schema;
 pub mod settings;
 pub mod telegram;
 pub mod util;
 
+use settings::DatabaseBackend;
+
+#[cfg(not(feature = "sqlite"))]
+const SQLITE_DISABLED: &str = "SQLite support requires building with the `sqlite` feature";
+
 pub fn run_whosin_bot(settings: &settings::Settings) -> Result<(), failure::Error> {
-    let timeout = std::time::Duration::from_millis(settings.database.timeout_ms);
-    let repository = db::PostgresRepository::new(&settings.database.url, timeout)?;
-    let bot = telegram::WhosInBot::new(&settings.telegram.token, Box::new(repository));
+    let repository = create_repository(&settings.database)?;
+    let transport = telegram::TelegramTransport::new(&settings.telegram);
+    let bot = bot::WhosInBot::new(transport, repository)
+        .with_retention(settings.retention.policies(), settings.retention.interval());
 
     bot.run()?;
     Ok(())
 }
 
+pub fn create_repository(
+    settings: &settings::Database,
+) -> Result<Box<dyn db::Repository>, failure::Error> {
+    match settings.backend {
+        DatabaseBackend::Postgres => {
+            let timeout = std::time::Duration::from_millis(settings.timeout_ms);
+            let repository = db::PostgresRepository::new(&settings.url, timeout)?;
+            Ok(Box::new(repository))
+        }
+
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => {
+            let timeout = std::time::Duration::from_millis(settings.timeout_ms);
+            let repository = db::SqliteRepository::new(&settings.url, timeout)?;
+            Ok(Box::new(repository))
+        }
+
+        #[cfg(not(feature = "sqlite"))]
+        DatabaseBackend::Sqlite => bail!(SQLITE_DISABLED),
+
+        DatabaseBackend::Memory => {
+            warn!("Using the in-memory database, all data will be lost on exit.");
+            Ok(Box::new(db::InMemoryRepository::new()))
+        }
+    }
+}
+
 pub fn migrate_db(settings: &settings::Settings) -> Result<(), failure::Error> {
-    db::migrate(&settings.database.url)?;
+    match settings.database.backend {
+        DatabaseBackend::Postgres => db::migrate_postgres(&settings.database.url)?,
+
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => db::migrate_sqlite(&settings.database.url)?,
+
+        #[cfg(not(feature = "sqlite"))]
+        DatabaseBackend::Sqlite => bail!(SQLITE_DISABLED),
+
+        DatabaseBackend::Memory => info!("The in-memory database does not need migrations."),
+    }
+
     Ok(())
 }
@@ -0,0 +1,34 @@
+use chrono::{NaiveDateTime, Utc};
+
+use crate::schema::w_chat_aliases;
+
+use super::type_aliases::*;
+
+/// A name a chat gave to a command, e.g. `/yes` for `/in`. Both include the slash.
+#[derive(Queryable, Debug, Clone, PartialEq, Eq)]
+pub struct ChatAlias {
+    pub chat_id: ChatId,
+    pub alias: String,
+    pub command: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "w_chat_aliases"]
+pub struct NewChatAlias<'a> {
+    pub chat_id: ChatId,
+    pub alias: &'a str,
+    pub command: &'a str,
+    pub created_at: NaiveDateTime,
+}
+
+impl<'a> NewChatAlias<'a> {
+    pub fn new(chat_id: ChatId, alias: &'a str, command: &'a str) -> NewChatAlias<'a> {
+        NewChatAlias {
+            chat_id,
+            alias,
+            command,
+            created_at: Utc::now().naive_local(),
+        }
+    }
+}
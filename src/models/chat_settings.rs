@@ -0,0 +1,275 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::{Duration, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+
+use crate::schema::w_chat_settings;
+
+use super::type_aliases::*;
+
+/// The preferences of a chat. Chats that never changed them have no row, and get the defaults of
+/// `ChatSettings::default_for`.
+#[derive(Identifiable, Queryable, Debug, Clone, PartialEq, Eq)]
+#[table_name = "w_chat_settings"]
+#[primary_key(chat_id)]
+pub struct ChatSettings {
+    pub chat_id: ChatId,
+    pub pin_calls: bool,
+    /// Whether to answer unknown commands that do not name this bot, which may well be meant for
+    /// another bot in the chat.
+    pub answer_unknown_commands: bool,
+    /// Whether new calls start quiet, see `RollCall::quiet`.
+    pub quiet: bool,
+    /// One of `LANGUAGES`.
+    pub language: String,
+    /// The IANA name of the timezone of the dates and times of the chat, see `parse_timezone`.
+    pub timezone: String,
+    pub reply_style: ReplyStyle,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl ChatSettings {
+    pub fn default_for(chat_id: ChatId) -> ChatSettings {
+        let new_settings = NewChatSettings::new(chat_id);
+        ChatSettings {
+            chat_id,
+            pin_calls: new_settings.pin_calls,
+            answer_unknown_commands: new_settings.answer_unknown_commands,
+            quiet: new_settings.quiet,
+            language: new_settings.language,
+            timezone: new_settings.timezone,
+            reply_style: new_settings.reply_style,
+            created_at: new_settings.created_at,
+            updated_at: new_settings.updated_at,
+        }
+    }
+
+    /// The timezone of the chat, or UTC if its name is not known.
+    pub fn tz(&self) -> Tz {
+        parse_timezone(&self.timezone).unwrap_or(Tz::UTC)
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "w_chat_settings"]
+pub struct NewChatSettings {
+    pub chat_id: ChatId,
+    pub pin_calls: bool,
+    pub answer_unknown_commands: bool,
+    pub quiet: bool,
+    pub language: String,
+    pub timezone: String,
+    pub reply_style: ReplyStyle,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl NewChatSettings {
+    pub fn new(chat_id: ChatId) -> NewChatSettings {
+        let now = Utc::now().naive_local();
+        NewChatSettings {
+            chat_id,
+            pin_calls: false,
+            answer_unknown_commands: false,
+            quiet: false,
+            language: LANGUAGES[0].to_owned(),
+            timezone: Tz::UTC.name().to_owned(),
+            reply_style: ReplyStyle::List,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(AsChangeset, Debug, PartialEq, Eq)]
+#[table_name = "w_chat_settings"]
+pub struct UpdateChatSettings {
+    pub pin_calls: Option<bool>,
+    pub answer_unknown_commands: Option<bool>,
+    pub quiet: Option<bool>,
+    pub language: Option<String>,
+    pub timezone: Option<String>,
+    pub reply_style: Option<ReplyStyle>,
+    pub updated_at: NaiveDateTime,
+}
+
+impl UpdateChatSettings {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> UpdateChatSettings {
+        UpdateChatSettings {
+            pin_calls: None,
+            answer_unknown_commands: None,
+            quiet: None,
+            language: None,
+            timezone: None,
+            reply_style: None,
+            updated_at: Utc::now().naive_local(),
+        }
+    }
+
+    pub fn with_pin_calls(self, pin_calls: bool) -> UpdateChatSettings {
+        UpdateChatSettings {
+            pin_calls: Some(pin_calls),
+            ..self
+        }
+    }
+
+    pub fn with_answer_unknown_commands(self, answer: bool) -> UpdateChatSettings {
+        UpdateChatSettings {
+            answer_unknown_commands: Some(answer),
+            ..self
+        }
+    }
+
+    pub fn with_quiet(self, quiet: bool) -> UpdateChatSettings {
+        UpdateChatSettings {
+            quiet: Some(quiet),
+            ..self
+        }
+    }
+
+    pub fn with_language(self, language: &str) -> UpdateChatSettings {
+        UpdateChatSettings {
+            language: Some(language.to_owned()),
+            ..self
+        }
+    }
+
+    pub fn with_timezone(self, timezone: Tz) -> UpdateChatSettings {
+        UpdateChatSettings {
+            timezone: Some(timezone.name().to_owned()),
+            ..self
+        }
+    }
+
+    pub fn with_reply_style(self, reply_style: ReplyStyle) -> UpdateChatSettings {
+        UpdateChatSettings {
+            reply_style: Some(reply_style),
+            ..self
+        }
+    }
+}
+
+/// The languages the bot can reply in, the first one being the default.
+pub const LANGUAGES: [&str; 3] = ["en", "de", "vi"];
+
+/// Parses the IANA name of a timezone, such as `UTC` or `Europe/Berlin`.
+pub fn parse_timezone(text: &str) -> Option<Tz> {
+    text.trim().parse().ok()
+}
+
+/// The time in UTC, in which times are stored, of a local time in a timezone. A time that is
+/// skipped when the clocks go forward is taken to be an hour later, and a time that comes twice
+/// when they go back is taken the first time.
+pub fn local_to_utc(tz: Tz, local: NaiveDateTime) -> NaiveDateTime {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => at.naive_utc(),
+        LocalResult::None => local_to_utc(tz, local + Duration::hours(1)),
+    }
+}
+
+/// The local time in a timezone of a time in UTC.
+pub fn utc_to_local(tz: Tz, at: NaiveDateTime) -> NaiveDateTime {
+    tz.from_utc_datetime(&at).naive_local()
+}
+
+/// How responses are shown: by listing everyone after every response, or by keeping the board of
+/// the call up to date, which new calls then get right away.
+#[derive(AsExpression, FromSqlRow, Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[sql_type = "Text"]
+pub enum ReplyStyle {
+    List,
+    Board,
+}
+
+impl fmt::Display for ReplyStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                ReplyStyle::List => "LIST",
+                ReplyStyle::Board => "BOARD",
+            }
+        )
+    }
+}
+
+impl FromStr for ReplyStyle {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LIST" => Ok(ReplyStyle::List),
+            "BOARD" => Ok(ReplyStyle::Board),
+            x => Err(format!("Unrecognized variant {}", x)),
+        }
+    }
+}
+
+impl<DB> ToSql<Text, DB> for ReplyStyle
+where
+    DB: Backend,
+    String: ToSql<Text, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        self.to_string().to_sql(out)
+    }
+}
+
+impl<DB> FromSql<Text, DB> for ReplyStyle
+where
+    DB: Backend,
+    String: FromSql<Text, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        String::from_sql(bytes)?
+            .parse::<ReplyStyle>()
+            .map_err(|e| e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(Some(Tz::UTC), parse_timezone("UTC"));
+        assert_eq!(Some(Tz::Europe__Berlin), parse_timezone(" Europe/Berlin "));
+        assert_eq!(None, parse_timezone(""));
+        assert_eq!(None, parse_timezone("UTC+2"));
+        assert_eq!(None, parse_timezone("Europe/Nowhere"));
+    }
+
+    #[test]
+    fn test_local_to_utc_follows_daylight_saving_time() {
+        let at =
+            |month, day, hour, min| NaiveDate::from_ymd(2019, month, day).and_hms(hour, min, 0);
+        let berlin = Tz::Europe__Berlin;
+
+        assert_eq!(at(3, 26, 17, 0), local_to_utc(berlin, at(3, 26, 18, 0)));
+        assert_eq!(at(4, 2, 16, 0), local_to_utc(berlin, at(4, 2, 18, 0)));
+        assert_eq!(at(4, 2, 18, 0), utc_to_local(berlin, at(4, 2, 16, 0)));
+
+        // skipped when the clocks go forward, and twice when they go back
+        assert_eq!(at(3, 31, 1, 30), local_to_utc(berlin, at(3, 31, 2, 30)));
+        assert_eq!(at(10, 27, 0, 30), local_to_utc(berlin, at(10, 27, 2, 30)));
+    }
+
+    #[test]
+    fn test_reply_style_to_and_from_string() -> Result<(), String> {
+        assert_eq!("BOARD", ReplyStyle::Board.to_string());
+        assert_eq!(ReplyStyle::List, "LIST".parse::<ReplyStyle>()?);
+        assert!("INVALID".parse::<ReplyStyle>().is_err());
+        Ok(())
+    }
+}
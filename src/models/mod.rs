@@ -1,7 +1,17 @@
+mod chat_aliases;
+mod chat_settings;
+mod recurring_calls;
+mod retention;
 mod roll_call_responses;
 mod roll_calls;
+mod scheduled_calls;
 mod type_aliases;
 
+pub use chat_aliases::*;
+pub use chat_settings::*;
+pub use recurring_calls::*;
+pub use retention::*;
 pub use roll_call_responses::*;
 pub use roll_calls::*;
+pub use scheduled_calls::*;
 pub use type_aliases::*;
@@ -0,0 +1,221 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::schema::w_recurring_calls;
+
+use super::chat_settings::{local_to_utc, parse_timezone, utc_to_local, ChatSettings};
+use super::type_aliases::*;
+
+/// A rule that opens a roll call in the chat every week, on `weekday` at `start_time` in the
+/// timezone of the chat when it was added. `next_at` is in UTC like all stored times, and moves
+/// with daylight saving time.
+#[derive(Identifiable, Queryable, Debug, Clone)]
+#[table_name = "w_recurring_calls"]
+pub struct RecurringCall {
+    pub id: RecurrenceId,
+    pub chat_id: ChatId,
+    pub title: String,
+    pub weekday: i32,
+    pub start_time: NaiveTime,
+    pub next_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// The IANA name of the timezone of `weekday` and `start_time`.
+    pub timezone: String,
+}
+
+impl RecurringCall {
+    pub fn weekday(&self) -> Weekday {
+        weekday_from_i32(self.weekday)
+    }
+
+    pub fn tz(&self) -> Tz {
+        parse_timezone(&self.timezone).unwrap_or(Tz::UTC)
+    }
+
+    /// The first occurrence of the rule after `after`, skipping any that were missed. Both are in
+    /// UTC.
+    pub fn next_occurrence(&self, after: NaiveDateTime) -> NaiveDateTime {
+        next_occurrence(self.weekday(), self.start_time, self.tz(), after)
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "w_recurring_calls"]
+pub struct NewRecurringCall<'a> {
+    pub chat_id: ChatId,
+    pub title: &'a str,
+    pub weekday: i32,
+    pub start_time: NaiveTime,
+    pub next_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub timezone: &'a str,
+}
+
+impl<'a> NewRecurringCall<'a> {
+    /// A rule for the chat of `settings`, in the timezone of the chat.
+    pub fn new(
+        settings: &ChatSettings,
+        title: &'a str,
+        weekday: Weekday,
+        start_time: NaiveTime,
+    ) -> NewRecurringCall<'a> {
+        let now = Utc::now().naive_local();
+        let timezone = settings.tz();
+        NewRecurringCall {
+            chat_id: settings.chat_id,
+            title,
+            weekday: weekday.num_days_from_monday() as i32,
+            start_time,
+            next_at: next_occurrence(weekday, start_time, timezone, now),
+            created_at: now,
+            updated_at: now,
+            timezone: timezone.name(),
+        }
+    }
+}
+
+#[derive(AsChangeset, Debug)]
+#[table_name = "w_recurring_calls"]
+pub struct UpdateRecurringCall {
+    pub next_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl UpdateRecurringCall {
+    pub fn new(next_at: NaiveDateTime) -> UpdateRecurringCall {
+        UpdateRecurringCall {
+            next_at,
+            updated_at: Utc::now().naive_local(),
+        }
+    }
+}
+
+/// The weekday stored as its `num_days_from_monday`, from 0 for Monday to 6 for Sunday.
+fn weekday_from_i32(days_from_monday: i32) -> Weekday {
+    match days_from_monday {
+        0 => Weekday::Mon,
+        1 => Weekday::Tue,
+        2 => Weekday::Wed,
+        3 => Weekday::Thu,
+        4 => Weekday::Fri,
+        5 => Weekday::Sat,
+        _ => Weekday::Sun,
+    }
+}
+
+/// The first time in UTC after `after` that it is `weekday` at `time` in a timezone, which is an
+/// hour apart in summer and in winter where the clocks change.
+fn next_occurrence(
+    weekday: Weekday,
+    time: NaiveTime,
+    tz: Tz,
+    after: NaiveDateTime,
+) -> NaiveDateTime {
+    let mut local_after = utc_to_local(tz, after);
+    loop {
+        let local = next_local_occurrence(weekday, time, local_after);
+        let at = local_to_utc(tz, local);
+        // a time that is skipped or comes twice may map back to before `after`
+        if at > after {
+            return at;
+        }
+        local_after = local;
+    }
+}
+
+fn next_local_occurrence(weekday: Weekday, time: NaiveTime, after: NaiveDateTime) -> NaiveDateTime {
+    let days_ahead =
+        (7 + weekday.num_days_from_monday() - after.weekday().num_days_from_monday()) % 7;
+    let candidate = (after.date() + Duration::days(i64::from(days_ahead))).and_time(time);
+
+    if candidate > after {
+        candidate
+    } else {
+        candidate + Duration::weeks(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn at(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        // 2019-04-01 was a Monday
+        NaiveDate::from_ymd(2019, 4, day).and_hms(hour, min, 0)
+    }
+
+    #[test]
+    fn test_weekday_from_i32() {
+        assert_eq!(Weekday::Mon, weekday_from_i32(0));
+        assert_eq!(Weekday::Tue, weekday_from_i32(1));
+        assert_eq!(Weekday::Sat, weekday_from_i32(5));
+        assert_eq!(Weekday::Sun, weekday_from_i32(6));
+    }
+
+    fn next_occurrence_utc(
+        weekday: Weekday,
+        time: NaiveTime,
+        after: NaiveDateTime,
+    ) -> NaiveDateTime {
+        next_occurrence(weekday, time, Tz::UTC, after)
+    }
+
+    #[test]
+    fn test_next_occurrence_later_in_the_week() {
+        let time = NaiveTime::from_hms(18, 0, 0);
+        assert_eq!(
+            at(2, 18, 0),
+            next_occurrence_utc(Weekday::Tue, time, at(1, 9, 0))
+        );
+        assert_eq!(
+            at(7, 18, 0),
+            next_occurrence_utc(Weekday::Sun, time, at(1, 9, 0))
+        );
+    }
+
+    #[test]
+    fn test_next_occurrence_on_the_same_day() {
+        let time = NaiveTime::from_hms(18, 0, 0);
+        assert_eq!(
+            at(2, 18, 0),
+            next_occurrence_utc(Weekday::Tue, time, at(2, 17, 59))
+        );
+        assert_eq!(
+            at(9, 18, 0),
+            next_occurrence_utc(Weekday::Tue, time, at(2, 18, 0))
+        );
+    }
+
+    #[test]
+    fn test_next_occurrence_next_week() {
+        let time = NaiveTime::from_hms(18, 0, 0);
+        assert_eq!(
+            at(8, 18, 0),
+            next_occurrence_utc(Weekday::Mon, time, at(3, 12, 0))
+        );
+    }
+
+    #[test]
+    fn test_next_occurrence_keeps_local_time_across_daylight_saving_time() {
+        let time = NaiveTime::from_hms(18, 0, 0);
+        let berlin = Tz::Europe__Berlin;
+        let at = |month, day, hour| NaiveDate::from_ymd(2019, month, day).and_hms(hour, 0, 0);
+
+        assert_eq!(
+            at(3, 26, 17),
+            next_occurrence(Weekday::Tue, time, berlin, at(3, 25, 9))
+        );
+        assert_eq!(
+            at(4, 2, 16),
+            next_occurrence(Weekday::Tue, time, berlin, at(3, 26, 17))
+        );
+        assert_eq!(
+            at(10, 29, 17),
+            next_occurrence(Weekday::Tue, time, berlin, at(10, 22, 16))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+
+use super::type_aliases::*;
+
+/// How many calls of a chat are kept and for how long. Open calls are always kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// The number of latest calls to keep, or `None` to keep any number.
+    pub max_calls: Option<usize>,
+    /// How long to keep calls after they were created, or `None` to keep them forever.
+    pub max_age: Option<Duration>,
+}
+
+impl Default for RetentionPolicy {
+    /// Keeps the 10 latest calls of every chat.
+    fn default() -> Self {
+        RetentionPolicy {
+            max_calls: Some(10),
+            max_age: None,
+        }
+    }
+}
+
+/// The retention policy of all chats, and of the chats that have their own.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Retention {
+    pub default: RetentionPolicy,
+    pub chats: HashMap<ChatId, RetentionPolicy>,
+}
+
+impl Retention {
+    pub fn for_chat(&self, chat_id: ChatId) -> &RetentionPolicy {
+        self.chats.get(&chat_id).unwrap_or(&self.default)
+    }
+}
+
+/// The number of rows deleted by pruning old calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrunedCalls {
+    pub calls: usize,
+    pub responses: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_chat_falls_back_to_default() {
+        let own_policy = RetentionPolicy {
+            max_calls: None,
+            max_age: Some(Duration::days(7)),
+        };
+        let retention = Retention {
+            chats: hashmap! { 2 => own_policy },
+            ..Default::default()
+        };
+
+        assert_eq!(&RetentionPolicy::default(), retention.for_chat(1));
+        assert_eq!(&own_policy, retention.for_chat(2));
+    }
+}
@@ -11,11 +11,21 @@ use diesel::sql_types::Text;
 pub struct Attendance {
     pub status: AttendanceStatus,
     pub reason: String,
+    pub guests: i32,
 }
 
 impl Attendance {
     pub fn new(status: AttendanceStatus, reason: String) -> Attendance {
-        Attendance { status, reason }
+        Attendance {
+            status,
+            reason,
+            guests: 0,
+        }
+    }
+
+    pub fn with_guests(mut self, guests: i32) -> Attendance {
+        self.guests = guests;
+        self
     }
 }
 
@@ -26,6 +36,7 @@ pub enum AttendanceStatus {
     In,
     Out,
     Maybe,
+    Waitlist,
 }
 
 impl fmt::Display for AttendanceStatus {
@@ -37,6 +48,7 @@ impl fmt::Display for AttendanceStatus {
                 AttendanceStatus::In => "IN",
                 AttendanceStatus::Out => "OUT",
                 AttendanceStatus::Maybe => "MAYBE",
+                AttendanceStatus::Waitlist => "WAITLIST",
             }
         )
     }
@@ -49,6 +61,7 @@ impl FromStr for AttendanceStatus {
             "IN" => Ok(AttendanceStatus::In),
             "OUT" => Ok(AttendanceStatus::Out),
             "MAYBE" => Ok(AttendanceStatus::Maybe),
+            "WAITLIST" => Ok(AttendanceStatus::Waitlist),
             x => Err(format!("Unrecognized variant {}", x)),
         }
     }
@@ -85,6 +98,7 @@ mod tests {
         assert_eq!("IN", AttendanceStatus::In.to_string());
         assert_eq!("OUT", AttendanceStatus::Out.to_string());
         assert_eq!("MAYBE", AttendanceStatus::Maybe.to_string());
+        assert_eq!("WAITLIST", AttendanceStatus::Waitlist.to_string());
     }
 
     #[test]
@@ -95,6 +109,10 @@ mod tests {
             AttendanceStatus::Maybe,
             "MAYBE".parse::<AttendanceStatus>()?
         );
+        assert_eq!(
+            AttendanceStatus::Waitlist,
+            "WAITLIST".parse::<AttendanceStatus>()?
+        );
         Ok(())
     }
 
@@ -20,6 +20,51 @@ pub struct RollCallResponse {
     pub reason: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub guests: i32,
+}
+
+impl RollCallResponse {
+    /// The number of places the response takes, counting the responder and their guests.
+    pub fn party_size(&self) -> i64 {
+        1 + i64::from(self.guests)
+    }
+}
+
+/// A user of the chat who responds for themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatUser {
+    pub id: UserId,
+    pub name: String,
+}
+
+impl ChatUser {
+    pub fn new(id: UserId, name: &str) -> ChatUser {
+        ChatUser {
+            id,
+            name: name.to_owned(),
+        }
+    }
+}
+
+/// The outcome of recording a response. `status` is the status the response was recorded with,
+/// which is `Waitlist` when an `In` did not fit within the call's limit, and `promoted` has the
+/// responses that moved from the waitlist to `In` as a result. Someone who is already in keeps
+/// their place when the guests they add do not fit, and `refused_guests` counts those guests.
+#[derive(Debug, Clone)]
+pub struct ResponseUpdate {
+    pub call_with_responses: CallWithResponses,
+    pub status: AttendanceStatus,
+    pub promoted: Vec<RollCallResponse>,
+    pub refused_guests: i32,
+}
+
+/// The outcome of deleting a response. `removed` tells whether there was a response to delete,
+/// and `promoted` has the responses that moved from the waitlist to `In` as a result.
+#[derive(Debug, Clone)]
+pub struct ResponseRemoval {
+    pub call_with_responses: CallWithResponses,
+    pub removed: bool,
+    pub promoted: Vec<RollCallResponse>,
 }
 
 #[derive(Insertable, Debug)]
@@ -33,6 +78,7 @@ pub struct NewRollCallResponse<'a> {
     pub reason: &'a str,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub guests: i32,
 }
 
 impl<'a> NewRollCallResponse<'a> {
@@ -44,7 +90,7 @@ impl<'a> NewRollCallResponse<'a> {
     ) -> NewRollCallResponse<'a> {
         assert!(user_id > 0);
 
-        let unique_token = format!("self:{}", Self::hash(&user_id.to_string()));
+        let unique_token = Self::self_token(user_id);
         let now = Utc::now().naive_local();
 
         NewRollCallResponse {
@@ -56,6 +102,7 @@ impl<'a> NewRollCallResponse<'a> {
             reason: &attendance.reason,
             created_at: now,
             updated_at: now,
+            guests: attendance.guests,
         }
     }
 
@@ -66,7 +113,7 @@ impl<'a> NewRollCallResponse<'a> {
     ) -> NewRollCallResponse<'a> {
         assert!(!user_name.is_empty());
 
-        let unique_token = format!("for:{}", Self::hash(&user_name.to_lowercase()));
+        let unique_token = Self::for_token(user_name);
         let now = Utc::now().naive_local();
 
         NewRollCallResponse {
@@ -78,9 +125,45 @@ impl<'a> NewRollCallResponse<'a> {
             reason: &attendance.reason,
             created_at: now,
             updated_at: now,
+            guests: attendance.guests,
+        }
+    }
+
+    /// Carries a response over to a new call as a `Maybe`, without its reason and guests, for the
+    /// same user or name.
+    pub fn carried_over(
+        call_id: CallId,
+        response: &'a RollCallResponse,
+    ) -> NewRollCallResponse<'a> {
+        let now = Utc::now().naive_local();
+
+        NewRollCallResponse {
+            roll_call_id: call_id,
+            unique_token: response.unique_token.clone(),
+            user_id: response.user_id,
+            user_name: response.user_name.as_ref().map_or("", |name| name.as_str()),
+            status: AttendanceStatus::Maybe,
+            reason: "",
+            created_at: now,
+            updated_at: now,
+            guests: 0,
         }
     }
 
+    pub fn party_size(&self) -> i64 {
+        1 + i64::from(self.guests)
+    }
+
+    /// The token of the response of a user for themselves.
+    pub fn self_token(user_id: UserId) -> String {
+        format!("self:{}", Self::hash(&user_id.to_string()))
+    }
+
+    /// The token of a response given for someone else, which ignores the case of their name.
+    pub fn for_token(user_name: &str) -> String {
+        format!("for:{}", Self::hash(&user_name.to_lowercase()))
+    }
+
     fn hash(input: &str) -> String {
         let mut hasher = Sha256::new();
         hasher.input_str(input);
@@ -95,6 +178,7 @@ pub struct UpdateRollCallResponse<'a> {
     pub status: Option<AttendanceStatus>,
     pub reason: Option<&'a str>,
     pub updated_at: Option<NaiveDateTime>,
+    pub guests: Option<i32>,
 }
 
 impl<'a> UpdateRollCallResponse<'a> {
@@ -102,6 +186,7 @@ impl<'a> UpdateRollCallResponse<'a> {
         user_name: &'a str,
         status: AttendanceStatus,
         reason: &'a str,
+        guests: i32,
     ) -> UpdateRollCallResponse<'a> {
         let now = Utc::now().naive_local();
         UpdateRollCallResponse {
@@ -109,6 +194,18 @@ impl<'a> UpdateRollCallResponse<'a> {
             status: Some(status),
             reason: Some(reason),
             updated_at: Some(now),
+            guests: Some(guests),
+        }
+    }
+
+    pub fn new_status(status: AttendanceStatus) -> UpdateRollCallResponse<'static> {
+        let now = Utc::now().naive_local();
+        UpdateRollCallResponse {
+            user_name: None,
+            status: Some(status),
+            reason: None,
+            updated_at: Some(now),
+            guests: None,
         }
     }
 }
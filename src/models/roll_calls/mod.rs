@@ -1,14 +1,17 @@
 use chrono::{NaiveDateTime, Utc};
 
+pub use selector::CallSelector;
 pub use status::CallStatus;
 
 use crate::schema::w_roll_calls;
 
+use super::chat_settings::ChatSettings;
 use super::type_aliases::*;
 
+mod selector;
 mod status;
 
-#[derive(Identifiable, Queryable, Debug)]
+#[derive(Identifiable, Queryable, Debug, Clone)]
 #[table_name = "w_roll_calls"]
 pub struct RollCall {
     pub id: CallId,
@@ -18,6 +21,34 @@ pub struct RollCall {
     pub quiet: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub attendance_limit: Option<i32>,
+    pub board_message_id: Option<MessageId>,
+    pub pinned_message_id: Option<MessageId>,
+}
+
+impl RollCall {
+    /// The number of places left given the number of people who are in, guests included, or
+    /// `None` if the call has no limit.
+    pub fn free_places(&self, in_count: i64) -> Option<i64> {
+        self.attendance_limit
+            .map(|limit| (i64::from(limit) - in_count).max(0))
+    }
+
+    /// Whether a party of the given size still fits in the call.
+    pub fn fits(&self, in_count: i64, party_size: i64) -> bool {
+        match self.free_places(in_count) {
+            Some(free_places) => party_size <= free_places,
+            None => true,
+        }
+    }
+}
+
+/// The outcome of restarting a call. `previous_call` is the call that was restarted, as it was
+/// closed to make way for the new call.
+#[derive(Debug, Clone)]
+pub struct RestartedCall {
+    pub call_with_responses: CallWithResponses,
+    pub previous_call: CallWithResponses,
 }
 
 #[derive(Insertable, Debug)]
@@ -32,13 +63,14 @@ pub struct NewRollCall<'a> {
 }
 
 impl<'a> NewRollCall<'a> {
-    pub fn new(chat_id: ChatId, title: &'a str) -> NewRollCall<'a> {
+    /// A new call of the chat with the given settings, which provide its defaults.
+    pub fn new(settings: &ChatSettings, title: &'a str) -> NewRollCall<'a> {
         let now = Utc::now().naive_local();
         NewRollCall {
-            chat_id,
+            chat_id: settings.chat_id,
             title,
             status: CallStatus::Open,
-            quiet: false,
+            quiet: settings.quiet,
             created_at: now,
             updated_at: now,
         }
@@ -51,6 +83,9 @@ pub struct UpdateRollCall<'a> {
     pub status: Option<CallStatus>,
     pub title: Option<&'a str>,
     pub quiet: Option<bool>,
+    pub attendance_limit: Option<Option<i32>>,
+    pub board_message_id: Option<Option<MessageId>>,
+    pub pinned_message_id: Option<Option<MessageId>>,
     pub updated_at: Option<NaiveDateTime>,
 }
 
@@ -61,6 +96,9 @@ impl<'a> UpdateRollCall<'a> {
             status: None,
             title: None,
             quiet: None,
+            attendance_limit: None,
+            board_message_id: None,
+            pinned_message_id: None,
             updated_at: Some(Utc::now().naive_local()),
         }
     }
@@ -79,6 +117,27 @@ impl<'a> UpdateRollCall<'a> {
         }
     }
 
+    pub fn with_attendance_limit(self, limit: Option<i32>) -> UpdateRollCall<'a> {
+        UpdateRollCall {
+            attendance_limit: Some(limit),
+            ..self
+        }
+    }
+
+    pub fn with_board_message_id(self, message_id: Option<MessageId>) -> UpdateRollCall<'a> {
+        UpdateRollCall {
+            board_message_id: Some(message_id),
+            ..self
+        }
+    }
+
+    pub fn with_pinned_message_id(self, message_id: Option<MessageId>) -> UpdateRollCall<'a> {
+        UpdateRollCall {
+            pinned_message_id: Some(message_id),
+            ..self
+        }
+    }
+
     pub fn with_title(self, title: &'a str) -> UpdateRollCall<'a> {
         UpdateRollCall {
             title: Some(title),
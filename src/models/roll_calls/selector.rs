@@ -0,0 +1,45 @@
+use super::RollCall;
+use crate::models::CallId;
+
+/// Picks one of the open calls of a chat. Commands without a selector act on the `Latest` call,
+/// while `#12` selects a call by its number and `#football` by the start of its title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallSelector {
+    Latest,
+    Id(CallId),
+    Name(String),
+}
+
+impl CallSelector {
+    pub fn matches(&self, call: &RollCall) -> bool {
+        match *self {
+            CallSelector::Latest => true,
+            CallSelector::Id(id) => call.id == id,
+            CallSelector::Name(ref name) => {
+                call.title.to_lowercase().starts_with(&name.to_lowercase())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::testutil::factories::*;
+
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let call = RollCall {
+            id: 3,
+            title: "Football on Saturday".to_string(),
+            ..create_call()
+        };
+
+        assert!(CallSelector::Latest.matches(&call));
+        assert!(CallSelector::Id(3).matches(&call));
+        assert!(!CallSelector::Id(4).matches(&call));
+        assert!(CallSelector::Name("football".to_string()).matches(&call));
+        assert!(!CallSelector::Name("saturday".to_string()).matches(&call));
+    }
+}
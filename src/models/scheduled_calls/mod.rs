@@ -0,0 +1,89 @@
+use chrono::{NaiveDateTime, Utc};
+
+pub use status::ScheduleStatus;
+
+use crate::schema::w_scheduled_calls;
+
+use super::type_aliases::*;
+
+mod status;
+
+/// A roll call that the scheduler opens at `start_at`, and closes at `end_at` if one is given.
+#[derive(Identifiable, Queryable, Debug, Clone)]
+#[table_name = "w_scheduled_calls"]
+pub struct ScheduledCall {
+    pub id: ScheduleId,
+    pub chat_id: ChatId,
+    pub status: ScheduleStatus,
+    pub title: String,
+    pub start_at: NaiveDateTime,
+    pub end_at: Option<NaiveDateTime>,
+    pub roll_call_id: Option<CallId>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "w_scheduled_calls"]
+pub struct NewScheduledCall<'a> {
+    pub chat_id: ChatId,
+    pub status: ScheduleStatus,
+    pub title: &'a str,
+    pub start_at: NaiveDateTime,
+    pub end_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl<'a> NewScheduledCall<'a> {
+    pub fn new(
+        chat_id: ChatId,
+        title: &'a str,
+        start_at: NaiveDateTime,
+        end_at: Option<NaiveDateTime>,
+    ) -> NewScheduledCall<'a> {
+        let now = Utc::now().naive_local();
+        NewScheduledCall {
+            chat_id,
+            status: ScheduleStatus::Pending,
+            title,
+            start_at,
+            end_at,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(AsChangeset, Debug)]
+#[table_name = "w_scheduled_calls"]
+pub struct UpdateScheduledCall {
+    pub status: Option<ScheduleStatus>,
+    pub roll_call_id: Option<CallId>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl UpdateScheduledCall {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> UpdateScheduledCall {
+        UpdateScheduledCall {
+            status: None,
+            roll_call_id: None,
+            updated_at: Some(Utc::now().naive_local()),
+        }
+    }
+
+    pub fn with_status(self, status: ScheduleStatus) -> UpdateScheduledCall {
+        UpdateScheduledCall {
+            status: Some(status),
+            ..self
+        }
+    }
+
+    pub fn with_roll_call_id(self, roll_call_id: CallId) -> UpdateScheduledCall {
+        UpdateScheduledCall {
+            roll_call_id: Some(roll_call_id),
+            ..self
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+/** Boilerplate to enable enum in database model. */
+#[derive(AsExpression, FromSqlRow, Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[sql_type = "Text"]
+pub enum ScheduleStatus {
+    Pending,
+    Started,
+    Done,
+}
+
+impl fmt::Display for ScheduleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                ScheduleStatus::Pending => "PENDING",
+                ScheduleStatus::Started => "STARTED",
+                ScheduleStatus::Done => "DONE",
+            }
+        )
+    }
+}
+
+impl FromStr for ScheduleStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PENDING" => Ok(ScheduleStatus::Pending),
+            "STARTED" => Ok(ScheduleStatus::Started),
+            "DONE" => Ok(ScheduleStatus::Done),
+            x => Err(format!("Unrecognized variant {}", x)),
+        }
+    }
+}
+
+impl<DB> ToSql<Text, DB> for ScheduleStatus
+where
+    DB: Backend,
+    String: ToSql<Text, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        self.to_string().to_sql(out)
+    }
+}
+
+impl<DB> FromSql<Text, DB> for ScheduleStatus
+where
+    DB: Backend,
+    String: FromSql<Text, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        String::from_sql(bytes)?
+            .parse::<ScheduleStatus>()
+            .map_err(|e| e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_to_string() {
+        assert_eq!("PENDING", ScheduleStatus::Pending.to_string());
+        assert_eq!("STARTED", ScheduleStatus::Started.to_string());
+        assert_eq!("DONE", ScheduleStatus::Done.to_string());
+    }
+
+    #[test]
+    fn test_status_from_valid_string() -> Result<(), String> {
+        assert_eq!(
+            ScheduleStatus::Pending,
+            "PENDING".parse::<ScheduleStatus>()?
+        );
+        assert_eq!(
+            ScheduleStatus::Started,
+            "STARTED".parse::<ScheduleStatus>()?
+        );
+        assert_eq!(ScheduleStatus::Done, "DONE".parse::<ScheduleStatus>()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_from_invalid_string() {
+        assert!("INVALID".parse::<ScheduleStatus>().is_err())
+    }
+}
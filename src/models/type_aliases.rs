@@ -3,8 +3,11 @@ use super::roll_calls::RollCall;
 
 pub type CallId = i64;
 pub type ResponseId = i64;
+pub type ScheduleId = i64;
+pub type RecurrenceId = i64;
 
 pub type ChatId = i64;
 pub type UserId = i64;
+pub type MessageId = i64;
 
 pub type CallWithResponses = (RollCall, Vec<RollCallResponse>);
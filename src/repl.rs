@@ -0,0 +1,350 @@
+use std::cell::{Cell, RefCell};
+use std::io::{BufRead, Write};
+
+use crate::bot::{
+    dispatch, report_sent, tick, BotResult, ChatButton, ChatCommand, ChatTransport, HandlerResult,
+    PinChange, Reply, SentMessage, TickResult,
+};
+use crate::models::{ChatId, MessageId, UserId};
+
+const HELP: &str = "\
+Type bot commands as you would in a chat, e.g. /start_roll_call Football
+Unknown commands are only answered when they name the bot, e.g. /weather@WhosInBot
+Other commands:
+  :user <id> <name>   act as another user
+  :chat <id>          switch to another chat
+  :help               show this message
+  :quit               exit";
+
+/// A `ChatTransport` that reads commands from a terminal and prints the replies, acting as a fake
+/// user in a fake chat that can both be switched at any time. Scheduled tasks run before every
+/// prompt, and their messages are printed along with the chat they were sent to.
+pub struct ReplTransport<R, W> {
+    input: RefCell<R>,
+    output: RefCell<W>,
+    last_message_id: Cell<MessageId>,
+}
+
+struct Session {
+    chat_id: ChatId,
+    user_id: UserId,
+    username: String,
+}
+
+enum Line {
+    Command(ChatCommand),
+    SwitchUser(UserId, String),
+    SwitchChat(ChatId),
+    Help,
+    Quit,
+    Empty,
+    Invalid(&'static str),
+}
+
+impl<R: BufRead, W: Write> ReplTransport<R, W> {
+    pub fn new(input: R, output: W) -> ReplTransport<R, W> {
+        ReplTransport {
+            input: RefCell::new(input),
+            output: RefCell::new(output),
+            last_message_id: Cell::new(0),
+        }
+    }
+
+    fn read_line(&self) -> Result<Option<String>, failure::Error> {
+        let mut line = String::new();
+        match self.input.borrow_mut().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+
+    fn print(&self, text: &str) -> BotResult {
+        let mut output = self.output.borrow_mut();
+        writeln!(output, "{}", text)?;
+        Ok(())
+    }
+
+    /// Prints the reply, with its buttons as the commands they would send, followed by the edits
+    /// and pins it makes. Every message gets a number like a real chat would give it, which is
+    /// shown for tagged messages so that later edits can be matched with them.
+    fn deliver<S>(&self, prefix: &str, chat_id: ChatId, reply: &Reply, on_sent: &S) -> BotResult
+    where
+        S: Fn(SentMessage) -> BotResult,
+    {
+        if !reply.text.is_empty() {
+            let message_id = self.last_message_id.get() + 1;
+            self.last_message_id.set(message_id);
+
+            let pinned = reply.pin == Some(PinChange::Reply);
+            match reply.tag {
+                Some(tag) => {
+                    self.print(&format!(
+                        "{}(message {}) {}",
+                        prefix, message_id, reply.text
+                    ))?;
+                    let message = SentMessage {
+                        chat_id,
+                        message_id,
+                        tag,
+                        pinned,
+                    };
+                    report_sent(on_sent, message);
+                }
+                None => self.print(&format!("{}{}", prefix, reply.text))?,
+            }
+            self.print_buttons(&reply.buttons)?;
+
+            if pinned {
+                self.print(&format!("{}(message {} pinned)", prefix, message_id))?;
+            }
+        }
+
+        for edit in reply.edits.iter() {
+            let edited = format!(
+                "{}(message {} edited) {}",
+                prefix, edit.message_id, edit.text
+            );
+            self.print(&edited)?;
+            self.print_buttons(&edit.buttons)?;
+        }
+
+        match reply.pin {
+            Some(PinChange::Message(message_id)) => {
+                self.print(&format!("{}(message {} pinned)", prefix, message_id))
+            }
+            Some(PinChange::Unpin) => self.print(&format!("{}(message unpinned)", prefix)),
+            Some(PinChange::Reply) | None => Ok(()),
+        }
+    }
+
+    fn print_buttons(&self, buttons: &[ChatButton]) -> BotResult {
+        if !buttons.is_empty() {
+            let buttons: Vec<String> = buttons
+                .iter()
+                .map(|button| format!("[{}: {}]", button.label, button.command))
+                .collect();
+            self.print(&buttons.join(" "))?;
+        }
+
+        Ok(())
+    }
+
+    fn prompt(&self, session: &Session) -> BotResult {
+        let mut output = self.output.borrow_mut();
+        write!(
+            output,
+            "[chat {}] {} ({})> ",
+            session.chat_id, session.username, session.user_id
+        )?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+impl<R: BufRead, W: Write> ChatTransport for ReplTransport<R, W> {
+    fn run<F, T, S>(&self, handler: F, ticker: T, on_sent: S) -> BotResult
+    where
+        F: Fn(ChatCommand) -> HandlerResult,
+        T: Fn() -> TickResult,
+        S: Fn(SentMessage) -> BotResult,
+    {
+        let logger = slog_scope::logger();
+        let mut session = Session {
+            chat_id: 1,
+            user_id: 1,
+            username: "User".to_string(),
+        };
+
+        self.print(HELP)?;
+        loop {
+            for message in tick(&ticker) {
+                let prefix = format!("[chat {}] ", message.chat_id);
+                self.deliver(&prefix, message.chat_id, &message.reply, &on_sent)?;
+            }
+
+            self.prompt(&session)?;
+            let line = match self.read_line()? {
+                Some(line) => line,
+                None => break,
+            };
+
+            match parse_line(&session, &line) {
+                Line::Command(command) => {
+                    let chat_id = command.chat_id;
+                    if let Some(reply) = dispatch(&handler, command, &logger) {
+                        self.deliver("", chat_id, &reply, &on_sent)?;
+                    }
+                }
+                Line::SwitchUser(user_id, username) => {
+                    session.user_id = user_id;
+                    session.username = username;
+                }
+                Line::SwitchChat(chat_id) => session.chat_id = chat_id,
+                Line::Help => self.print(HELP)?,
+                Line::Quit => break,
+                Line::Empty => {}
+                Line::Invalid(message) => self.print(message)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_line(session: &Session, line: &str) -> Line {
+    let line = line.trim();
+    if line.is_empty() {
+        return Line::Empty;
+    }
+
+    if line.starts_with('/') {
+        let command = ChatCommand::parse(
+            session.chat_id,
+            session.user_id,
+            &session.username,
+            line,
+            None,
+        );
+        return match command {
+            Some(command) => Line::Command(command),
+            None => Line::Invalid("Invalid command."),
+        };
+    }
+
+    let mut words = line.split_whitespace();
+    match words.next() {
+        Some(":user") => {
+            let user_id = words.next().and_then(|id| id.parse::<UserId>().ok());
+            let username = words.collect::<Vec<_>>().join(" ");
+            match user_id {
+                Some(user_id) if user_id > 0 && !username.is_empty() => {
+                    Line::SwitchUser(user_id, username)
+                }
+                _ => Line::Invalid("Usage: :user <id> <name>, with a positive id."),
+            }
+        }
+        Some(":chat") => match words.next().and_then(|id| id.parse::<ChatId>().ok()) {
+            Some(chat_id) => Line::SwitchChat(chat_id),
+            None => Line::Invalid("Usage: :chat <id>"),
+        },
+        Some(":help") => Line::Help,
+        Some(":quit") | Some(":q") => Line::Quit,
+        _ => Line::Invalid("Commands start with '/'. Type :help for help."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::{ChatEdit, ChatMessage, MessageTag};
+
+    use super::*;
+
+    fn run_with_input(input: &str) -> String {
+        let mut output = Vec::new();
+        {
+            let transport = ReplTransport::new(input.as_bytes(), &mut output);
+            let handler = |command: ChatCommand| {
+                Ok(Some(Reply::from(format!(
+                    "{} {} from {} ({}) in chat {}",
+                    command.command,
+                    command.command_params,
+                    command.username,
+                    command.user_id,
+                    command.chat_id
+                ))))
+            };
+
+            transport.run(handler, || Ok(vec![]), |_| Ok(())).unwrap();
+        }
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_run_passes_commands_to_handler() {
+        let output = run_with_input("/in running late\n");
+        assert!(output.contains("/in running late from User (1) in chat 1"));
+    }
+
+    #[test]
+    fn test_run_switches_user_and_chat() {
+        let output = run_with_input(":user 42 Alice Smith\n:chat -100\n/out\n");
+        assert!(output.contains("/out  from Alice Smith (42) in chat -100"));
+    }
+
+    #[test]
+    fn test_run_prints_scheduled_messages() {
+        let mut output = Vec::new();
+        {
+            let transport = ReplTransport::new("\n".as_bytes(), &mut output);
+            let ticker = || {
+                Ok(vec![ChatMessage {
+                    chat_id: 5,
+                    reply: Reply::from("Roll call started.").with_buttons(vec![ChatButton {
+                        label: "In".to_string(),
+                        command: "/in #1".to_string(),
+                    }]),
+                }])
+            };
+
+            transport.run(|_| Ok(None), ticker, |_| Ok(())).unwrap();
+        }
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(2, output.matches("[chat 5] Roll call started.").count());
+        assert_eq!(2, output.matches("[In: /in #1]").count());
+    }
+
+    #[test]
+    fn test_run_numbers_tagged_messages_and_prints_edits() {
+        let mut output = Vec::new();
+        let sent = RefCell::new(vec![]);
+        {
+            let transport = ReplTransport::new("/whos_in\n/in\n".as_bytes(), &mut output);
+            let handler = |command: ChatCommand| match command.command.as_str() {
+                "/whos_in" => Ok(Some(
+                    Reply::from("Roll call #7").with_tag(MessageTag::Board(7)),
+                )),
+                _ => Ok(Some(Reply::from("User is in!").with_edits(vec![
+                    ChatEdit {
+                        message_id: 1,
+                        text: "Roll call #7\n\nIn (1)".to_string(),
+                        buttons: vec![],
+                    },
+                ]))),
+            };
+            let on_sent = |message: SentMessage| {
+                sent.borrow_mut().push(message);
+                Ok(())
+            };
+
+            transport.run(handler, || Ok(vec![]), on_sent).unwrap();
+        }
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("(message 1) Roll call #7"));
+        assert!(output.contains("User is in!\n(message 1 edited) Roll call #7"));
+
+        let expected = SentMessage {
+            chat_id: 1,
+            message_id: 1,
+            tag: MessageTag::Board(7),
+            pinned: false,
+        };
+        assert_eq!(vec![expected], sent.into_inner());
+    }
+
+    #[test]
+    fn test_run_stops_at_quit() {
+        let output = run_with_input(":quit\n/in\n");
+        assert!(!output.contains("/in"));
+    }
+
+    #[test]
+    fn test_run_rejects_invalid_lines() {
+        let output = run_with_input("hello\n:user Alice\n:chat\n");
+        assert!(output.contains("Commands start with '/'."));
+        assert!(output.contains("Usage: :user <id> <name>"));
+        assert!(output.contains("Usage: :chat <id>"));
+    }
+}
@@ -1,3 +1,40 @@
+table! {
+    w_chat_aliases (chat_id, alias) {
+        chat_id -> Int8,
+        alias -> Varchar,
+        command -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    w_chat_settings (chat_id) {
+        chat_id -> Int8,
+        pin_calls -> Bool,
+        answer_unknown_commands -> Bool,
+        quiet -> Bool,
+        language -> Varchar,
+        timezone -> Varchar,
+        reply_style -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    w_recurring_calls (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        title -> Text,
+        weekday -> Int4,
+        start_time -> Time,
+        next_at -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        timezone -> Varchar,
+    }
+}
+
 table! {
     w_roll_call_responses (id) {
         id -> Int8,
@@ -9,6 +46,7 @@ table! {
         reason -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        guests -> Int4,
     }
 }
 
@@ -21,9 +59,34 @@ table! {
         quiet -> Bool,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        attendance_limit -> Nullable<Int4>,
+        board_message_id -> Nullable<Int8>,
+        pinned_message_id -> Nullable<Int8>,
+    }
+}
+
+table! {
+    w_scheduled_calls (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        status -> Varchar,
+        title -> Text,
+        start_at -> Timestamp,
+        end_at -> Nullable<Timestamp>,
+        roll_call_id -> Nullable<Int8>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
 joinable!(w_roll_call_responses -> w_roll_calls (roll_call_id));
+joinable!(w_scheduled_calls -> w_roll_calls (roll_call_id));
 
-allow_tables_to_appear_in_same_query!(w_roll_call_responses, w_roll_calls,);
+allow_tables_to_appear_in_same_query!(
+    w_chat_aliases,
+    w_chat_settings,
+    w_recurring_calls,
+    w_roll_call_responses,
+    w_roll_calls,
+    w_scheduled_calls,
+);
@@ -1,14 +1,44 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
 use config::{ConfigError, Environment, File};
 
+use crate::models::{ChatId, Retention as RetentionPolicies, RetentionPolicy};
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    Postgres,
+    Sqlite,
+    Memory,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Database {
+    pub backend: DatabaseBackend,
     pub url: String,
     pub timeout_ms: u64,
 }
 
+#[derive(Debug, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum TelegramMode {
+    Polling,
+    Webhook,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Webhook {
+    pub listen: String,
+    pub path: String,
+    pub secret: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Telegram {
     pub token: String,
+    pub mode: TelegramMode,
+    pub webhook: Webhook,
 }
 
 #[derive(Debug, Deserialize)]
@@ -16,11 +46,59 @@ pub struct Sentry {
     pub dsn: String,
 }
 
+/// How long roll calls are kept, where 0 means no limit. Chats may override either limit.
+#[derive(Debug, Deserialize)]
+pub struct Retention {
+    pub max_calls: usize,
+    pub max_age_days: i64,
+    pub interval_minutes: i64,
+    #[serde(default)]
+    pub chats: Vec<ChatRetention>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatRetention {
+    pub chat_id: ChatId,
+    pub max_calls: Option<usize>,
+    pub max_age_days: Option<i64>,
+}
+
+impl Retention {
+    pub fn policies(&self) -> RetentionPolicies {
+        let policy = |max_calls: usize, max_age_days: i64| RetentionPolicy {
+            max_calls: Some(max_calls).filter(|&max_calls| max_calls > 0),
+            max_age: Some(max_age_days)
+                .filter(|&max_age_days| max_age_days > 0)
+                .map(Duration::days),
+        };
+
+        let chats: HashMap<ChatId, RetentionPolicy> = self
+            .chats
+            .iter()
+            .map(|chat| {
+                let max_calls = chat.max_calls.unwrap_or(self.max_calls);
+                let max_age_days = chat.max_age_days.unwrap_or(self.max_age_days);
+                (chat.chat_id, policy(max_calls, max_age_days))
+            })
+            .collect();
+
+        RetentionPolicies {
+            default: policy(self.max_calls, self.max_age_days),
+            chats,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::minutes(self.interval_minutes)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Settings {
     pub database: Database,
     pub telegram: Telegram,
     pub sentry: Sentry,
+    pub retention: Retention,
 }
 
 impl Settings {
@@ -66,7 +144,31 @@ mod tests {
     fn test_load_test_settings_with_fallback() -> Result<(), failure::Error> {
         let settings = Settings::test()?;
         assert_eq!(5000, settings.database.timeout_ms);
+        assert_eq!(DatabaseBackend::Postgres, settings.database.backend);
+        assert_eq!(TelegramMode::Polling, settings.telegram.mode);
+        assert_eq!("/telegram", settings.telegram.webhook.path);
+        assert_eq!(RetentionPolicies::default(), settings.retention.policies());
 
         Ok(())
     }
+
+    #[test]
+    fn test_retention_policies_override_per_chat() {
+        let retention = Retention {
+            max_calls: 10,
+            max_age_days: 0,
+            interval_minutes: 60,
+            chats: vec![ChatRetention {
+                chat_id: -100,
+                max_calls: Some(0),
+                max_age_days: Some(30),
+            }],
+        };
+
+        let policies = retention.policies();
+        assert_eq!(Some(10), policies.for_chat(1).max_calls);
+        assert_eq!(None, policies.for_chat(1).max_age);
+        assert_eq!(None, policies.for_chat(-100).max_calls);
+        assert_eq!(Some(Duration::days(30)), policies.for_chat(-100).max_age);
+    }
 }
@@ -1,101 +1,388 @@
+use std::time::Duration;
+
 use failure::SyncFailure;
-use futures::Stream;
-use regex::Regex;
+use futures::unsync::mpsc;
+use futures::{Future, Stream};
 use slog_scope;
 use telegram_bot::{self, *};
-use tokio_core::reactor::Core;
+use tokio_core::reactor::{Core, Handle, Interval};
+
+use crate::bot::{
+    dispatch, report_sent, tick, BotResult, ChatButton, ChatCommand, ChatEdit, ChatTransport,
+    HandlerResult, PinChange, Reply, SentMessage, TickResult,
+};
+use crate::settings::{Telegram, TelegramMode};
+
+use super::updates::{self, ChatUpdate, TextMention, Updates};
+use super::webhook;
+
+/// How often scheduled tasks are checked for work.
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct ChatCommand {
-    pub chat_id: i64,
-    pub user_id: i64,
-    pub username: String,
-    pub command: String,
-    pub command_params: String,
+/// The commands that inline keyboard buttons may send. Clients can put anything in the callback
+/// data, so buttons cannot be used to run other commands.
+const CALLBACK_COMMANDS: [&str; 3] = ["/in", "/out", "/maybe"];
+
+enum Event {
+    Update(Box<ChatUpdate>),
+    Tick,
+    Sent(SentMessage),
+}
+
+/// Connects the bot to Telegram, receiving updates by long polling or through the webhook server
+/// depending on the configured `TelegramMode`.
+pub struct TelegramTransport<'a> {
+    settings: &'a Telegram,
 }
 
-pub type BotResult = Result<(), failure::Error>;
+impl<'a> TelegramTransport<'a> {
+    pub fn new(settings: &'a Telegram) -> TelegramTransport<'a> {
+        TelegramTransport { settings }
+    }
+}
+
+impl<'a> ChatTransport for TelegramTransport<'a> {
+    fn run<F, T, S>(&self, handler: F, ticker: T, on_sent: S) -> BotResult
+    where
+        F: Fn(ChatCommand) -> HandlerResult,
+        T: Fn() -> TickResult,
+        S: Fn(SentMessage) -> BotResult,
+    {
+        let token = &self.settings.token;
+        match self.settings.mode {
+            TelegramMode::Polling => run_with_updates(token, handler, ticker, on_sent, |api, _| {
+                Ok(updates::poll(api))
+            }),
+
+            TelegramMode::Webhook => {
+                run_with_updates(token, handler, ticker, on_sent, |_, handle| {
+                    webhook::listen(handle, &self.settings.webhook)
+                })
+            }
+        }
+    }
+}
 
-pub fn run<F>(token: &str, handler: F) -> BotResult
+fn run_with_updates<F, T, S, U>(
+    token: &str,
+    handler: F,
+    ticker: T,
+    on_sent: S,
+    updates_fn: U,
+) -> BotResult
 where
-    F: Fn(ChatCommand) -> Result<Option<String>, failure::Error>,
+    F: Fn(ChatCommand) -> HandlerResult,
+    T: Fn() -> TickResult,
+    S: Fn(SentMessage) -> BotResult,
+    U: FnOnce(&Api, &Handle) -> Result<Updates, failure::Error>,
 {
     let mut core = Core::new().map_err(SyncFailure::new)?;
     let api = Api::configure(token)
         .build(core.handle())
         .map_err(SyncFailure::new)?;
 
+    // commands may name the bot they are for, as in `/in@WhosInBot`
+    let me = core.run(api.send(GetMe)).map_err(SyncFailure::new)?;
+    let bot_username = me
+        .username
+        .ok_or_else(|| format_err!("The bot has no username"))?;
+    info!("Running as @{}", bot_username);
+
+    let updates = updates_fn(&api, &core.handle())?.map(|update| Event::Update(Box::new(update)));
+    let ticks = Interval::new(TICK_INTERVAL, &core.handle())?
+        .map(|_| Event::Tick)
+        .map_err(failure::Error::from);
+    let (sent_sender, sent_receiver) = mpsc::unbounded();
+    let sent = sent_receiver
+        .map(Event::Sent)
+        .map_err(|_| format_err!("The channel of sent messages has closed"));
+    let outbox = Outbox {
+        api: &api,
+        handle: core.handle(),
+        sent: sent_sender,
+    };
     let logger = slog_scope::logger();
 
     info!("Waiting for messages...");
-    let future = api.stream().for_each(|update| {
-        if let UpdateKind::Message(message) = update.kind {
-            if let Some(reply) = handle_message(&handler, &message, &logger) {
-                api.spawn(message.chat.text(reply))
+    let future = updates.select(ticks).select(sent).for_each(|event| {
+        match event {
+            Event::Update(update) => match update.update.kind {
+                UpdateKind::Message(message) => {
+                    let mentions = &update.text_mentions;
+                    if let Some(reply) =
+                        handle_message(&handler, &message, mentions, &bot_username, &logger)
+                    {
+                        outbox.deliver(message.chat.id(), reply)
+                    }
+                }
+
+                UpdateKind::CallbackQuery(query) => {
+                    // stops the spinner on the button, the reply goes to the chat as usual
+                    api.spawn(query.answer(""));
+                    if let Some(reply) = handle_callback_query(&handler, &query, &logger) {
+                        outbox.deliver(query.message.chat.id(), reply)
+                    }
+                }
+
+                _ => {}
+            },
+
+            Event::Tick => {
+                for message in tick(&ticker) {
+                    outbox.deliver(ChatId::new(message.chat_id), message.reply)
+                }
             }
+
+            Event::Sent(message) => report_sent(&on_sent, message),
         }
         Ok(())
     });
 
-    core.run(future).map_err(|e| SyncFailure::new(e).into())
+    core.run(future)
+}
+
+/// Sends replies to Telegram without waiting for the results. Tagged messages come back as
+/// `Event::Sent` once Telegram has given them an id.
+struct Outbox<'a> {
+    api: &'a Api,
+    handle: Handle,
+    sent: mpsc::UnboundedSender<SentMessage>,
+}
+
+impl<'a> Outbox<'a> {
+    fn deliver(&self, chat: ChatId, reply: Reply) {
+        if !reply.text.is_empty() {
+            self.send(chat, &reply);
+        }
+
+        for edit in reply.edits.iter() {
+            self.api.spawn(build_edit(chat, edit))
+        }
+
+        match reply.pin {
+            Some(PinChange::Message(message_id)) => {
+                let message = EarlierMessage(chat, MessageId::new(message_id));
+                spawn_pin_request(&self.handle, self.api, message.pin())
+            }
+            Some(PinChange::Unpin) => {
+                spawn_pin_request(&self.handle, self.api, chat.unpin_message())
+            }
+            Some(PinChange::Reply) | None => {}
+        }
+    }
+
+    fn send(&self, chat: ChatId, reply: &Reply) {
+        let request = build_request(chat, reply);
+        let tag = reply.tag;
+        let pinned = reply.pin == Some(PinChange::Reply);
+        if tag.is_none() && !pinned {
+            return self.api.spawn(request);
+        }
+
+        let (api, handle, sent) = (self.api.clone(), self.handle.clone(), self.sent.clone());
+        let future = self.api.send(request).then(move |result| {
+            match result {
+                Ok(message) => {
+                    if pinned {
+                        spawn_pin_request(&handle, &api, message.pin());
+                    }
+                    if let Some(tag) = tag {
+                        let message = SentMessage {
+                            chat_id: chat.into(),
+                            message_id: message.id.into(),
+                            tag,
+                            pinned,
+                        };
+                        // the receiver only goes away when the bot stops
+                        let _ = sent.unbounded_send(message);
+                    }
+                }
+                Err(err) => error!("Failed to send a message: {}", err),
+            }
+            Ok(())
+        });
+        self.handle.spawn(future)
+    }
+}
+
+/// A message sent earlier, known by its chat and id only.
+struct EarlierMessage(ChatId, MessageId);
+
+impl ToSourceChat for EarlierMessage {
+    fn to_source_chat(&self) -> ChatId {
+        self.0
+    }
+}
+
+impl ToMessageId for EarlierMessage {
+    fn to_message_id(&self) -> MessageId {
+        self.1
+    }
+}
+
+/// Pinning fails when the admins of the chat have not allowed the bot to pin messages. That is
+/// up to them, so the failure is only logged.
+fn spawn_pin_request<R: Request>(handle: &Handle, api: &Api, request: R) {
+    handle.spawn(api.send(request).then(|result| {
+        if let Err(err) = result {
+            warn!(
+                "Failed to change the pinned message, the bot may not be allowed to: {}",
+                err
+            );
+        }
+        Ok(())
+    }))
 }
 
-fn handle_message<F>(handler: &F, message: &Message, logger: &slog::Logger) -> Option<String>
+fn handle_message<F>(
+    handler: &F,
+    message: &Message,
+    text_mentions: &[TextMention],
+    bot_username: &str,
+    logger: &slog::Logger,
+) -> Option<Reply>
 where
-    F: Fn(ChatCommand) -> Result<Option<String>, failure::Error>,
+    F: Fn(ChatCommand) -> HandlerResult,
 {
-    if let Some(command) = parse_message(message) {
-        let chat_id = command.chat_id;
-        let user_id = command.user_id;
+    parse_message(message, text_mentions, bot_username)
+        .and_then(|command| dispatch(handler, command, logger))
+}
 
-        let sentry_scope = |scope: &mut sentry::Scope| {
-            scope.set_tag("chat_id", chat_id);
-            scope.set_tag("user_id", user_id);
-        };
+fn handle_callback_query<F>(
+    handler: &F,
+    query: &CallbackQuery,
+    logger: &slog::Logger,
+) -> Option<Reply>
+where
+    F: Fn(ChatCommand) -> HandlerResult,
+{
+    parse_callback_query(query).and_then(|command| dispatch(handler, command, logger))
+}
 
-        let logger_scope = &logger.new(o!(
-            "chat_id" => chat_id,
-            "user_id" => user_id,
-        ));
-
-        let handle = || match handler(command) {
-            Ok(Some(reply)) => Some(reply),
-            Ok(None) => None,
-            Err(err) => {
-                error!("An error has occurred: {}", err; "details" => format!("{:?}", err));
-                sentry::integrations::failure::capture_error(&err);
-                Some("An error has occurred.".to_string())
-            }
+/// Parses a command sent to the chat, unless it names another bot. Everything in a private chat
+/// is meant for this bot.
+fn parse_message(
+    message: &Message,
+    text_mentions: &[TextMention],
+    bot_username: &str,
+) -> Option<ChatCommand> {
+    if let MessageKind::Text { ref data, .. } = message.kind {
+        let command = ChatCommand::parse(
+            message.chat.id().into(),
+            message.from.id.into(),
+            &message.from.first_name,
+            &quote_text_mentions(data, text_mentions),
+            Some(bot_username),
+        )?;
+
+        return match message.chat {
+            MessageChat::Private(_) => Some(ChatCommand {
+                addressed: true,
+                ..command
+            }),
+            _ => Some(command),
         };
-
-        return sentry::with_scope(sentry_scope, || slog_scope::scope(logger_scope, handle));
     }
 
     None
 }
 
-lazy_static! {
-    static ref COMMAND_REGEX: Regex =
-        Regex::new(r"^(/[^@[:space:]]+)(@\S*)?\s*(.*)$").expect("Failed to create Regex");
+/// Quotes the names of several words that mention users without a username, as in
+/// `/set_in_for "Mary Jane", Tom`, so that they are read as one name. Only names that start the
+/// params or an entry of a list are quoted, leaving those in the middle of a reason alone.
+fn quote_text_mentions(text: &str, text_mentions: &[TextMention]) -> String {
+    let mut spans: Vec<(usize, usize)> = text_mentions
+        .iter()
+        .filter_map(|mention| {
+            let start = utf16_to_byte(text, mention.offset)?;
+            let end = utf16_to_byte(text, mention.offset + mention.length)?;
+            Some((start, end))
+        })
+        .collect();
+    spans.sort();
+
+    let mut quoted = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, end) in spans {
+        if start < copied {
+            continue;
+        }
+
+        let name = &text[start..end];
+        let before = text[..start].trim_end();
+        let starts_entry = before.ends_with(',')
+            || text[before.len()..start].contains('\n')
+            || !before.contains(char::is_whitespace);
+        if name.contains(char::is_whitespace) && !name.contains('"') && starts_entry {
+            quoted.push_str(&text[copied..start]);
+            quoted.push_str(&format!("\"{}\"", name));
+            copied = end;
+        }
+    }
+    quoted.push_str(&text[copied..]);
+    quoted
 }
 
-fn parse_message(message: &Message) -> Option<ChatCommand> {
-    if let MessageKind::Text { ref data, .. } = message.kind {
-        if let Some(captures) = COMMAND_REGEX.captures(data) {
-            let command = captures[1].to_owned();
-            let command_params = captures[3].trim_end().to_owned();
-
-            return Some(ChatCommand {
-                chat_id: message.chat.id().into(),
-                user_id: message.from.id.into(),
-                username: message.from.first_name.clone(),
-                command,
-                command_params,
-            });
+/// Converts an offset in UTF-16 code units, as Telegram counts them, to a byte offset in `text`.
+fn utf16_to_byte(text: &str, offset: usize) -> Option<usize> {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units == offset {
+            return Some(index);
         }
+        units += c.len_utf16();
     }
 
-    None
+    if units == offset {
+        Some(text.len())
+    } else {
+        None
+    }
+}
+
+fn parse_callback_query(query: &CallbackQuery) -> Option<ChatCommand> {
+    parse_button_data(&query.message, &query.from, &query.data)
+}
+
+/// Parses the data of a button pressed by `user` under `message` as a command sent by the user.
+fn parse_button_data(message: &Message, user: &User, data: &str) -> Option<ChatCommand> {
+    ChatCommand::parse(
+        message.chat.id().into(),
+        user.id.into(),
+        &user.first_name,
+        data,
+        None,
+    )
+    .filter(|command| CALLBACK_COMMANDS.contains(&command.command.as_str()))
+}
+
+fn build_request(chat: ChatId, reply: &Reply) -> SendMessage<'static> {
+    let mut request = chat.text(reply.text.clone());
+    if !reply.buttons.is_empty() {
+        request.reply_markup(build_keyboard(&reply.buttons));
+    }
+
+    request
+}
+
+/// Builds the request to edit a message. Leaving out the buttons removes those the message had.
+fn build_edit(chat: ChatId, edit: &ChatEdit) -> EditMessageText<'static> {
+    let message_id = MessageId::new(edit.message_id);
+    let mut request = EditMessageText::new(chat, message_id, edit.text.clone());
+    if !edit.buttons.is_empty() {
+        request.reply_markup(build_keyboard(&edit.buttons));
+    }
+
+    request
+}
+
+fn build_keyboard(buttons: &[ChatButton]) -> InlineKeyboardMarkup {
+    let row = buttons
+        .iter()
+        .map(|button| InlineKeyboardButton::callback(&button.label, &button.command))
+        .collect();
+
+    InlineKeyboardMarkup::from(vec![row])
 }
 
 #[cfg(test)]
@@ -135,13 +422,14 @@ mod tests {
         #[test]
         fn test_parse_valid_command() {
             let message = build_message("/some_command command params ");
-            let actual = parse_message(&message);
+            let actual = parse_message(&message, &[], "WhosInBot");
             let expected = Some(ChatCommand {
                 chat_id: 123,
                 user_id: 12345,
                 username: "User 1".to_string(),
                 command: "/some_command".to_string(),
                 command_params: "command params".to_string(),
+                addressed: false,
             });
 
             assert_eq!(expected, actual);
@@ -149,28 +437,123 @@ mod tests {
 
         #[test]
         fn test_parse_valid_command_with_mention() {
-            let message = build_message("/some_command@bot command params ");
-            let actual = parse_message(&message);
+            let message = build_message("/some_command@whosinbot command params ");
+            let actual = parse_message(&message, &[], "WhosInBot");
             let expected = Some(ChatCommand {
                 chat_id: 123,
                 user_id: 12345,
                 username: "User 1".to_string(),
                 command: "/some_command".to_string(),
                 command_params: "command params".to_string(),
+                addressed: true,
             });
 
             assert_eq!(expected, actual);
         }
 
+        #[test]
+        fn test_ignore_commands_for_other_bots() {
+            let message = build_message("/start@OtherBot");
+            assert_eq!(None, parse_message(&message, &[], "WhosInBot"));
+        }
+
+        #[test]
+        fn test_parse_private_command_as_addressed() {
+            let message = Message {
+                chat: MessageChat::Private(User {
+                    id: UserId::from(12345),
+                    first_name: "User 1".to_string(),
+                    last_name: None,
+                    username: None,
+                }),
+                ..build_message("/unknown")
+            };
+            let actual = parse_message(&message, &[], "WhosInBot").map(|command| command.addressed);
+
+            assert_eq!(Some(true), actual);
+        }
+
+        #[test]
+        fn test_parse_command_with_params_on_several_lines() {
+            let message = build_message("/set_in_for Dave\nSarah +1\n");
+            let actual =
+                parse_message(&message, &[], "WhosInBot").map(|command| command.command_params);
+
+            assert_eq!(Some("Dave\nSarah +1".to_string()), actual);
+        }
+
+        fn text_mention(offset: usize, length: usize) -> TextMention {
+            TextMention { offset, length }
+        }
+
+        #[test]
+        fn test_parse_command_with_text_mentions() {
+            // Telegram counts offsets in UTF-16 code units, in which the emoji takes two
+            let message = build_message("/set_in_for Mary Jane, 🎾\nMary Jane");
+            let mentions = [text_mention(12, 9), text_mention(26, 9)];
+            let actual = parse_message(&message, &mentions, "WhosInBot")
+                .map(|command| command.command_params);
+            assert_eq!(Some("\"Mary Jane\", 🎾\n\"Mary Jane\"".to_string()), actual);
+
+            let message = build_message("/in with Mary Jane");
+            let actual = parse_message(&message, &[text_mention(9, 9)], "WhosInBot")
+                .map(|command| command.command_params);
+            assert_eq!(Some("with Mary Jane".to_string()), actual);
+        }
+
         #[test]
         fn test_parse_invalid_commands() {
-            assert_eq!(None, parse_message(&build_message("")));
-            assert_eq!(None, parse_message(&build_message("  ")));
-            assert_eq!(None, parse_message(&build_message("invalid")));
+            assert_eq!(None, parse_message(&build_message(""), &[], "WhosInBot"));
+            assert_eq!(None, parse_message(&build_message("  "), &[], "WhosInBot"));
+            assert_eq!(
+                None,
+                parse_message(&build_message("invalid"), &[], "WhosInBot")
+            );
+            assert_eq!(
+                None,
+                parse_message(
+                    &build_message("invalid /invalid command params"),
+                    &[],
+                    "WhosInBot"
+                )
+            );
+        }
+    }
+
+    mod parse_button_data_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_attendance_button() {
+            let message = build_message("Roll call #5 started.");
+            let user = User {
+                id: UserId::from(678),
+                first_name: "User 2".to_string(),
+                last_name: None,
+                username: None,
+            };
+
+            let actual = parse_button_data(&message, &user, "/maybe #5");
+            let expected = Some(ChatCommand {
+                chat_id: 123,
+                user_id: 678,
+                username: "User 2".to_string(),
+                command: "/maybe".to_string(),
+                command_params: "#5".to_string(),
+                addressed: false,
+            });
+
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_parse_other_button_data() {
+            let message = build_message("Roll call #5 started.");
             assert_eq!(
                 None,
-                parse_message(&build_message("invalid /invalid command params"))
+                parse_button_data(&message, &message.from, "/end_roll_call")
             );
+            assert_eq!(None, parse_button_data(&message, &message.from, "in"));
         }
     }
 
@@ -181,13 +564,16 @@ mod tests {
         fn test_handle_message_with_success_result() {
             let message = build_message("/command params");
             let handler = |command: ChatCommand| {
-                Ok(Some(format!(
+                Ok(Some(Reply::from(format!(
                     "response to {} {} from {} in chat {}",
                     command.command, command.command_params, command.user_id, command.chat_id
-                )))
+                ))))
             };
 
-            let result = with_test_logger(|logger| handle_message(&handler, &message, logger));
+            let result = with_test_logger(|logger| {
+                handle_message(&handler, &message, &[], "WhosInBot", logger)
+            })
+            .map(|reply| reply.text);
 
             assert_eq!(
                 Some("response to /command params from 12345 in chat 123".to_string()),
@@ -200,7 +586,10 @@ mod tests {
             let message = build_message("/command params");
             let handler = |_: ChatCommand| Ok(None);
 
-            let result = with_test_logger(|logger| handle_message(&handler, &message, logger));
+            let result = with_test_logger(|logger| {
+                handle_message(&handler, &message, &[], "WhosInBot", logger)
+            })
+            .map(|reply| reply.text);
 
             assert_eq!(None, result);
         }
@@ -210,7 +599,10 @@ mod tests {
             let message = build_message("/command params");
             let handler = |_: ChatCommand| bail!("mock error");
 
-            let result = with_test_logger(|logger| handle_message(&handler, &message, logger));
+            let result = with_test_logger(|logger| {
+                handle_message(&handler, &message, &[], "WhosInBot", logger)
+            })
+            .map(|reply| reply.text);
 
             assert_eq!(Some("An error has occurred.".to_string()), result);
         }
@@ -1,6 +1,5 @@
-mod base_bot;
-mod commands;
-mod views;
-mod whosin;
+pub use base_bot::TelegramTransport;
 
-pub use whosin::*;
+mod base_bot;
+mod updates;
+mod webhook;
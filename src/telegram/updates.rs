@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use failure::SyncFailure;
+use futures::{stream, Future, Stream};
+use serde::de::{Deserialize, Deserializer, Error};
+use telegram_bot::{Api, GetUpdates, Integer, Update};
+use telegram_bot_raw::{HttpRequest, JsonIdResponse, JsonRequestType, RawMessageEntity, Request};
+
+pub type Updates = Box<dyn Stream<Item = ChatUpdate, Error = failure::Error>>;
+
+/// How long Telegram may hold a polling request open while waiting for updates.
+const POLL_TIMEOUT_SECONDS: u64 = 5;
+
+/// An `Update` along with the text mentions in its message. telegram-bot keeps the fields of
+/// `MessageEntity` private, so they are read from the JSON of the update instead.
+#[derive(Debug)]
+pub struct ChatUpdate {
+    pub update: Update,
+    pub text_mentions: Vec<TextMention>,
+}
+
+/// A mention of a user without a username, in UTF-16 code units as Telegram counts them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextMention {
+    pub offset: usize,
+    pub length: usize,
+}
+
+#[derive(Deserialize)]
+struct RawUpdate {
+    message: Option<RawMessage>,
+}
+
+#[derive(Deserialize)]
+struct RawMessage {
+    #[serde(default)]
+    entities: Vec<RawMessageEntity>,
+}
+
+impl<'de> Deserialize<'de> for ChatUpdate {
+    fn deserialize<D>(deserializer: D) -> Result<ChatUpdate, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let json = serde_json::Value::deserialize(deserializer)?;
+        let update = Update::deserialize(&json).map_err(D::Error::custom)?;
+        let raw = RawUpdate::deserialize(&json).map_err(D::Error::custom)?;
+
+        let entities = raw
+            .message
+            .map_or_else(Vec::new, |message| message.entities);
+        let text_mentions = entities
+            .into_iter()
+            .filter(|entity| entity.type_ == "text_mention")
+            .map(|entity| TextMention {
+                offset: entity.offset as usize,
+                length: entity.length as usize,
+            })
+            .collect();
+
+        Ok(ChatUpdate {
+            update,
+            text_mentions,
+        })
+    }
+}
+
+/// `getUpdates`, answered with `ChatUpdate`s rather than plain `Update`s.
+struct GetChatUpdates(GetUpdates);
+
+impl Request for GetChatUpdates {
+    type Type = JsonRequestType<GetUpdates>;
+    type Response = JsonIdResponse<Vec<ChatUpdate>>;
+
+    fn serialize(&self) -> Result<HttpRequest, telegram_bot_raw::Error> {
+        self.0.serialize()
+    }
+}
+
+/// Receives updates by long polling, the way `Api::stream` does.
+pub fn poll(api: &Api) -> Updates {
+    let api = api.clone();
+    let updates = stream::unfold(0, move |last_update: Integer| {
+        let mut request = GetUpdates::new();
+        request
+            .offset(last_update + 1)
+            .timeout(POLL_TIMEOUT_SECONDS as Integer);
+
+        let timeout = Duration::from_secs(POLL_TIMEOUT_SECONDS + 1);
+        let updates = api
+            .send_timeout(GetChatUpdates(request), timeout)
+            .map(move |updates| {
+                let updates = updates.unwrap_or_else(Vec::new);
+                let last_update = updates
+                    .iter()
+                    .map(|update| update.update.id)
+                    .fold(last_update, Integer::max);
+                (stream::iter_ok(updates), last_update)
+            });
+        Some(updates)
+    });
+
+    Box::new(
+        updates
+            .flatten()
+            .map_err(|err: telegram_bot::Error| SyncFailure::new(err).into()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use telegram_bot::UpdateKind;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_update_with_text_mentions() {
+        let json = r#"{
+            "update_id": 7,
+            "message": {
+                "message_id": 1234,
+                "from": {"id": 12345, "first_name": "User 1", "is_bot": false},
+                "chat": {"id": 12345, "type": "private", "first_name": "User 1"},
+                "date": 12345,
+                "text": "/set_in_for Mary Jane, @tom",
+                "entities": [
+                    {"type": "bot_command", "offset": 0, "length": 11},
+                    {"type": "text_mention", "offset": 12, "length": 9,
+                        "user": {"id": 5, "first_name": "Mary Jane", "is_bot": false}},
+                    {"type": "mention", "offset": 23, "length": 4}
+                ]
+            }
+        }"#;
+
+        let actual: ChatUpdate = serde_json::from_str(json).unwrap();
+
+        assert_eq!(7, actual.update.id);
+        match actual.update.kind {
+            UpdateKind::Message(_) => {}
+            kind => panic!("Expected a message, got {:?}", kind),
+        }
+        let expected = vec![TextMention {
+            offset: 12,
+            length: 9,
+        }];
+        assert_eq!(expected, actual.text_mentions);
+    }
+
+    #[test]
+    fn test_deserialize_update_without_message() {
+        let json = r#"{
+            "update_id": 8,
+            "callback_query": {
+                "id": "1",
+                "from": {"id": 12345, "first_name": "User 1", "is_bot": false},
+                "message": {
+                    "message_id": 1234,
+                    "from": {"id": 1, "first_name": "WhosInBot", "is_bot": true},
+                    "chat": {"id": 12345, "type": "private", "first_name": "User 1"},
+                    "date": 12345,
+                    "text": "Roll call #5 started."
+                },
+                "chat_instance": "1",
+                "data": "/in"
+            }
+        }"#;
+
+        let actual: ChatUpdate = serde_json::from_str(json).unwrap();
+
+        assert_eq!(8, actual.update.id);
+        assert!(actual.text_mentions.is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+use std::net::SocketAddr;
+
+use crypto::util::fixed_time_eq;
+use futures::sync::mpsc::{self, UnboundedSender};
+use futures::{future, Future, Stream};
+use hyper::header::ContentLength;
+use hyper::server::{Http, Request, Response, Service};
+use hyper::{Method, StatusCode};
+use tokio_core::reactor::Handle;
+
+use crate::settings::Webhook;
+
+use super::updates::{ChatUpdate, Updates};
+
+const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+/// The largest request body that is read, far more than any update Telegram sends.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Starts an HTTP server on the reactor behind `handle` that accepts the updates Telegram posts
+/// to the webhook, and returns them as a stream.
+pub fn listen(handle: &Handle, settings: &Webhook) -> Result<Updates, failure::Error> {
+    let addr: SocketAddr = settings.listen.parse()?;
+    let (sender, receiver) = mpsc::unbounded();
+
+    let service = WebhookService {
+        path: settings.path.clone(),
+        secret: settings.secret.clone(),
+        updates: sender,
+    };
+
+    let serve = Http::new().serve_addr_handle(&addr, handle, move || Ok(service.clone()))?;
+
+    let connection_handle = handle.clone();
+    let server = serve
+        .for_each(move |connection| {
+            connection_handle.spawn(connection.map_err(|err| {
+                warn!("Webhook connection error: {}", err);
+            }));
+            Ok(())
+        })
+        .map_err(|err| {
+            error!("Webhook server error: {}", err; "details" => format!("{:?}", err));
+        });
+
+    handle.spawn(server);
+    info!("Listening for webhook updates on {}{}", addr, settings.path);
+
+    let updates = receiver.map_err(|_| format_err!("Webhook server has stopped"));
+    Ok(Box::new(updates))
+}
+
+#[derive(Clone)]
+struct WebhookService {
+    path: String,
+    secret: String,
+    updates: UnboundedSender<ChatUpdate>,
+}
+
+impl WebhookService {
+    fn check_request(
+        &self,
+        method: &Method,
+        path: &str,
+        secret: Option<&[u8]>,
+    ) -> Result<(), StatusCode> {
+        if path != self.path {
+            return Err(StatusCode::NotFound);
+        }
+
+        if *method != Method::Post {
+            return Err(StatusCode::MethodNotAllowed);
+        }
+
+        if self.secret.is_empty() {
+            return Ok(());
+        }
+
+        match secret {
+            Some(secret) if fixed_time_eq(secret, self.secret.as_bytes()) => Ok(()),
+            _ => Err(StatusCode::Unauthorized),
+        }
+    }
+}
+
+impl Service for WebhookService {
+    type Request = Request;
+    type Response = Response;
+    type Error = hyper::Error;
+    type Future = Box<dyn Future<Item = Response, Error = hyper::Error>>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        let secret = request
+            .headers()
+            .get_raw(SECRET_TOKEN_HEADER)
+            .and_then(|raw| raw.one());
+
+        if let Err(status) = self.check_request(request.method(), request.path(), secret) {
+            warn!("Rejected webhook request"; "status" => status.as_u16());
+            return Box::new(future::ok(Response::new().with_status(status)));
+        }
+
+        if let Some(&ContentLength(length)) = request.headers().get::<ContentLength>() {
+            if length > MAX_BODY_SIZE as u64 {
+                warn!("Rejected webhook request with a body of {} bytes", length);
+                return Box::new(future::ok(
+                    Response::new().with_status(StatusCode::PayloadTooLarge),
+                ));
+            }
+        }
+
+        let updates = self.updates.clone();
+        let response = read_body(request.body(), MAX_BODY_SIZE).then(move |body| {
+            let body = match body {
+                Ok(body) => body,
+                Err(BodyError::TooLarge) => {
+                    warn!(
+                        "Rejected webhook request with a body of over {} bytes",
+                        MAX_BODY_SIZE
+                    );
+                    return Ok(Response::new().with_status(StatusCode::PayloadTooLarge));
+                }
+                Err(BodyError::Hyper(err)) => return Err(err),
+            };
+
+            // Telegram keeps retrying updates that are not acknowledged, so unparseable ones are
+            // logged and dropped instead of being rejected.
+            match serde_json::from_slice::<ChatUpdate>(&body) {
+                Ok(update) => {
+                    if updates.unbounded_send(update).is_err() {
+                        error!("Dropped webhook update because the bot has stopped");
+                    }
+                }
+                Err(err) => warn!("Ignored unparseable webhook update: {}", err),
+            }
+
+            Ok(Response::new().with_status(StatusCode::Ok))
+        });
+
+        Box::new(response)
+    }
+}
+
+/// Why the body of a webhook request was not read.
+#[derive(Debug)]
+enum BodyError {
+    TooLarge,
+    Hyper(hyper::Error),
+}
+
+impl From<hyper::Error> for BodyError {
+    fn from(err: hyper::Error) -> BodyError {
+        BodyError::Hyper(err)
+    }
+}
+
+/// Reads the body of a request, giving up as soon as it grows past `limit` bytes, so that a body
+/// without a `Content-Length` cannot fill up the memory either.
+fn read_body<S>(body: S, limit: usize) -> impl Future<Item = Vec<u8>, Error = BodyError>
+where
+    S: Stream<Error = hyper::Error>,
+    S::Item: AsRef<[u8]>,
+{
+    body.from_err().fold(Vec::new(), move |mut body, chunk| {
+        let chunk = chunk.as_ref();
+        if body.len() + chunk.len() > limit {
+            return Err(BodyError::TooLarge);
+        }
+        body.extend_from_slice(chunk);
+        Ok(body)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn create_service(secret: &str) -> WebhookService {
+        let (sender, _) = mpsc::unbounded();
+        WebhookService {
+            path: "/telegram".to_string(),
+            secret: secret.to_string(),
+            updates: sender,
+        }
+    }
+
+    #[test]
+    fn test_check_request_accepts_valid_request() {
+        let service = create_service("secret");
+        let actual = service.check_request(&Method::Post, "/telegram", Some(b"secret"));
+        assert_eq!(Ok(()), actual);
+    }
+
+    #[test]
+    fn test_check_request_without_configured_secret() {
+        let service = create_service("");
+        let actual = service.check_request(&Method::Post, "/telegram", None);
+        assert_eq!(Ok(()), actual);
+    }
+
+    #[test]
+    fn test_check_request_rejects_wrong_path_or_method() {
+        let service = create_service("secret");
+        assert_eq!(
+            Err(StatusCode::NotFound),
+            service.check_request(&Method::Post, "/other", Some(b"secret"))
+        );
+        assert_eq!(
+            Err(StatusCode::MethodNotAllowed),
+            service.check_request(&Method::Get, "/telegram", Some(b"secret"))
+        );
+    }
+
+    #[test]
+    fn test_check_request_rejects_invalid_secret() {
+        let service = create_service("secret");
+        assert_eq!(
+            Err(StatusCode::Unauthorized),
+            service.check_request(&Method::Post, "/telegram", Some(b"wrong"))
+        );
+        assert_eq!(
+            Err(StatusCode::Unauthorized),
+            service.check_request(&Method::Post, "/telegram", None)
+        );
+    }
+
+    #[test]
+    fn test_read_body_concatenates_chunks() {
+        let body =
+            stream::iter_ok::<_, hyper::Error>(vec![b"{\"update_id\"".to_vec(), b": 1}".to_vec()]);
+        let actual = read_body(body, 16).wait().unwrap();
+        assert_eq!(b"{\"update_id\": 1}".to_vec(), actual);
+    }
+
+    #[test]
+    fn test_read_body_rejects_body_over_limit() {
+        let body = stream::iter_ok::<_, hyper::Error>(vec![vec![b'x'; 10], vec![b'x'; 10]]);
+        match read_body(body, 16).wait() {
+            Err(BodyError::TooLarge) => {}
+            other => panic!("Expected the body to be too large, got {:?}", other),
+        }
+    }
+}
@@ -1,6 +1,15 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use slog::Logger;
 use slog_scope;
 
+use crate::bot::{
+    dispatch, report_sent, tick, BotResult, ChatCommand, ChatMessage, ChatTransport, HandlerResult,
+    PinChange, Reply, SentMessage, TickResult,
+};
+use crate::models::MessageId;
+
 pub fn with_test_logger<R, F>(func: F) -> R
 where
     F: Fn(&Logger) -> R,
@@ -8,9 +17,74 @@ where
     func(&slog_scope::logger())
 }
 
+/// A `ChatTransport` that feeds a fixed list of commands to the bot, runs a single tick, and
+/// records the replies and scheduled messages. Tagged replies are reported as sent with the
+/// number of the reply as their message id.
+#[derive(Default)]
+pub struct FakeTransport {
+    pub commands: Vec<ChatCommand>,
+    pub replies: Rc<RefCell<Vec<Reply>>>,
+    pub messages: Rc<RefCell<Vec<ChatMessage>>>,
+}
+
+impl FakeTransport {
+    pub fn new(commands: Vec<ChatCommand>) -> FakeTransport {
+        FakeTransport {
+            commands,
+            ..Default::default()
+        }
+    }
+}
+
+impl ChatTransport for FakeTransport {
+    fn run<F, T, S>(&self, handler: F, ticker: T, on_sent: S) -> BotResult
+    where
+        F: Fn(ChatCommand) -> HandlerResult,
+        T: Fn() -> TickResult,
+        S: Fn(SentMessage) -> BotResult,
+    {
+        let logger = slog_scope::logger();
+        for command in self.commands.iter().cloned() {
+            let chat_id = command.chat_id;
+            if let Some(reply) = dispatch(&handler, command, &logger) {
+                let (tag, pinned) = (reply.tag, reply.pin == Some(PinChange::Reply));
+                self.replies.borrow_mut().push(reply);
+
+                if let Some(tag) = tag {
+                    let message_id = self.replies.borrow().len() as MessageId;
+                    let message = SentMessage {
+                        chat_id,
+                        message_id,
+                        tag,
+                        pinned,
+                    };
+                    report_sent(&on_sent, message);
+                }
+            }
+        }
+
+        self.messages.borrow_mut().extend(tick(&ticker));
+
+        Ok(())
+    }
+}
+
 pub mod factories {
+    use crate::bot::ChatCommand;
     use crate::models::{AttendanceStatus::*, CallStatus::*, *};
 
+    /// A command sent by user 1 in chat 2 without naming the bot, as most commands are.
+    pub fn create_command(command: &str, params: &str) -> ChatCommand {
+        ChatCommand {
+            chat_id: 2,
+            user_id: 1,
+            username: "User 1".to_string(),
+            command: command.to_string(),
+            command_params: params.to_string(),
+            addressed: false,
+        }
+    }
+
     pub fn create_call() -> RollCall {
         let now = chrono::Utc::now().naive_local();
 
@@ -22,6 +96,9 @@ pub mod factories {
             quiet: false,
             created_at: now,
             updated_at: now,
+            attendance_limit: None,
+            board_message_id: None,
+            pinned_message_id: None,
         }
     }
 
@@ -32,6 +109,41 @@ pub mod factories {
         }
     }
 
+    pub fn create_scheduled_call(
+        start_at: chrono::NaiveDateTime,
+        end_at: Option<chrono::NaiveDateTime>,
+    ) -> ScheduledCall {
+        let now = chrono::Utc::now().naive_local();
+
+        ScheduledCall {
+            id: 1,
+            chat_id: 2,
+            status: ScheduleStatus::Pending,
+            title: "call title".to_string(),
+            start_at,
+            end_at,
+            roll_call_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn create_recurring_call() -> RecurringCall {
+        let now = chrono::Utc::now().naive_local();
+
+        RecurringCall {
+            id: 1,
+            chat_id: 2,
+            title: "Football".to_string(),
+            weekday: 1,
+            start_time: chrono::NaiveTime::from_hms(18, 0, 0),
+            next_at: chrono::NaiveDate::from_ymd(2019, 4, 9).and_hms(18, 0, 0),
+            created_at: now,
+            updated_at: now,
+            timezone: "UTC".to_string(),
+        }
+    }
+
     pub fn create_responses() -> Vec<RollCallResponse> {
         let now = chrono::Utc::now().naive_local();
 
@@ -46,6 +158,7 @@ pub mod factories {
                 reason: Some("will come".to_string()),
                 created_at: now,
                 updated_at: now,
+                guests: 0,
             },
             RollCallResponse {
                 id: 2,
@@ -57,6 +170,7 @@ pub mod factories {
                 reason: Some("won't come".to_string()),
                 created_at: now,
                 updated_at: now,
+                guests: 0,
             },
             RollCallResponse {
                 id: 3,
@@ -68,6 +182,7 @@ pub mod factories {
                 reason: Some("also will come".to_string()),
                 created_at: now,
                 updated_at: now,
+                guests: 0,
             },
             RollCallResponse {
                 id: 4,
@@ -79,7 +194,20 @@ pub mod factories {
                 reason: Some("might come".to_string()),
                 created_at: now,
                 updated_at: now,
+                guests: 0,
             },
         ]
     }
+
+    pub fn create_response_update(
+        call_with_responses: CallWithResponses,
+        status: AttendanceStatus,
+    ) -> ResponseUpdate {
+        ResponseUpdate {
+            call_with_responses,
+            status,
+            promoted: vec![],
+            refused_guests: 0,
+        }
+    }
 }